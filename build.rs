@@ -0,0 +1,23 @@
+//! Build script embedding the git commit the binary was built from
+//!
+//! `cad-dsl version` prints it so bug reports can name the exact build.
+//! Builds outside a git checkout (e.g. from a source tarball) report
+//! `unknown`.
+
+use std::process::Command;
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=CAD_DSL_GIT_COMMIT={}", commit);
+    // Re-run when HEAD moves to another branch or commit
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
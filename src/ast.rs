@@ -8,6 +8,7 @@
 //! - `span`: Span access trait for AST nodes
 //! - `types`: Type annotations and statement definitions
 //! - `expr`: Expression AST with type-safe operator precedence
+//! - `program`: Imports, top-level definitions (sketches, structs, impls, functions) and the program root
 //! - `display`: Display trait implementations for pretty-printing
 //! - `conversions`: Type conversions for parser convenience
 //! - `tests`: Unit tests for AST functionality
@@ -19,6 +20,7 @@
 mod conversions;
 mod display;
 pub mod expr;
+pub mod program;
 pub mod span;
 pub mod types;
 
@@ -33,7 +35,15 @@ mod tests;
 pub use span::HasSpan;
 
 // Re-export types
-pub use types::{Stmt, Type};
+pub use types::{CompoundOp, Stmt, Type};
+
+// Re-export top-level definitions
+pub use program::{
+    FieldDef, FunctionDef, ImplBlock, ImportDecl, Item, Param, Program, SketchDef, SketchItem,
+    StructDef, Visibility,
+};
 
 // Re-export all expression types
-pub use expr::{AddLhs, AddRhs, Atom, CmpLhs, CmpRhs, Expr, MulLhs, MulRhs, PowLhs, PowRhs};
+pub use expr::{
+    AddLhs, AddRhs, Atom, CmpLhs, CmpRhs, Expr, MatchArm, MulLhs, MulRhs, Pattern, PowLhs, PowRhs,
+};
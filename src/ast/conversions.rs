@@ -16,11 +16,13 @@ impl<'src> From<AddLhs<'src>> for CmpRhs<'src> {
             AddLhs::Mod { lhs, rhs, span } => CmpRhs::Mod { lhs, rhs, span },
             AddLhs::Pow { lhs, rhs, span } => CmpRhs::Pow { lhs, rhs, span },
             AddLhs::Neg { inner, span } => CmpRhs::Neg { inner, span },
+            AddLhs::BitNot { inner, span } => CmpRhs::BitNot { inner, span },
             AddLhs::Ref { inner, span } => CmpRhs::Ref { inner, span },
             AddLhs::Var { name, span } => CmpRhs::Var { name, span },
-            AddLhs::IntLit { value, span } => CmpRhs::IntLit { value, span },
-            AddLhs::FloatLit { value, span } => CmpRhs::FloatLit { value, span },
+            AddLhs::IntLit { value, text, span } => CmpRhs::IntLit { value, text, span },
+            AddLhs::FloatLit { value, text, span } => CmpRhs::FloatLit { value, text, span },
             AddLhs::BoolLit { value, span } => CmpRhs::BoolLit { value, span },
+            AddLhs::StrLit { value, span } => CmpRhs::StrLit { value, span },
             AddLhs::Call { name, args, span } => CmpRhs::Call { name, args, span },
             AddLhs::MethodCall {
                 receiver,
@@ -44,6 +46,26 @@ impl<'src> From<AddLhs<'src>> for CmpRhs<'src> {
             },
             AddLhs::ArrayLit { elements, span } => CmpRhs::ArrayLit { elements, span },
             AddLhs::StructLit { name, fields, span } => CmpRhs::StructLit { name, fields, span },
+            AddLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => CmpRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            AddLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => CmpRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -60,11 +82,13 @@ impl<'src> From<AddLhs<'src>> for CmpLhs<'src> {
             AddLhs::Mod { lhs, rhs, span } => CmpLhs::Mod { lhs, rhs, span },
             AddLhs::Pow { lhs, rhs, span } => CmpLhs::Pow { lhs, rhs, span },
             AddLhs::Neg { inner, span } => CmpLhs::Neg { inner, span },
+            AddLhs::BitNot { inner, span } => CmpLhs::BitNot { inner, span },
             AddLhs::Ref { inner, span } => CmpLhs::Ref { inner, span },
             AddLhs::Var { name, span } => CmpLhs::Var { name, span },
-            AddLhs::IntLit { value, span } => CmpLhs::IntLit { value, span },
-            AddLhs::FloatLit { value, span } => CmpLhs::FloatLit { value, span },
+            AddLhs::IntLit { value, text, span } => CmpLhs::IntLit { value, text, span },
+            AddLhs::FloatLit { value, text, span } => CmpLhs::FloatLit { value, text, span },
             AddLhs::BoolLit { value, span } => CmpLhs::BoolLit { value, span },
+            AddLhs::StrLit { value, span } => CmpLhs::StrLit { value, span },
             AddLhs::Call { name, args, span } => CmpLhs::Call { name, args, span },
             AddLhs::MethodCall {
                 receiver,
@@ -88,6 +112,26 @@ impl<'src> From<AddLhs<'src>> for CmpLhs<'src> {
             },
             AddLhs::ArrayLit { elements, span } => CmpLhs::ArrayLit { elements, span },
             AddLhs::StructLit { name, fields, span } => CmpLhs::StructLit { name, fields, span },
+            AddLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => CmpLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            AddLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => CmpLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -97,9 +141,10 @@ impl<'src> From<Atom<'src>> for MulRhs<'src> {
     fn from(atom: Atom<'src>) -> Self {
         match atom {
             Atom::Var { name, span } => MulRhs::Var { name, span },
-            Atom::IntLit { value, span } => MulRhs::IntLit { value, span },
-            Atom::FloatLit { value, span } => MulRhs::FloatLit { value, span },
+            Atom::IntLit { value, text, span } => MulRhs::IntLit { value, text, span },
+            Atom::FloatLit { value, text, span } => MulRhs::FloatLit { value, text, span },
             Atom::BoolLit { value, span } => MulRhs::BoolLit { value, span },
+            Atom::StrLit { value, span } => MulRhs::StrLit { value, span },
             Atom::Call { name, args, span } => MulRhs::Call { name, args, span },
             Atom::MethodCall {
                 receiver,
@@ -123,6 +168,26 @@ impl<'src> From<Atom<'src>> for MulRhs<'src> {
             },
             Atom::ArrayLit { elements, span } => MulRhs::ArrayLit { elements, span },
             Atom::StructLit { name, fields, span } => MulRhs::StructLit { name, fields, span },
+            Atom::Match {
+                scrutinee,
+                arms,
+                span,
+            } => MulRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            Atom::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => MulRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -132,9 +197,10 @@ impl<'src> From<Atom<'src>> for MulLhs<'src> {
     fn from(atom: Atom<'src>) -> Self {
         match atom {
             Atom::Var { name, span } => MulLhs::Var { name, span },
-            Atom::IntLit { value, span } => MulLhs::IntLit { value, span },
-            Atom::FloatLit { value, span } => MulLhs::FloatLit { value, span },
+            Atom::IntLit { value, text, span } => MulLhs::IntLit { value, text, span },
+            Atom::FloatLit { value, text, span } => MulLhs::FloatLit { value, text, span },
             Atom::BoolLit { value, span } => MulLhs::BoolLit { value, span },
+            Atom::StrLit { value, span } => MulLhs::StrLit { value, span },
             Atom::Call { name, args, span } => MulLhs::Call { name, args, span },
             Atom::MethodCall {
                 receiver,
@@ -158,6 +224,26 @@ impl<'src> From<Atom<'src>> for MulLhs<'src> {
             },
             Atom::ArrayLit { elements, span } => MulLhs::ArrayLit { elements, span },
             Atom::StructLit { name, fields, span } => MulLhs::StructLit { name, fields, span },
+            Atom::Match {
+                scrutinee,
+                arms,
+                span,
+            } => MulLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            Atom::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => MulLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -172,11 +258,13 @@ impl<'src> From<MulLhs<'src>> for AddRhs<'src> {
             MulLhs::Mod { lhs, rhs, span } => AddRhs::Mod { lhs, rhs, span },
             MulLhs::Pow { lhs, rhs, span } => AddRhs::Pow { lhs, rhs, span },
             MulLhs::Neg { inner, span } => AddRhs::Neg { inner, span },
+            MulLhs::BitNot { inner, span } => AddRhs::BitNot { inner, span },
             MulLhs::Ref { inner, span } => AddRhs::Ref { inner, span },
             MulLhs::Var { name, span } => AddRhs::Var { name, span },
-            MulLhs::IntLit { value, span } => AddRhs::IntLit { value, span },
-            MulLhs::FloatLit { value, span } => AddRhs::FloatLit { value, span },
+            MulLhs::IntLit { value, text, span } => AddRhs::IntLit { value, text, span },
+            MulLhs::FloatLit { value, text, span } => AddRhs::FloatLit { value, text, span },
             MulLhs::BoolLit { value, span } => AddRhs::BoolLit { value, span },
+            MulLhs::StrLit { value, span } => AddRhs::StrLit { value, span },
             MulLhs::Call { name, args, span } => AddRhs::Call { name, args, span },
             MulLhs::MethodCall {
                 receiver,
@@ -200,6 +288,26 @@ impl<'src> From<MulLhs<'src>> for AddRhs<'src> {
             },
             MulLhs::ArrayLit { elements, span } => AddRhs::ArrayLit { elements, span },
             MulLhs::StructLit { name, fields, span } => AddRhs::StructLit { name, fields, span },
+            MulLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => AddRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            MulLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => AddRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -214,11 +322,13 @@ impl<'src> From<MulLhs<'src>> for AddLhs<'src> {
             MulLhs::Mod { lhs, rhs, span } => AddLhs::Mod { lhs, rhs, span },
             MulLhs::Pow { lhs, rhs, span } => AddLhs::Pow { lhs, rhs, span },
             MulLhs::Neg { inner, span } => AddLhs::Neg { inner, span },
+            MulLhs::BitNot { inner, span } => AddLhs::BitNot { inner, span },
             MulLhs::Ref { inner, span } => AddLhs::Ref { inner, span },
             MulLhs::Var { name, span } => AddLhs::Var { name, span },
-            MulLhs::IntLit { value, span } => AddLhs::IntLit { value, span },
-            MulLhs::FloatLit { value, span } => AddLhs::FloatLit { value, span },
+            MulLhs::IntLit { value, text, span } => AddLhs::IntLit { value, text, span },
+            MulLhs::FloatLit { value, text, span } => AddLhs::FloatLit { value, text, span },
             MulLhs::BoolLit { value, span } => AddLhs::BoolLit { value, span },
+            MulLhs::StrLit { value, span } => AddLhs::StrLit { value, span },
             MulLhs::Call { name, args, span } => AddLhs::Call { name, args, span },
             MulLhs::MethodCall {
                 receiver,
@@ -242,6 +352,26 @@ impl<'src> From<MulLhs<'src>> for AddLhs<'src> {
             },
             MulLhs::ArrayLit { elements, span } => AddLhs::ArrayLit { elements, span },
             MulLhs::StructLit { name, fields, span } => AddLhs::StructLit { name, fields, span },
+            MulLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => AddLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            MulLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => AddLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -251,9 +381,10 @@ impl<'src> From<Atom<'src>> for PowLhs<'src> {
     fn from(atom: Atom<'src>) -> Self {
         match atom {
             Atom::Var { name, span } => PowLhs::Var { name, span },
-            Atom::IntLit { value, span } => PowLhs::IntLit { value, span },
-            Atom::FloatLit { value, span } => PowLhs::FloatLit { value, span },
+            Atom::IntLit { value, text, span } => PowLhs::IntLit { value, text, span },
+            Atom::FloatLit { value, text, span } => PowLhs::FloatLit { value, text, span },
             Atom::BoolLit { value, span } => PowLhs::BoolLit { value, span },
+            Atom::StrLit { value, span } => PowLhs::StrLit { value, span },
             Atom::Call { name, args, span } => PowLhs::Call { name, args, span },
             Atom::MethodCall {
                 receiver,
@@ -277,6 +408,26 @@ impl<'src> From<Atom<'src>> for PowLhs<'src> {
             },
             Atom::ArrayLit { elements, span } => PowLhs::ArrayLit { elements, span },
             Atom::StructLit { name, fields, span } => PowLhs::StructLit { name, fields, span },
+            Atom::Match {
+                scrutinee,
+                arms,
+                span,
+            } => PowLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            Atom::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => PowLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -286,9 +437,10 @@ impl<'src> From<Atom<'src>> for PowRhs<'src> {
     fn from(atom: Atom<'src>) -> Self {
         match atom {
             Atom::Var { name, span } => PowRhs::Var { name, span },
-            Atom::IntLit { value, span } => PowRhs::IntLit { value, span },
-            Atom::FloatLit { value, span } => PowRhs::FloatLit { value, span },
+            Atom::IntLit { value, text, span } => PowRhs::IntLit { value, text, span },
+            Atom::FloatLit { value, text, span } => PowRhs::FloatLit { value, text, span },
             Atom::BoolLit { value, span } => PowRhs::BoolLit { value, span },
+            Atom::StrLit { value, span } => PowRhs::StrLit { value, span },
             Atom::Call { name, args, span } => PowRhs::Call { name, args, span },
             Atom::MethodCall {
                 receiver,
@@ -312,6 +464,26 @@ impl<'src> From<Atom<'src>> for PowRhs<'src> {
             },
             Atom::ArrayLit { elements, span } => PowRhs::ArrayLit { elements, span },
             Atom::StructLit { name, fields, span } => PowRhs::StructLit { name, fields, span },
+            Atom::Match {
+                scrutinee,
+                arms,
+                span,
+            } => PowRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            Atom::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => PowRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -322,11 +494,13 @@ impl<'src> From<PowLhs<'src>> for PowRhs<'src> {
         match pow {
             PowLhs::Paren { inner, span } => PowRhs::Paren { inner, span },
             PowLhs::Neg { inner, span } => PowRhs::Neg { inner, span },
+            PowLhs::BitNot { inner, span } => PowRhs::BitNot { inner, span },
             PowLhs::Ref { inner, span } => PowRhs::Ref { inner, span },
             PowLhs::Var { name, span } => PowRhs::Var { name, span },
-            PowLhs::IntLit { value, span } => PowRhs::IntLit { value, span },
-            PowLhs::FloatLit { value, span } => PowRhs::FloatLit { value, span },
+            PowLhs::IntLit { value, text, span } => PowRhs::IntLit { value, text, span },
+            PowLhs::FloatLit { value, text, span } => PowRhs::FloatLit { value, text, span },
             PowLhs::BoolLit { value, span } => PowRhs::BoolLit { value, span },
+            PowLhs::StrLit { value, span } => PowRhs::StrLit { value, span },
             PowLhs::Call { name, args, span } => PowRhs::Call { name, args, span },
             PowLhs::MethodCall {
                 receiver,
@@ -350,6 +524,26 @@ impl<'src> From<PowLhs<'src>> for PowRhs<'src> {
             },
             PowLhs::ArrayLit { elements, span } => PowRhs::ArrayLit { elements, span },
             PowLhs::StructLit { name, fields, span } => PowRhs::StructLit { name, fields, span },
+            PowLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => PowRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            PowLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => PowRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -360,11 +554,13 @@ impl<'src> From<PowLhs<'src>> for MulRhs<'src> {
         match pow {
             PowLhs::Paren { inner, span } => MulRhs::Paren { inner, span },
             PowLhs::Neg { inner, span } => MulRhs::Neg { inner, span },
+            PowLhs::BitNot { inner, span } => MulRhs::BitNot { inner, span },
             PowLhs::Ref { inner, span } => MulRhs::Ref { inner, span },
             PowLhs::Var { name, span } => MulRhs::Var { name, span },
-            PowLhs::IntLit { value, span } => MulRhs::IntLit { value, span },
-            PowLhs::FloatLit { value, span } => MulRhs::FloatLit { value, span },
+            PowLhs::IntLit { value, text, span } => MulRhs::IntLit { value, text, span },
+            PowLhs::FloatLit { value, text, span } => MulRhs::FloatLit { value, text, span },
             PowLhs::BoolLit { value, span } => MulRhs::BoolLit { value, span },
+            PowLhs::StrLit { value, span } => MulRhs::StrLit { value, span },
             PowLhs::Call { name, args, span } => MulRhs::Call { name, args, span },
             PowLhs::MethodCall {
                 receiver,
@@ -388,6 +584,26 @@ impl<'src> From<PowLhs<'src>> for MulRhs<'src> {
             },
             PowLhs::ArrayLit { elements, span } => MulRhs::ArrayLit { elements, span },
             PowLhs::StructLit { name, fields, span } => MulRhs::StructLit { name, fields, span },
+            PowLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => MulRhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            PowLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => MulRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
@@ -398,11 +614,13 @@ impl<'src> From<PowLhs<'src>> for MulLhs<'src> {
         match pow {
             PowLhs::Paren { inner, span } => MulLhs::Paren { inner, span },
             PowLhs::Neg { inner, span } => MulLhs::Neg { inner, span },
+            PowLhs::BitNot { inner, span } => MulLhs::BitNot { inner, span },
             PowLhs::Ref { inner, span } => MulLhs::Ref { inner, span },
             PowLhs::Var { name, span } => MulLhs::Var { name, span },
-            PowLhs::IntLit { value, span } => MulLhs::IntLit { value, span },
-            PowLhs::FloatLit { value, span } => MulLhs::FloatLit { value, span },
+            PowLhs::IntLit { value, text, span } => MulLhs::IntLit { value, text, span },
+            PowLhs::FloatLit { value, text, span } => MulLhs::FloatLit { value, text, span },
             PowLhs::BoolLit { value, span } => MulLhs::BoolLit { value, span },
+            PowLhs::StrLit { value, span } => MulLhs::StrLit { value, span },
             PowLhs::Call { name, args, span } => MulLhs::Call { name, args, span },
             PowLhs::MethodCall {
                 receiver,
@@ -426,6 +644,26 @@ impl<'src> From<PowLhs<'src>> for MulLhs<'src> {
             },
             PowLhs::ArrayLit { elements, span } => MulLhs::ArrayLit { elements, span },
             PowLhs::StructLit { name, fields, span } => MulLhs::StructLit { name, fields, span },
+            PowLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            } => MulLhs::Match {
+                scrutinee,
+                arms,
+                span,
+            },
+            PowLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => MulLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            },
         }
     }
 }
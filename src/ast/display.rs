@@ -1,4 +1,5 @@
 use crate::ast::expr::*;
+use crate::lexer::escape_string;
 
 // ============================================================================
 // Display Implementations
@@ -7,10 +8,22 @@ use crate::ast::expr::*;
 impl<'src> std::fmt::Display for Expr<'src> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Expr::Pipe { lhs, rhs_fn, .. } => write!(f, "({} |> {})", lhs, rhs_fn),
+            Expr::Range { start, end, .. } => write!(f, "({}..{})", start, end),
             Expr::And { lhs, rhs, .. } => write!(f, "({} and {})", lhs, rhs),
             Expr::Or { lhs, rhs, .. } => write!(f, "({} or {})", lhs, rhs),
+            Expr::BitAnd { lhs, rhs, .. } => write!(f, "({} & {})", lhs, rhs),
+            Expr::BitOr { lhs, rhs, .. } => write!(f, "({} | {})", lhs, rhs),
+            Expr::Shl { lhs, rhs, .. } => write!(f, "({} << {})", lhs, rhs),
+            Expr::Shr { lhs, rhs, .. } => write!(f, "({} >> {})", lhs, rhs),
             Expr::Eq { lhs, rhs, .. } => write!(f, "({} == {})", lhs, rhs),
             Expr::NotEq { lhs, rhs, .. } => write!(f, "({} != {})", lhs, rhs),
+            Expr::Lt { lhs, rhs, .. } => write!(f, "({} < {})", lhs, rhs),
+            Expr::Gt { lhs, rhs, .. } => write!(f, "({} > {})", lhs, rhs),
+            Expr::LtEq { lhs, rhs, .. } => write!(f, "({} <= {})", lhs, rhs),
+            Expr::GtEq { lhs, rhs, .. } => write!(f, "({} >= {})", lhs, rhs),
+            Expr::In { lhs, rhs, .. } => write!(f, "({} in {})", lhs, rhs),
+            Expr::NotIn { lhs, rhs, .. } => write!(f, "({} not in {})", lhs, rhs),
             Expr::Add { lhs, rhs, .. } => write!(f, "({} + {})", lhs, rhs),
             Expr::Sub { lhs, rhs, .. } => write!(f, "({} - {})", lhs, rhs),
             Expr::Paren { inner, .. } => write!(f, "({})", inner),
@@ -19,11 +32,13 @@ impl<'src> std::fmt::Display for Expr<'src> {
             Expr::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             Expr::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             Expr::Neg { inner, .. } => write!(f, "(-{})", inner),
+            Expr::BitNot { inner, .. } => write!(f, "(~{})", inner),
             Expr::Ref { inner, .. } => write!(f, "(&{})", inner),
             Expr::Var { name, .. } => write!(f, "{}", name),
             Expr::IntLit { value, .. } => write!(f, "{}", value),
             Expr::FloatLit { value, .. } => write!(f, "{}", value),
             Expr::BoolLit { value, .. } => write!(f, "{}", value),
+            Expr::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             Expr::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -52,6 +67,16 @@ impl<'src> std::fmt::Display for Expr<'src> {
             Expr::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             Expr::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -72,6 +97,18 @@ impl<'src> std::fmt::Display for Expr<'src> {
                 }
                 write!(f, " }}")
             }
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -81,8 +118,18 @@ impl<'src> std::fmt::Display for CmpLhs<'src> {
         match self {
             CmpLhs::And { lhs, rhs, .. } => write!(f, "({} and {})", lhs, rhs),
             CmpLhs::Or { lhs, rhs, .. } => write!(f, "({} or {})", lhs, rhs),
+            CmpLhs::BitAnd { lhs, rhs, .. } => write!(f, "({} & {})", lhs, rhs),
+            CmpLhs::BitOr { lhs, rhs, .. } => write!(f, "({} | {})", lhs, rhs),
+            CmpLhs::Shl { lhs, rhs, .. } => write!(f, "({} << {})", lhs, rhs),
+            CmpLhs::Shr { lhs, rhs, .. } => write!(f, "({} >> {})", lhs, rhs),
             CmpLhs::Eq { lhs, rhs, .. } => write!(f, "({} == {})", lhs, rhs),
             CmpLhs::NotEq { lhs, rhs, .. } => write!(f, "({} != {})", lhs, rhs),
+            CmpLhs::Lt { lhs, rhs, .. } => write!(f, "({} < {})", lhs, rhs),
+            CmpLhs::Gt { lhs, rhs, .. } => write!(f, "({} > {})", lhs, rhs),
+            CmpLhs::LtEq { lhs, rhs, .. } => write!(f, "({} <= {})", lhs, rhs),
+            CmpLhs::GtEq { lhs, rhs, .. } => write!(f, "({} >= {})", lhs, rhs),
+            CmpLhs::In { lhs, rhs, .. } => write!(f, "({} in {})", lhs, rhs),
+            CmpLhs::NotIn { lhs, rhs, .. } => write!(f, "({} not in {})", lhs, rhs),
             CmpLhs::Add { lhs, rhs, .. } => write!(f, "({} + {})", lhs, rhs),
             CmpLhs::Sub { lhs, rhs, .. } => write!(f, "({} - {})", lhs, rhs),
             CmpLhs::Paren { inner, .. } => write!(f, "({})", inner),
@@ -91,11 +138,13 @@ impl<'src> std::fmt::Display for CmpLhs<'src> {
             CmpLhs::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             CmpLhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             CmpLhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            CmpLhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             CmpLhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             CmpLhs::Var { name, .. } => write!(f, "{}", name),
             CmpLhs::IntLit { value, .. } => write!(f, "{}", value),
             CmpLhs::FloatLit { value, .. } => write!(f, "{}", value),
             CmpLhs::BoolLit { value, .. } => write!(f, "{}", value),
+            CmpLhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             CmpLhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -124,6 +173,16 @@ impl<'src> std::fmt::Display for CmpLhs<'src> {
             CmpLhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            CmpLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             CmpLhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -144,6 +203,18 @@ impl<'src> std::fmt::Display for CmpLhs<'src> {
                 }
                 write!(f, " }}")
             }
+            CmpLhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -159,11 +230,13 @@ impl<'src> std::fmt::Display for CmpRhs<'src> {
             CmpRhs::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             CmpRhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             CmpRhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            CmpRhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             CmpRhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             CmpRhs::Var { name, .. } => write!(f, "{}", name),
             CmpRhs::IntLit { value, .. } => write!(f, "{}", value),
             CmpRhs::FloatLit { value, .. } => write!(f, "{}", value),
             CmpRhs::BoolLit { value, .. } => write!(f, "{}", value),
+            CmpRhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             CmpRhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -192,6 +265,16 @@ impl<'src> std::fmt::Display for CmpRhs<'src> {
             CmpRhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            CmpRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             CmpRhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -212,6 +295,18 @@ impl<'src> std::fmt::Display for CmpRhs<'src> {
                 }
                 write!(f, " }}")
             }
+            CmpRhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -227,11 +322,13 @@ impl<'src> std::fmt::Display for AddLhs<'src> {
             AddLhs::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             AddLhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             AddLhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            AddLhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             AddLhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             AddLhs::Var { name, .. } => write!(f, "{}", name),
             AddLhs::IntLit { value, .. } => write!(f, "{}", value),
             AddLhs::FloatLit { value, .. } => write!(f, "{}", value),
             AddLhs::BoolLit { value, .. } => write!(f, "{}", value),
+            AddLhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             AddLhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -260,6 +357,16 @@ impl<'src> std::fmt::Display for AddLhs<'src> {
             AddLhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            AddLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             AddLhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -280,6 +387,18 @@ impl<'src> std::fmt::Display for AddLhs<'src> {
                 }
                 write!(f, " }}")
             }
+            AddLhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -293,11 +412,13 @@ impl<'src> std::fmt::Display for AddRhs<'src> {
             AddRhs::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             AddRhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             AddRhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            AddRhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             AddRhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             AddRhs::Var { name, .. } => write!(f, "{}", name),
             AddRhs::IntLit { value, .. } => write!(f, "{}", value),
             AddRhs::FloatLit { value, .. } => write!(f, "{}", value),
             AddRhs::BoolLit { value, .. } => write!(f, "{}", value),
+            AddRhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             AddRhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -326,6 +447,16 @@ impl<'src> std::fmt::Display for AddRhs<'src> {
             AddRhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            AddRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             AddRhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -346,6 +477,18 @@ impl<'src> std::fmt::Display for AddRhs<'src> {
                 }
                 write!(f, " }}")
             }
+            AddRhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -359,11 +502,13 @@ impl<'src> std::fmt::Display for MulLhs<'src> {
             MulLhs::Mod { lhs, rhs, .. } => write!(f, "({} % {})", lhs, rhs),
             MulLhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             MulLhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            MulLhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             MulLhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             MulLhs::Var { name, .. } => write!(f, "{}", name),
             MulLhs::IntLit { value, .. } => write!(f, "{}", value),
             MulLhs::FloatLit { value, .. } => write!(f, "{}", value),
             MulLhs::BoolLit { value, .. } => write!(f, "{}", value),
+            MulLhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             MulLhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -392,6 +537,16 @@ impl<'src> std::fmt::Display for MulLhs<'src> {
             MulLhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            MulLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             MulLhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -412,6 +567,18 @@ impl<'src> std::fmt::Display for MulLhs<'src> {
                 }
                 write!(f, " }}")
             }
+            MulLhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -422,11 +589,13 @@ impl<'src> std::fmt::Display for MulRhs<'src> {
             MulRhs::Paren { inner, .. } => write!(f, "({})", inner),
             MulRhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             MulRhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            MulRhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             MulRhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             MulRhs::Var { name, .. } => write!(f, "{}", name),
             MulRhs::IntLit { value, .. } => write!(f, "{}", value),
             MulRhs::FloatLit { value, .. } => write!(f, "{}", value),
             MulRhs::BoolLit { value, .. } => write!(f, "{}", value),
+            MulRhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             MulRhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -455,6 +624,16 @@ impl<'src> std::fmt::Display for MulRhs<'src> {
             MulRhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            MulRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             MulRhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -475,6 +654,18 @@ impl<'src> std::fmt::Display for MulRhs<'src> {
                 }
                 write!(f, " }}")
             }
+            MulRhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -484,11 +675,13 @@ impl<'src> std::fmt::Display for PowLhs<'src> {
         match self {
             PowLhs::Paren { inner, .. } => write!(f, "({})", inner),
             PowLhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            PowLhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             PowLhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             PowLhs::Var { name, .. } => write!(f, "{}", name),
             PowLhs::IntLit { value, .. } => write!(f, "{}", value),
             PowLhs::FloatLit { value, .. } => write!(f, "{}", value),
             PowLhs::BoolLit { value, .. } => write!(f, "{}", value),
+            PowLhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             PowLhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -517,6 +710,16 @@ impl<'src> std::fmt::Display for PowLhs<'src> {
             PowLhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            PowLhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             PowLhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -537,6 +740,18 @@ impl<'src> std::fmt::Display for PowLhs<'src> {
                 }
                 write!(f, " }}")
             }
+            PowLhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -547,11 +762,13 @@ impl<'src> std::fmt::Display for PowRhs<'src> {
             PowRhs::Paren { inner, .. } => write!(f, "({})", inner),
             PowRhs::Pow { lhs, rhs, .. } => write!(f, "({} ^ {})", lhs, rhs),
             PowRhs::Neg { inner, .. } => write!(f, "(-{})", inner),
+            PowRhs::BitNot { inner, .. } => write!(f, "(~{})", inner),
             PowRhs::Ref { inner, .. } => write!(f, "(&{})", inner),
             PowRhs::Var { name, .. } => write!(f, "{}", name),
             PowRhs::IntLit { value, .. } => write!(f, "{}", value),
             PowRhs::FloatLit { value, .. } => write!(f, "{}", value),
             PowRhs::BoolLit { value, .. } => write!(f, "{}", value),
+            PowRhs::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             PowRhs::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -580,6 +797,16 @@ impl<'src> std::fmt::Display for PowRhs<'src> {
             PowRhs::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            PowRhs::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             PowRhs::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -600,6 +827,18 @@ impl<'src> std::fmt::Display for PowRhs<'src> {
                 }
                 write!(f, " }}")
             }
+            PowRhs::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
         }
     }
 }
@@ -611,6 +850,7 @@ impl<'src> std::fmt::Display for Atom<'src> {
             Atom::IntLit { value, .. } => write!(f, "{}", value),
             Atom::FloatLit { value, .. } => write!(f, "{}", value),
             Atom::BoolLit { value, .. } => write!(f, "{}", value),
+            Atom::StrLit { value, .. } => write!(f, "\"{}\"", escape_string(value)),
             Atom::Call { name, args, .. } => {
                 write!(f, "{}(", name)?;
                 for (i, arg) in args.iter().enumerate() {
@@ -639,6 +879,16 @@ impl<'src> std::fmt::Display for Atom<'src> {
             Atom::FieldAccess {
                 receiver, field, ..
             } => write!(f, "{}.{}", receiver, field),
+            Atom::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                condition, then_branch, else_branch
+            ),
             Atom::ArrayLit { elements, .. } => {
                 write!(f, "[")?;
                 for (i, elem) in elements.iter().enumerate() {
@@ -659,6 +909,44 @@ impl<'src> std::fmt::Display for Atom<'src> {
                 }
                 write!(f, " }}")
             }
+            Atom::Match {
+                scrutinee, arms, ..
+            } => {
+                write!(f, "(match {} {{ ", scrutinee)?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arm)?;
+                }
+                write!(f, " }})")
+            }
+        }
+    }
+}
+
+impl<'src> std::fmt::Display for MatchArm<'src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} => {}", self.pattern, self.body)
+    }
+}
+
+impl<'src> std::fmt::Display for Pattern<'src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard { .. } => write!(f, "_"),
+            Pattern::Literal { value } => write!(f, "{}", value),
+            Pattern::Binding { name, .. } => write!(f, "{}", name),
+            Pattern::Struct { name, fields, .. } => {
+                write!(f, "{} {{ ", name)?;
+                for (i, (field_name, pattern)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", field_name, pattern)?;
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
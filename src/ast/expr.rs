@@ -1,5 +1,5 @@
 use crate::ast::span::HasSpan;
-use crate::lexer::Span;
+use crate::span::Span;
 use subenum::subenum;
 
 // ============================================================================
@@ -9,18 +9,18 @@ use subenum::subenum;
 /// Top-level expression type with operator precedence hierarchy
 ///
 /// Hierarchy with separate Left/Right-hand side types:
-/// - Expr: All variants (top-level)
-/// - LogLhs: And, Or, Eq, NotEq, Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (left side of logical ops)
-/// - LogRhs: Paren, Eq, NotEq, Add, Sub, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (right side of logical ops, NO And/Or)
-/// - CmpLhs: Eq, Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (left side of ==)
-/// - CmpRhs: Paren, Add, Sub, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (right side of ==, NO Eq)
-/// - AddLhs: Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (left side of +/-)
-/// - AddRhs: Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (right side of +/-, NO Add/Sub)
-/// - MulLhs: Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit (left side of *//, NO Add/Sub)
-/// - MulRhs: Paren, Pow, Var, IntLit, FloatLit, BoolLit (right side of *//, NO Mul/Div)
-/// - PowLhs: Paren, Var, IntLit, FloatLit, BoolLit (left side of ^, NO operators)
-/// - PowRhs: Paren, Pow, Var, IntLit, FloatLit, BoolLit (right side of ^, allows Pow for right-associativity)
-/// - Atom: Var, IntLit, FloatLit, BoolLit (only literals and variables)
+/// - Expr: All variants (top-level), and the only home of Pipe and Range
+/// - LogLhs: And, Or, Eq, NotEq, Lt, Gt, LtEq, GtEq, Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (left side of logical ops)
+/// - LogRhs: Paren, Eq, NotEq, Lt, Gt, LtEq, GtEq, Add, Sub, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (right side of logical ops, NO And/Or)
+/// - CmpLhs: Eq, NotEq, Lt, Gt, LtEq, GtEq, In, NotIn, Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (left side of ==)
+/// - CmpRhs: Paren, Add, Sub, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (right side of ==, NO comparisons)
+/// - AddLhs: Add, Sub, Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (left side of +/-)
+/// - AddRhs: Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (right side of +/-, NO Add/Sub)
+/// - MulLhs: Paren, Mul, Div, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (left side of *//, NO Add/Sub)
+/// - MulRhs: Paren, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (right side of *//, NO Mul/Div)
+/// - PowLhs: Paren, Var, IntLit, FloatLit, BoolLit, StrLit (left side of ^, NO operators)
+/// - PowRhs: Paren, Pow, Var, IntLit, FloatLit, BoolLit, StrLit (right side of ^, allows Pow for right-associativity)
+/// - Atom: Var, IntLit, FloatLit, BoolLit, StrLit (only literals and variables)
 ///
 /// This ensures:
 /// - Logical RHS cannot contain logical operators (enforces precedence)
@@ -33,6 +33,22 @@ use subenum::subenum;
 #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr<'src> {
+    // Pipeline - in Expr only (lowest precedence, below logical operators)
+    // `lhs |> f(args)` passes lhs as the first argument of f; rhs_fn is
+    // always a Var or Call
+    Pipe {
+        lhs: Box<Expr<'src>>,
+        rhs_fn: Box<Atom<'src>>,
+        span: Span,
+    },
+
+    // Range - in Expr only (below pipelines); `start..end` excludes end
+    Range {
+        start: Box<Expr<'src>>,
+        end: Box<Expr<'src>>,
+        span: Span,
+    },
+
     // Logical AND - in CmpLhs (same level as equality operators)
     // lhs can be And/Or, rhs cannot (enforces left-associativity and precedence)
     #[subenum(CmpLhs)]
@@ -51,6 +67,42 @@ pub enum Expr<'src> {
         span: Span,
     },
 
+    // Bitwise AND - in CmpLhs (between the logical and comparison operators)
+    // lhs can be AND or a shift, rhs only a shift (binds looser than shifts, tighter than OR)
+    #[subenum(CmpLhs)]
+    BitAnd {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Bitwise OR - in CmpLhs (between the logical and comparison operators)
+    // lhs can be any bitwise op, rhs cannot be OR (the loosest bitwise operator)
+    #[subenum(CmpLhs)]
+    BitOr {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Shift left - in CmpLhs (between the logical and comparison operators)
+    // lhs can be a shift, rhs no bitwise op (the tightest bitwise operators)
+    #[subenum(CmpLhs)]
+    Shl {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Shift right - in CmpLhs (between the logical and comparison operators)
+    // lhs can be a shift, rhs no bitwise op (the tightest bitwise operators)
+    #[subenum(CmpLhs)]
+    Shr {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
     // Equality - in CmpLhs only
     // lhs can be Eq, rhs cannot (enforces left-associativity and precedence)
     #[subenum(CmpLhs)]
@@ -69,6 +121,60 @@ pub enum Expr<'src> {
         span: Span,
     },
 
+    // Less Than - in CmpLhs only
+    // lhs can be Lt, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    Lt {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Greater Than - in CmpLhs only
+    // lhs can be Gt, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    Gt {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Less Than or Equal - in CmpLhs only
+    // lhs can be LtEq, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    LtEq {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Greater Than or Equal - in CmpLhs only
+    // lhs can be GtEq, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    GtEq {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Membership (`in`) - in CmpLhs only
+    // lhs can be In, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    In {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
+    // Non-membership (`not in`) - in CmpLhs only
+    // lhs can be NotIn, rhs cannot (enforces left-associativity and precedence)
+    #[subenum(CmpLhs)]
+    NotIn {
+        lhs: Box<CmpLhs<'src>>,
+        rhs: Box<CmpRhs<'src>>,
+        span: Span,
+    },
+
     // Addition - in CmpLhs, CmpRhs, AddLhs
     // lhs can be Add/Sub, rhs cannot (enforces left-associativity and precedence)
     #[subenum(CmpLhs, CmpRhs, AddLhs)]
@@ -132,6 +238,14 @@ pub enum Expr<'src> {
         span: Span,
     },
 
+    // Bitwise NOT - in CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs
+    // Higher precedence than power (binds tighter)
+    #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs)]
+    BitNot {
+        inner: Box<PowLhs<'src>>,
+        span: Span,
+    },
+
     // Unary reference - in CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs
     // Higher precedence than power (binds tighter)
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs)]
@@ -144,18 +258,30 @@ pub enum Expr<'src> {
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
     Var { name: &'src str, span: Span },
 
-    // Integer literal - in all levels
+    // Integer literal, with `text` as written (`0xFF`, `1_000`) - in all levels
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
-    IntLit { value: i32, span: Span },
+    IntLit {
+        value: i32,
+        text: &'src str,
+        span: Span,
+    },
 
-    // Float literal - in all levels
+    // Float literal, with `text` as written (`2.5e-3`) - in all levels
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
-    FloatLit { value: f64, span: Span },
+    FloatLit {
+        value: f64,
+        text: &'src str,
+        span: Span,
+    },
 
     // Boolean literal - in all levels
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
     BoolLit { value: bool, span: Span },
 
+    // String literal, with escape sequences already replaced - in all levels
+    #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
+    StrLit { value: String, span: Span },
+
     // Function call - in all levels (high precedence like atoms)
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
     Call {
@@ -188,6 +314,17 @@ pub enum Expr<'src> {
         span: Span,
     },
 
+    // If expression - in all levels (high precedence like atoms)
+    // Both branches are required since the expression must have a value;
+    // `else if` nests another If as the else branch
+    #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
+    If {
+        condition: Box<Expr<'src>>,
+        then_branch: Box<Expr<'src>>,
+        else_branch: Box<Expr<'src>>,
+        span: Span,
+    },
+
     // Struct literal - in all levels (high precedence like atoms)
     #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
     StructLit {
@@ -195,6 +332,46 @@ pub enum Expr<'src> {
         fields: Vec<(&'src str, Expr<'src>)>,
         span: Span,
     },
+
+    // Match expression - in all levels (high precedence like atoms)
+    // The value of the first arm whose pattern matches the scrutinee
+    #[subenum(CmpLhs, CmpRhs, AddLhs, AddRhs, MulLhs, MulRhs, PowLhs, PowRhs, Atom)]
+    Match {
+        scrutinee: Box<Expr<'src>>,
+        arms: Vec<MatchArm<'src>>,
+        span: Span,
+    },
+}
+
+/// One `pattern => value` arm of a match expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm<'src> {
+    pub pattern: Pattern<'src>,
+    pub body: Expr<'src>,
+    pub span: Span,
+}
+
+/// The left-hand side of a match arm
+/// Examples:
+///   _
+///   0
+///   other
+///   Rect { w, h: 0 }
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern<'src> {
+    /// `_`, which matches anything
+    Wildcard { span: Span },
+    /// An int, float, bool or string literal the scrutinee must equal
+    Literal { value: Atom<'src> },
+    /// A name bound to the whole scrutinee
+    Binding { name: &'src str, span: Span },
+    /// A struct with patterns for some of its fields; the shorthand
+    /// `Circle { r }` binds field `r` to the name `r`
+    Struct {
+        name: &'src str,
+        fields: Vec<(&'src str, Pattern<'src>)>,
+        span: Span,
+    },
 }
 
 // ============================================================================
@@ -204,10 +381,22 @@ pub enum Expr<'src> {
 impl<'src> HasSpan for Expr<'src> {
     fn span(&self) -> Span {
         match self {
+            Expr::Pipe { span, .. } => *span,
+            Expr::Range { span, .. } => *span,
             Expr::And { span, .. } => *span,
             Expr::Or { span, .. } => *span,
+            Expr::BitAnd { span, .. } => *span,
+            Expr::BitOr { span, .. } => *span,
+            Expr::Shl { span, .. } => *span,
+            Expr::Shr { span, .. } => *span,
             Expr::Eq { span, .. } => *span,
             Expr::NotEq { span, .. } => *span,
+            Expr::Lt { span, .. } => *span,
+            Expr::Gt { span, .. } => *span,
+            Expr::LtEq { span, .. } => *span,
+            Expr::GtEq { span, .. } => *span,
+            Expr::In { span, .. } => *span,
+            Expr::NotIn { span, .. } => *span,
             Expr::Add { span, .. } => *span,
             Expr::Sub { span, .. } => *span,
             Expr::Paren { span, .. } => *span,
@@ -216,16 +405,20 @@ impl<'src> HasSpan for Expr<'src> {
             Expr::Mod { span, .. } => *span,
             Expr::Pow { span, .. } => *span,
             Expr::Neg { span, .. } => *span,
+            Expr::BitNot { span, .. } => *span,
             Expr::Ref { span, .. } => *span,
             Expr::Var { span, .. } => *span,
             Expr::IntLit { span, .. } => *span,
             Expr::FloatLit { span, .. } => *span,
             Expr::BoolLit { span, .. } => *span,
+            Expr::StrLit { span, .. } => *span,
             Expr::Call { span, .. } => *span,
             Expr::MethodCall { span, .. } => *span,
             Expr::FieldAccess { span, .. } => *span,
             Expr::ArrayLit { span, .. } => *span,
             Expr::StructLit { span, .. } => *span,
+            Expr::Match { span, .. } => *span,
+            Expr::If { span, .. } => *span,
         }
     }
 }
@@ -235,8 +428,18 @@ impl<'src> HasSpan for CmpLhs<'src> {
         match self {
             CmpLhs::And { span, .. } => *span,
             CmpLhs::Or { span, .. } => *span,
+            CmpLhs::BitAnd { span, .. } => *span,
+            CmpLhs::BitOr { span, .. } => *span,
+            CmpLhs::Shl { span, .. } => *span,
+            CmpLhs::Shr { span, .. } => *span,
             CmpLhs::Eq { span, .. } => *span,
             CmpLhs::NotEq { span, .. } => *span,
+            CmpLhs::Lt { span, .. } => *span,
+            CmpLhs::Gt { span, .. } => *span,
+            CmpLhs::LtEq { span, .. } => *span,
+            CmpLhs::GtEq { span, .. } => *span,
+            CmpLhs::In { span, .. } => *span,
+            CmpLhs::NotIn { span, .. } => *span,
             CmpLhs::Add { span, .. } => *span,
             CmpLhs::Sub { span, .. } => *span,
             CmpLhs::Paren { span, .. } => *span,
@@ -245,16 +448,20 @@ impl<'src> HasSpan for CmpLhs<'src> {
             CmpLhs::Mod { span, .. } => *span,
             CmpLhs::Pow { span, .. } => *span,
             CmpLhs::Neg { span, .. } => *span,
+            CmpLhs::BitNot { span, .. } => *span,
             CmpLhs::Ref { span, .. } => *span,
             CmpLhs::Var { span, .. } => *span,
             CmpLhs::IntLit { span, .. } => *span,
             CmpLhs::FloatLit { span, .. } => *span,
             CmpLhs::BoolLit { span, .. } => *span,
+            CmpLhs::StrLit { span, .. } => *span,
             CmpLhs::Call { span, .. } => *span,
             CmpLhs::MethodCall { span, .. } => *span,
             CmpLhs::FieldAccess { span, .. } => *span,
             CmpLhs::ArrayLit { span, .. } => *span,
             CmpLhs::StructLit { span, .. } => *span,
+            CmpLhs::Match { span, .. } => *span,
+            CmpLhs::If { span, .. } => *span,
         }
     }
 }
@@ -270,16 +477,20 @@ impl<'src> HasSpan for CmpRhs<'src> {
             CmpRhs::Mod { span, .. } => *span,
             CmpRhs::Pow { span, .. } => *span,
             CmpRhs::Neg { span, .. } => *span,
+            CmpRhs::BitNot { span, .. } => *span,
             CmpRhs::Ref { span, .. } => *span,
             CmpRhs::Var { span, .. } => *span,
             CmpRhs::IntLit { span, .. } => *span,
             CmpRhs::FloatLit { span, .. } => *span,
             CmpRhs::BoolLit { span, .. } => *span,
+            CmpRhs::StrLit { span, .. } => *span,
             CmpRhs::Call { span, .. } => *span,
             CmpRhs::MethodCall { span, .. } => *span,
             CmpRhs::FieldAccess { span, .. } => *span,
             CmpRhs::ArrayLit { span, .. } => *span,
             CmpRhs::StructLit { span, .. } => *span,
+            CmpRhs::Match { span, .. } => *span,
+            CmpRhs::If { span, .. } => *span,
         }
     }
 }
@@ -295,16 +506,20 @@ impl<'src> HasSpan for AddLhs<'src> {
             AddLhs::Mod { span, .. } => *span,
             AddLhs::Pow { span, .. } => *span,
             AddLhs::Neg { span, .. } => *span,
+            AddLhs::BitNot { span, .. } => *span,
             AddLhs::Ref { span, .. } => *span,
             AddLhs::Var { span, .. } => *span,
             AddLhs::IntLit { span, .. } => *span,
             AddLhs::FloatLit { span, .. } => *span,
             AddLhs::BoolLit { span, .. } => *span,
+            AddLhs::StrLit { span, .. } => *span,
             AddLhs::Call { span, .. } => *span,
             AddLhs::MethodCall { span, .. } => *span,
             AddLhs::FieldAccess { span, .. } => *span,
             AddLhs::ArrayLit { span, .. } => *span,
             AddLhs::StructLit { span, .. } => *span,
+            AddLhs::Match { span, .. } => *span,
+            AddLhs::If { span, .. } => *span,
         }
     }
 }
@@ -318,16 +533,20 @@ impl<'src> HasSpan for AddRhs<'src> {
             AddRhs::Mod { span, .. } => *span,
             AddRhs::Pow { span, .. } => *span,
             AddRhs::Neg { span, .. } => *span,
+            AddRhs::BitNot { span, .. } => *span,
             AddRhs::Ref { span, .. } => *span,
             AddRhs::Var { span, .. } => *span,
             AddRhs::IntLit { span, .. } => *span,
             AddRhs::FloatLit { span, .. } => *span,
             AddRhs::BoolLit { span, .. } => *span,
+            AddRhs::StrLit { span, .. } => *span,
             AddRhs::Call { span, .. } => *span,
             AddRhs::MethodCall { span, .. } => *span,
             AddRhs::FieldAccess { span, .. } => *span,
             AddRhs::ArrayLit { span, .. } => *span,
             AddRhs::StructLit { span, .. } => *span,
+            AddRhs::Match { span, .. } => *span,
+            AddRhs::If { span, .. } => *span,
         }
     }
 }
@@ -341,16 +560,20 @@ impl<'src> HasSpan for MulLhs<'src> {
             MulLhs::Mod { span, .. } => *span,
             MulLhs::Pow { span, .. } => *span,
             MulLhs::Neg { span, .. } => *span,
+            MulLhs::BitNot { span, .. } => *span,
             MulLhs::Ref { span, .. } => *span,
             MulLhs::Var { span, .. } => *span,
             MulLhs::IntLit { span, .. } => *span,
             MulLhs::FloatLit { span, .. } => *span,
             MulLhs::BoolLit { span, .. } => *span,
+            MulLhs::StrLit { span, .. } => *span,
             MulLhs::Call { span, .. } => *span,
             MulLhs::MethodCall { span, .. } => *span,
             MulLhs::FieldAccess { span, .. } => *span,
             MulLhs::ArrayLit { span, .. } => *span,
             MulLhs::StructLit { span, .. } => *span,
+            MulLhs::Match { span, .. } => *span,
+            MulLhs::If { span, .. } => *span,
         }
     }
 }
@@ -361,16 +584,20 @@ impl<'src> HasSpan for MulRhs<'src> {
             MulRhs::Paren { span, .. } => *span,
             MulRhs::Pow { span, .. } => *span,
             MulRhs::Neg { span, .. } => *span,
+            MulRhs::BitNot { span, .. } => *span,
             MulRhs::Ref { span, .. } => *span,
             MulRhs::Var { span, .. } => *span,
             MulRhs::IntLit { span, .. } => *span,
             MulRhs::FloatLit { span, .. } => *span,
             MulRhs::BoolLit { span, .. } => *span,
+            MulRhs::StrLit { span, .. } => *span,
             MulRhs::Call { span, .. } => *span,
             MulRhs::MethodCall { span, .. } => *span,
             MulRhs::FieldAccess { span, .. } => *span,
             MulRhs::ArrayLit { span, .. } => *span,
             MulRhs::StructLit { span, .. } => *span,
+            MulRhs::Match { span, .. } => *span,
+            MulRhs::If { span, .. } => *span,
         }
     }
 }
@@ -380,16 +607,20 @@ impl<'src> HasSpan for PowLhs<'src> {
         match self {
             PowLhs::Paren { span, .. } => *span,
             PowLhs::Neg { span, .. } => *span,
+            PowLhs::BitNot { span, .. } => *span,
             PowLhs::Ref { span, .. } => *span,
             PowLhs::Var { span, .. } => *span,
             PowLhs::IntLit { span, .. } => *span,
             PowLhs::FloatLit { span, .. } => *span,
             PowLhs::BoolLit { span, .. } => *span,
+            PowLhs::StrLit { span, .. } => *span,
             PowLhs::Call { span, .. } => *span,
             PowLhs::MethodCall { span, .. } => *span,
             PowLhs::FieldAccess { span, .. } => *span,
             PowLhs::ArrayLit { span, .. } => *span,
             PowLhs::StructLit { span, .. } => *span,
+            PowLhs::Match { span, .. } => *span,
+            PowLhs::If { span, .. } => *span,
         }
     }
 }
@@ -400,16 +631,20 @@ impl<'src> HasSpan for PowRhs<'src> {
             PowRhs::Paren { span, .. } => *span,
             PowRhs::Pow { span, .. } => *span,
             PowRhs::Neg { span, .. } => *span,
+            PowRhs::BitNot { span, .. } => *span,
             PowRhs::Ref { span, .. } => *span,
             PowRhs::Var { span, .. } => *span,
             PowRhs::IntLit { span, .. } => *span,
             PowRhs::FloatLit { span, .. } => *span,
             PowRhs::BoolLit { span, .. } => *span,
+            PowRhs::StrLit { span, .. } => *span,
             PowRhs::Call { span, .. } => *span,
             PowRhs::MethodCall { span, .. } => *span,
             PowRhs::FieldAccess { span, .. } => *span,
             PowRhs::ArrayLit { span, .. } => *span,
             PowRhs::StructLit { span, .. } => *span,
+            PowRhs::Match { span, .. } => *span,
+            PowRhs::If { span, .. } => *span,
         }
     }
 }
@@ -421,11 +656,31 @@ impl<'src> HasSpan for Atom<'src> {
             Atom::IntLit { span, .. } => *span,
             Atom::FloatLit { span, .. } => *span,
             Atom::BoolLit { span, .. } => *span,
+            Atom::StrLit { span, .. } => *span,
             Atom::Call { span, .. } => *span,
             Atom::MethodCall { span, .. } => *span,
             Atom::FieldAccess { span, .. } => *span,
             Atom::ArrayLit { span, .. } => *span,
             Atom::StructLit { span, .. } => *span,
+            Atom::Match { span, .. } => *span,
+            Atom::If { span, .. } => *span,
+        }
+    }
+}
+
+impl<'src> HasSpan for MatchArm<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<'src> HasSpan for Pattern<'src> {
+    fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard { span } => *span,
+            Pattern::Literal { value } => value.span(),
+            Pattern::Binding { span, .. } => *span,
+            Pattern::Struct { span, .. } => *span,
         }
     }
 }
@@ -0,0 +1,256 @@
+use crate::ast::expr::Expr;
+use crate::ast::span::HasSpan;
+use crate::ast::types::{Stmt, Type};
+use crate::span::Span;
+use std::path::PathBuf;
+
+// ============================================================================
+// Imports
+// ============================================================================
+
+/// Import of another source file, optionally limited to some of its names
+/// The path is relative to the directory of the importing file, with escape
+/// sequences already replaced
+/// Examples:
+///   import "shapes.cad";
+///   import "shapes.cad" use Point, Line;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl<'src> {
+    pub path: String,
+    pub path_span: Span,
+    pub items: Option<Vec<(&'src str, Span)>>,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for ImportDecl<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+// ============================================================================
+// Visibility
+// ============================================================================
+
+/// Whether a definition can be used from outside its source file
+/// Definitions are private unless prefixed with `pub`
+/// Example:
+///   pub struct Point { pub x: f64, pub y: f64 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    Public,
+    #[default]
+    Private,
+}
+
+// ============================================================================
+// Top-Level Definitions
+// ============================================================================
+
+/// A named sketch block with its parameters, statements and local functions
+/// Examples:
+///   sketch Bracket { let width: f64 = 10.0; fn half() -> f64 { width / 2.0 } }
+///   sketch Bolt(diameter: f64, length: f64) { let r = diameter / 2.0; }
+///   sketch Gauge { let r = 1.0; #[test] assert r > 0; }
+#[derive(Debug, Clone, PartialEq)]
+pub struct SketchDef<'src> {
+    pub name: &'src str,
+    pub name_span: Span,
+    pub parameters: Vec<Param<'src>>,
+    /// Statements, tests and local functions, in source order
+    pub items: Vec<SketchItem<'src>>,
+    pub visibility: Visibility,
+    pub span: Span,
+}
+
+impl<'src> SketchDef<'src> {
+    /// Functions defined inside the sketch
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionDef<'src>> {
+        self.items.iter().filter_map(|item| match item {
+            SketchItem::Function(function) => Some(function),
+            SketchItem::Stmt(_) | SketchItem::Test(_) => None,
+        })
+    }
+}
+
+impl<'src> HasSpan for SketchDef<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A single item inside a sketch body
+/// Examples:
+///   let r = 1.0;
+///   #[test] assert r > 0;
+///   fn half() -> f64 { r / 2.0 }
+#[derive(Debug, Clone, PartialEq)]
+pub enum SketchItem<'src> {
+    Stmt(Stmt<'src>),
+    /// A statement marked `#[test]`
+    Test(Stmt<'src>),
+    Function(FunctionDef<'src>),
+}
+
+/// A struct definition with fields and methods
+/// Example:
+///   struct Circle { center: Point, radius: f64, fn diameter() -> f64 { self.radius * 2.0 } }
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDef<'src> {
+    pub name: &'src str,
+    pub name_span: Span,
+    pub fields: Vec<FieldDef<'src>>,
+    pub methods: Vec<FunctionDef<'src>>,
+    pub visibility: Visibility,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for StructDef<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Methods added to a struct outside its definition
+/// Example:
+///   impl Circle { fn area() -> f64 { self.radius * self.radius * 3.14 } }
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplBlock<'src> {
+    pub target: &'src str,
+    pub target_span: Span,
+    pub methods: Vec<FunctionDef<'src>>,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for ImplBlock<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A single struct field: `name: Type`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDef<'src> {
+    pub name: &'src str,
+    pub name_span: Span,
+    pub ty: Type<'src>,
+    pub visibility: Visibility,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for FieldDef<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A function or method definition
+/// The body is a list of statements followed by an optional result expression
+/// Example:
+///   fn distance(p1: &Point, p2: &Point) -> f64 { let dx = p2.x - p1.x; dx }
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef<'src> {
+    pub name: &'src str,
+    pub name_span: Span,
+    pub params: Vec<Param<'src>>,
+    pub return_type: Option<Type<'src>>,
+    pub body: Vec<Stmt<'src>>,
+    pub result: Option<Expr<'src>>,
+    pub visibility: Visibility,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for FunctionDef<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A function or sketch parameter: `name: Type`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param<'src> {
+    pub name: &'src str,
+    pub name_span: Span,
+    pub ty: Type<'src>,
+    pub span: Span,
+}
+
+impl<'src> HasSpan for Param<'src> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+// ============================================================================
+// Program
+// ============================================================================
+
+/// A single definition at the top level of a source file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item<'src> {
+    Sketch(SketchDef<'src>),
+    Struct(StructDef<'src>),
+    Impl(ImplBlock<'src>),
+    Function(FunctionDef<'src>),
+}
+
+/// All top-level definitions parsed from one or more source files
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Program<'src> {
+    /// Files the program was parsed from; empty for stdin
+    pub source_files: Vec<PathBuf>,
+    pub imports: Vec<ImportDecl<'src>>,
+    /// Definitions after the imports, in source order
+    pub items: Vec<Item<'src>>,
+}
+
+impl<'src> Program<'src> {
+    /// Combine the definitions and source files of two programs
+    ///
+    /// Definitions are concatenated as they are; detecting names defined in
+    /// both is left to the caller.
+    pub fn merge(mut self, other: Program<'src>) -> Program<'src> {
+        self.source_files.extend(other.source_files);
+        self.imports.extend(other.imports);
+        self.items.extend(other.items);
+        self
+    }
+
+    /// Sketches defined in the program
+    pub fn sketches(&self) -> impl Iterator<Item = &SketchDef<'src>> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Sketch(sketch) => Some(sketch),
+            _ => None,
+        })
+    }
+
+    /// Structs defined in the program
+    pub fn structs(&self) -> impl Iterator<Item = &StructDef<'src>> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Struct(def) => Some(def),
+            _ => None,
+        })
+    }
+
+    /// `impl` blocks in the program
+    pub fn impls(&self) -> impl Iterator<Item = &ImplBlock<'src>> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Impl(block) => Some(block),
+            _ => None,
+        })
+    }
+
+    /// Top-level functions, not including sketch functions or methods
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionDef<'src>> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Function(function) => Some(function),
+            _ => None,
+        })
+    }
+
+    /// The first sketch named `name`, e.g. to pick an entry point
+    #[cfg_attr(not(test), allow(dead_code))] // No entry-point selection yet
+    pub fn sketch_by_name(&self, name: &str) -> Option<&SketchDef<'src>> {
+        self.sketches().find(|sketch| sketch.name == name)
+    }
+}
@@ -1,15 +1,15 @@
 use crate::ast::expr::Expr;
 use crate::ast::span::HasSpan;
-use crate::lexer::Span;
+use crate::span::Span;
 
 // ============================================================================
 // Type Annotations
 // ============================================================================
 
-/// Type annotations for variable declarations
+/// Type annotations for variable declarations, fields and parameters
 /// Currently includes only types without units
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Type {
+pub enum Type<'src> {
     /// Boolean type
     Bool { span: Span },
     /// 32-bit integer type
@@ -20,9 +20,15 @@ pub enum Type {
     Real { span: Span },
     /// Algebraic number (roots of polynomials with integer coefficients)
     Algebraic { span: Span },
+    /// User-defined type such as a struct (e.g. `Point`)
+    Named { name: &'src str, span: Span },
+    /// Reference to an entity of another type (e.g. `&Point`)
+    Ref { inner: Box<Type<'src>>, span: Span },
+    /// Opaque return type implementing a trait (e.g. `impl Shape`)
+    ImplTrait { trait_name: &'src str, span: Span },
 }
 
-impl HasSpan for Type {
+impl<'src> HasSpan for Type<'src> {
     fn span(&self) -> Span {
         match self {
             Type::Bool { span } => *span,
@@ -30,6 +36,9 @@ impl HasSpan for Type {
             Type::F64 { span } => *span,
             Type::Real { span } => *span,
             Type::Algebraic { span } => *span,
+            Type::Named { span, .. } => *span,
+            Type::Ref { span, .. } => *span,
+            Type::ImplTrait { span, .. } => *span,
         }
     }
 }
@@ -49,16 +58,140 @@ pub enum Stmt<'src> {
     Let {
         name: &'src str,
         name_span: Span,
-        type_annotation: Option<Type>,
+        type_annotation: Option<Type<'src>>,
         init: Option<Expr<'src>>,
         span: Span,
     },
+
+    /// Design invariant that must hold, with an optional message
+    /// Examples:
+    ///   assert length >= 2 * diameter;
+    ///   assert width > 0, width;
+    Assert {
+        condition: Expr<'src>,
+        message: Option<Expr<'src>>,
+        span: Span,
+    },
+
+    /// Conditional statements; an `else if` chain is an else body holding
+    /// a single If
+    /// Examples:
+    ///   if width > 10 { assert height > 2; }
+    ///   if a { let x = 1; } else if b { let x = 2; } else { let x = 3; }
+    If {
+        condition: Expr<'src>,
+        then_body: Vec<Stmt<'src>>,
+        else_body: Option<Vec<Stmt<'src>>>,
+        span: Span,
+    },
+
+    /// Loop over the elements of an array or range, binding each to `var`;
+    /// a label lets `break` and `continue` in nested loops name this one
+    /// Examples:
+    ///   for i in 0..5 { assert i < 5; }
+    ///   'rows: for p in points { assert p.y >= 0; }
+    For {
+        label: Option<&'src str>,
+        var: &'src str,
+        var_span: Span,
+        iterable: Expr<'src>,
+        body: Vec<Stmt<'src>>,
+        span: Span,
+    },
+
+    /// Loop that runs its body for as long as `condition` holds
+    /// Examples:
+    ///   while dist > tol { assert dist > 0; }
+    ///   'search: while running { break 'search; }
+    While {
+        label: Option<&'src str>,
+        condition: Expr<'src>,
+        body: Vec<Stmt<'src>>,
+        span: Span,
+    },
+
+    /// Run the body in the context of a transform or container value
+    /// Example:
+    ///   with shift { let p: Point = line.start; }
+    With {
+        context: Expr<'src>,
+        body: Vec<Stmt<'src>>,
+        span: Span,
+    },
+
+    /// Update a value in place with an arithmetic operator
+    /// Examples:
+    ///   total += width;
+    ///   p.x *= 2;
+    CompoundAssign {
+        target: Expr<'src>,
+        op: CompoundOp,
+        value: Expr<'src>,
+        span: Span,
+    },
+
+    /// Leave the innermost loop, or the one with the given label
+    /// Examples:
+    ///   break;
+    ///   break 'outer;
+    Break {
+        label: Option<&'src str>,
+        span: Span,
+    },
+
+    /// Skip to the next iteration of the innermost loop, or of the one with
+    /// the given label
+    /// Examples:
+    ///   continue;
+    ///   continue 'outer;
+    Continue {
+        label: Option<&'src str>,
+        span: Span,
+    },
+
+    /// A statement that failed to parse; the parser skipped its tokens up
+    /// to the next `;` and carried on after it
+    Error { span: Span },
 }
 
 impl<'src> HasSpan for Stmt<'src> {
     fn span(&self) -> Span {
         match self {
             Stmt::Let { span, .. } => *span,
+            Stmt::Assert { span, .. } => *span,
+            Stmt::If { span, .. } => *span,
+            Stmt::For { span, .. } => *span,
+            Stmt::While { span, .. } => *span,
+            Stmt::With { span, .. } => *span,
+            Stmt::CompoundAssign { span, .. } => *span,
+            Stmt::Break { span, .. } => *span,
+            Stmt::Continue { span, .. } => *span,
+            Stmt::Error { span } => *span,
+        }
+    }
+}
+
+/// The arithmetic operator of a compound assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompoundOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
+
+impl CompoundOp {
+    /// The operator as written, such as `+=`
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompoundOp::Add => "+=",
+            CompoundOp::Sub => "-=",
+            CompoundOp::Mul => "*=",
+            CompoundOp::Div => "/=",
+            CompoundOp::Mod => "%=",
+            CompoundOp::Pow => "^=",
         }
     }
 }
@@ -0,0 +1,17 @@
+//! Output generators that turn a parsed program into other formats
+//!
+//! # Module Structure
+//!
+//! - `dot`: Graphviz DOT rendering of the AST for visualisation
+
+// ============================================================================
+// Submodules
+// ============================================================================
+
+pub mod dot;
+
+// ============================================================================
+// Re-exports
+// ============================================================================
+
+pub use dot::DotGen;
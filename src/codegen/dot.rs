@@ -0,0 +1,515 @@
+//! Graphviz DOT rendering of the AST
+//!
+//! Every AST node becomes a labelled graph node and every parent/child link
+//! becomes an edge, labelled with the role of the child where it has one
+//! (`lhs`, `init`, `condition`, ...). Render the output with
+//! `dot -Tsvg ast.dot -o ast.svg`.
+
+use crate::ast::{
+    Expr, FieldDef, FunctionDef, ImplBlock, ImportDecl, Item, Param, Program, SketchDef,
+    SketchItem, Stmt, StructDef, Type,
+};
+use std::fmt::Write;
+
+// ============================================================================
+// DOT Generator
+// ============================================================================
+
+/// Walks the AST and accumulates DOT statements
+#[derive(Debug, Default)]
+pub struct DotGen {
+    out: String,
+    next_id: usize,
+}
+
+impl DotGen {
+    /// Render a whole program as a DOT digraph
+    pub fn render(program: &Program) -> String {
+        let mut generator = Self::default();
+        generator.program(program);
+        format!(
+            "digraph ast {{\n    node [shape=box];\n{}}}\n",
+            generator.out
+        )
+    }
+
+    /// Emit a node and return its id
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        writeln!(self.out, "    n{} [label=\"{}\"];", id, escape(label)).unwrap();
+        id
+    }
+
+    /// Emit an edge from `parent` to `child`, labelled with the child's role
+    fn edge(&mut self, parent: usize, child: usize, role: &str) {
+        if role.is_empty() {
+            writeln!(self.out, "    n{} -> n{};", parent, child).unwrap();
+        } else {
+            writeln!(
+                self.out,
+                "    n{} -> n{} [label=\"{}\"];",
+                parent,
+                child,
+                escape(role)
+            )
+            .unwrap();
+        }
+    }
+
+    // ========================================================================
+    // Top-Level Definitions
+    // ========================================================================
+
+    fn program(&mut self, program: &Program) -> usize {
+        let id = self.node("Program");
+        for import in &program.imports {
+            let child = self.import(import);
+            self.edge(id, child, "");
+        }
+        for item in &program.items {
+            let child = match item {
+                Item::Sketch(sketch) => self.sketch(sketch),
+                Item::Struct(def) => self.struct_def(def),
+                Item::Impl(block) => self.impl_block(block),
+                Item::Function(function) => self.function(function),
+            };
+            self.edge(id, child, "");
+        }
+        id
+    }
+
+    fn import(&mut self, import: &ImportDecl) -> usize {
+        let mut label = format!("Import {:?}", import.path);
+        if let Some(items) = &import.items {
+            let names: Vec<_> = items.iter().map(|(name, _)| *name).collect();
+            write!(label, " use {}", names.join(", ")).unwrap();
+        }
+        self.node(&label)
+    }
+
+    fn sketch(&mut self, sketch: &SketchDef) -> usize {
+        let id = self.node(&format!("Sketch {}", sketch.name));
+        for param in &sketch.parameters {
+            let child = self.param(param);
+            self.edge(id, child, "param");
+        }
+        for item in &sketch.items {
+            let (child, label) = match item {
+                SketchItem::Stmt(stmt) => (self.stmt(stmt), ""),
+                SketchItem::Test(stmt) => (self.stmt(stmt), "test"),
+                SketchItem::Function(function) => (self.function(function), ""),
+            };
+            self.edge(id, child, label);
+        }
+        id
+    }
+
+    fn struct_def(&mut self, def: &StructDef) -> usize {
+        let id = self.node(&format!("Struct {}", def.name));
+        for field in &def.fields {
+            let child = self.field(field);
+            self.edge(id, child, "field");
+        }
+        for method in &def.methods {
+            let child = self.function(method);
+            self.edge(id, child, "method");
+        }
+        id
+    }
+
+    fn impl_block(&mut self, block: &ImplBlock) -> usize {
+        let id = self.node(&format!("Impl {}", block.target));
+        for method in &block.methods {
+            let child = self.function(method);
+            self.edge(id, child, "method");
+        }
+        id
+    }
+
+    fn field(&mut self, field: &FieldDef) -> usize {
+        let id = self.node(&format!("Field {}", field.name));
+        let ty = self.ty(&field.ty);
+        self.edge(id, ty, "type");
+        id
+    }
+
+    fn function(&mut self, function: &FunctionDef) -> usize {
+        let id = self.node(&format!("Fn {}", function.name));
+        for param in &function.params {
+            let child = self.param(param);
+            self.edge(id, child, "param");
+        }
+        if let Some(return_type) = &function.return_type {
+            let child = self.ty(return_type);
+            self.edge(id, child, "returns");
+        }
+        for stmt in &function.body {
+            let child = self.stmt(stmt);
+            self.edge(id, child, "");
+        }
+        if let Some(result) = &function.result {
+            let child = self.expr(result);
+            self.edge(id, child, "result");
+        }
+        id
+    }
+
+    fn param(&mut self, param: &Param) -> usize {
+        let id = self.node(&format!("Param {}", param.name));
+        let ty = self.ty(&param.ty);
+        self.edge(id, ty, "type");
+        id
+    }
+
+    // ========================================================================
+    // Statements and Types
+    // ========================================================================
+
+    fn stmt(&mut self, stmt: &Stmt) -> usize {
+        match stmt {
+            Stmt::Let {
+                name,
+                type_annotation,
+                init,
+                ..
+            } => {
+                let id = self.node(&format!("Let {}", name));
+                if let Some(ty) = type_annotation {
+                    let child = self.ty(ty);
+                    self.edge(id, child, "type");
+                }
+                if let Some(init) = init {
+                    let child = self.expr(init);
+                    self.edge(id, child, "init");
+                }
+                id
+            }
+            Stmt::Assert {
+                condition, message, ..
+            } => {
+                let id = self.node("Assert");
+                let child = self.expr(condition);
+                self.edge(id, child, "condition");
+                if let Some(message) = message {
+                    let child = self.expr(message);
+                    self.edge(id, child, "message");
+                }
+                id
+            }
+            Stmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                let id = self.node("If");
+                let child = self.expr(condition);
+                self.edge(id, child, "condition");
+                for stmt in then_body {
+                    let child = self.stmt(stmt);
+                    self.edge(id, child, "then");
+                }
+                for stmt in else_body.iter().flatten() {
+                    let child = self.stmt(stmt);
+                    self.edge(id, child, "else");
+                }
+                id
+            }
+            Stmt::For {
+                label,
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                let id = self.node(&format!("{}For {}", loop_label(*label), var));
+                let child = self.expr(iterable);
+                self.edge(id, child, "iterable");
+                for stmt in body {
+                    let child = self.stmt(stmt);
+                    self.edge(id, child, "body");
+                }
+                id
+            }
+            Stmt::While {
+                label,
+                condition,
+                body,
+                ..
+            } => {
+                let id = self.node(&format!("{}While", loop_label(*label)));
+                let child = self.expr(condition);
+                self.edge(id, child, "condition");
+                for stmt in body {
+                    let child = self.stmt(stmt);
+                    self.edge(id, child, "body");
+                }
+                id
+            }
+            Stmt::With { context, body, .. } => {
+                let id = self.node("With");
+                let child = self.expr(context);
+                self.edge(id, child, "context");
+                for stmt in body {
+                    let child = self.stmt(stmt);
+                    self.edge(id, child, "body");
+                }
+                id
+            }
+            Stmt::CompoundAssign {
+                target, op, value, ..
+            } => {
+                let id = self.node(&format!("CompoundAssign {}", op.symbol()));
+                let child = self.expr(target);
+                self.edge(id, child, "target");
+                let child = self.expr(value);
+                self.edge(id, child, "value");
+                id
+            }
+            Stmt::Break { label, .. } => match label {
+                Some(label) => self.node(&format!("Break '{}", label)),
+                None => self.node("Break"),
+            },
+            Stmt::Continue { label, .. } => match label {
+                Some(label) => self.node(&format!("Continue '{}", label)),
+                None => self.node("Continue"),
+            },
+            Stmt::Error { .. } => self.node("Error"),
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) -> usize {
+        match ty {
+            Type::Bool { .. } => self.node("Type bool"),
+            Type::I32 { .. } => self.node("Type i32"),
+            Type::F64 { .. } => self.node("Type f64"),
+            Type::Real { .. } => self.node("Type Real"),
+            Type::Algebraic { .. } => self.node("Type Algebraic"),
+            Type::Named { name, .. } => self.node(&format!("Type {}", name)),
+            Type::Ref { inner, .. } => {
+                let id = self.node("Type &");
+                let child = self.ty(inner);
+                self.edge(id, child, "");
+                id
+            }
+            Type::ImplTrait { trait_name, .. } => self.node(&format!("Type impl {}", trait_name)),
+        }
+    }
+
+    // ========================================================================
+    // Expressions
+    // ========================================================================
+
+    fn expr(&mut self, expr: &Expr) -> usize {
+        let (label, children) = expr_parts(expr);
+        let id = self.node(&label);
+        for (role, child) in children {
+            let child_id = self.expr(&child);
+            self.edge(id, child_id, &role);
+        }
+        id
+    }
+}
+
+/// Label of an expression node and its children with their roles
+///
+/// Operands are stored as precedence-specific subenums; they are converted
+/// back to `Expr` so every child is rendered the same way.
+fn expr_parts<'src>(expr: &Expr<'src>) -> (String, Vec<(String, Expr<'src>)>) {
+    fn binary<'src>(
+        op: &str,
+        lhs: impl Into<Expr<'src>>,
+        rhs: impl Into<Expr<'src>>,
+    ) -> (String, Vec<(String, Expr<'src>)>) {
+        (
+            op.to_string(),
+            vec![
+                ("lhs".to_string(), lhs.into()),
+                ("rhs".to_string(), rhs.into()),
+            ],
+        )
+    }
+
+    fn numbered<'src>(role: &str, exprs: &[Expr<'src>]) -> Vec<(String, Expr<'src>)> {
+        exprs
+            .iter()
+            .enumerate()
+            .map(|(i, expr)| (format!("{}{}", role, i), expr.clone()))
+            .collect()
+    }
+
+    match expr {
+        Expr::Pipe { lhs, rhs_fn, .. } => binary("|>", (**lhs).clone(), (**rhs_fn).clone()),
+        Expr::Range { start, end, .. } => binary("..", (**start).clone(), (**end).clone()),
+        Expr::And { lhs, rhs, .. } => binary("and", (**lhs).clone(), (**rhs).clone()),
+        Expr::Or { lhs, rhs, .. } => binary("or", (**lhs).clone(), (**rhs).clone()),
+        Expr::BitAnd { lhs, rhs, .. } => binary("&", (**lhs).clone(), (**rhs).clone()),
+        Expr::BitOr { lhs, rhs, .. } => binary("|", (**lhs).clone(), (**rhs).clone()),
+        Expr::Shl { lhs, rhs, .. } => binary("<<", (**lhs).clone(), (**rhs).clone()),
+        Expr::Shr { lhs, rhs, .. } => binary(">>", (**lhs).clone(), (**rhs).clone()),
+        Expr::Eq { lhs, rhs, .. } => binary("==", (**lhs).clone(), (**rhs).clone()),
+        Expr::NotEq { lhs, rhs, .. } => binary("!=", (**lhs).clone(), (**rhs).clone()),
+        Expr::Lt { lhs, rhs, .. } => binary("<", (**lhs).clone(), (**rhs).clone()),
+        Expr::Gt { lhs, rhs, .. } => binary(">", (**lhs).clone(), (**rhs).clone()),
+        Expr::LtEq { lhs, rhs, .. } => binary("<=", (**lhs).clone(), (**rhs).clone()),
+        Expr::GtEq { lhs, rhs, .. } => binary(">=", (**lhs).clone(), (**rhs).clone()),
+        Expr::In { lhs, rhs, .. } => binary("in", (**lhs).clone(), (**rhs).clone()),
+        Expr::NotIn { lhs, rhs, .. } => binary("not in", (**lhs).clone(), (**rhs).clone()),
+        Expr::Add { lhs, rhs, .. } => binary("+", (**lhs).clone(), (**rhs).clone()),
+        Expr::Sub { lhs, rhs, .. } => binary("-", (**lhs).clone(), (**rhs).clone()),
+        Expr::Mul { lhs, rhs, .. } => binary("*", (**lhs).clone(), (**rhs).clone()),
+        Expr::Div { lhs, rhs, .. } => binary("/", (**lhs).clone(), (**rhs).clone()),
+        Expr::Mod { lhs, rhs, .. } => binary("%", (**lhs).clone(), (**rhs).clone()),
+        Expr::Pow { lhs, rhs, .. } => binary("^", (**lhs).clone(), (**rhs).clone()),
+        Expr::Paren { inner, .. } => ("( )".to_string(), vec![(String::new(), (**inner).clone())]),
+        Expr::Neg { inner, .. } => (
+            "Neg".to_string(),
+            vec![(String::new(), (**inner).clone().into())],
+        ),
+        Expr::BitNot { inner, .. } => (
+            "BitNot ~".to_string(),
+            vec![(String::new(), (**inner).clone().into())],
+        ),
+        Expr::Ref { inner, .. } => (
+            "Ref &".to_string(),
+            vec![(String::new(), (**inner).clone().into())],
+        ),
+        Expr::Var { name, .. } => (format!("Var {}", name), Vec::new()),
+        Expr::IntLit { value, .. } => (format!("Int {}", value), Vec::new()),
+        Expr::FloatLit { value, .. } => (format!("Float {:?}", value), Vec::new()),
+        Expr::BoolLit { value, .. } => (format!("Bool {}", value), Vec::new()),
+        Expr::StrLit { value, .. } => (format!("Str {:?}", value), Vec::new()),
+        Expr::Call { name, args, .. } => (format!("Call {}", name), numbered("arg", args)),
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            let mut children = vec![("receiver".to_string(), (**receiver).clone())];
+            children.extend(numbered("arg", args));
+            (format!("MethodCall .{}", method), children)
+        }
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => (
+            format!("Field .{}", field),
+            vec![("receiver".to_string(), (**receiver).clone())],
+        ),
+        Expr::ArrayLit { elements, .. } => ("Array".to_string(), numbered("", elements)),
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => (
+            "If".to_string(),
+            vec![
+                ("condition".to_string(), (**condition).clone()),
+                ("then".to_string(), (**then_branch).clone()),
+                ("else".to_string(), (**else_branch).clone()),
+            ],
+        ),
+        Expr::StructLit { name, fields, .. } => (
+            format!("StructLit {}", name),
+            fields
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.clone()))
+                .collect(),
+        ),
+        // Each arm's edge is labelled with its pattern
+        Expr::Match {
+            scrutinee, arms, ..
+        } => (
+            "Match".to_string(),
+            std::iter::once(("scrutinee".to_string(), (**scrutinee).clone()))
+                .chain(
+                    arms.iter()
+                        .map(|arm| (arm.pattern.to_string(), arm.body.clone())),
+                )
+                .collect(),
+        ),
+    }
+}
+
+/// The `'label: ` in front of a labelled loop's node name
+fn loop_label(label: Option<&str>) -> String {
+    label.map_or_else(String::new, |label| format!("'{}: ", label))
+}
+
+/// Escape a label for use inside a double-quoted DOT string
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+    use crate::parser::program;
+    use chumsky::Parser;
+
+    fn render_source(source: &str) -> String {
+        let Ok((tokens, _)) = tokenize(source);
+        let program = program().parse(&tokens).into_result().unwrap();
+        DotGen::render(&program)
+    }
+
+    #[test]
+    fn test_braces_are_balanced() {
+        let dot = render_source(
+            "struct Point { x: f64, y: f64 }\nsketch Main { let p = Point { x: 1.0, y: 2.0 }; }",
+        );
+
+        assert!(dot.starts_with("digraph ast {"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let dot = render_source("sketch Main { let x: i32 = 1 + 2; }");
+
+        assert!(dot.contains("[label=\"Program\"]"));
+        assert!(dot.contains("[label=\"Sketch Main\"]"));
+        assert!(dot.contains("[label=\"Let x\"]"));
+        assert!(dot.contains("[label=\"Type i32\"]"));
+        assert!(dot.contains("[label=\"+\"]"));
+        assert!(dot.contains("[label=\"init\"]"));
+        assert!(dot.contains("[label=\"lhs\"]"));
+        assert!(dot.contains("[label=\"Int 2\"]"));
+
+        // The AST is a tree: every node except the root has one incoming edge
+        let edges = dot.lines().filter(|line| line.contains(" -> ")).count();
+        let nodes = dot
+            .lines()
+            .filter(|line| line.contains("[label=") && !line.contains(" -> "))
+            .count();
+        assert_eq!(edges, nodes - 1);
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let dot = render_source("impl Circle { fn area() -> f64 { 1.0 } }");
+
+        assert!(dot.contains("[label=\"Impl Circle\"]"));
+        assert!(dot.contains("[label=\"method\"]"));
+    }
+
+    #[test]
+    fn test_quotes_are_escaped() {
+        let dot = render_source("import \"lib/a.cad\";");
+
+        assert!(dot.contains("[label=\"Import \\\"lib/a.cad\\\"\"]"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+    }
+}
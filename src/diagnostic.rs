@@ -0,0 +1,619 @@
+//! Diagnostics collected across compiler phases
+//!
+//! Each phase reports problems in its own error type. `Diagnostic` wraps them
+//! in a single type so a driver can gather everything into one list and
+//! render it with Ariadne at the end.
+
+use crate::error::{LexError, LexErrorKind};
+use crate::lexer::{Token, TokenTrait};
+use crate::parser::parse_error_report;
+use crate::span::{LineIndex, Span};
+use ariadne::{Color, Config, IndexType, Label, Report, ReportKind, Source};
+use chumsky::error::Rich;
+use std::collections::HashSet;
+use std::ops::Range;
+
+pub mod sarif;
+
+// ============================================================================
+// Diagnostic Types
+// ============================================================================
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(not(test), allow(dead_code))] // Warnings are not produced yet
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn report_kind(self) -> ReportKind<'static> {
+        match self {
+            Severity::Error => ReportKind::Error,
+            Severity::Warning => ReportKind::Warning,
+            Severity::Note => ReportKind::Advice,
+        }
+    }
+}
+
+/// The phase-specific error carried by a diagnostic
+#[derive(Debug, Clone)]
+pub enum DiagnosticKind<'src> {
+    Lex(LexError),
+    Parse(Rich<'src, Token<'src>>),
+    /// A parse error in a statement the parser then skipped, up to and
+    /// including the `;` that ends the `skipped` span
+    RecoveredStatement {
+        error: Rich<'src, Token<'src>>,
+        skipped: Span,
+    },
+    /// An import that could not be followed
+    Import {
+        message: String,
+        span: Span,
+    },
+    /// A top-level name defined again after `first_file` already defined it
+    DuplicateDefinition {
+        name: &'src str,
+        span: Span,
+        first_file: String,
+        first_span: Span,
+    },
+    /// Errors dropped after the accumulator reached its limit
+    TooManyErrors {
+        suppressed_count: usize,
+    },
+}
+
+/// A single problem reported by any compiler phase
+#[derive(Debug, Clone)]
+pub struct Diagnostic<'src> {
+    pub kind: DiagnosticKind<'src>,
+    pub severity: Severity,
+}
+
+impl<'src> Diagnostic<'src> {
+    /// Create an error diagnostic
+    pub fn error(kind: DiagnosticKind<'src>) -> Self {
+        Self {
+            kind,
+            severity: Severity::Error,
+        }
+    }
+
+    /// Stable identifier for the kind of problem, used as the SARIF rule id
+    pub fn code(&self) -> &'static str {
+        match &self.kind {
+            DiagnosticKind::Lex(_) => "lex-error",
+            DiagnosticKind::Parse(_) | DiagnosticKind::RecoveredStatement { .. } => "parse-error",
+            DiagnosticKind::Import { .. } => "import-error",
+            DiagnosticKind::DuplicateDefinition { .. } => "duplicate-definition",
+            DiagnosticKind::TooManyErrors { .. } => "too-many-errors",
+        }
+    }
+
+    /// One-line description of the problem
+    pub fn message(&self) -> String {
+        match &self.kind {
+            DiagnosticKind::Lex(error) => error.to_string(),
+            DiagnosticKind::Parse(error) | DiagnosticKind::RecoveredStatement { error, .. } => {
+                match error.found() {
+                    Some(token) => format!("unexpected '{}'", token.value_str()),
+                    None => "unexpected end of input".to_string(),
+                }
+            }
+            DiagnosticKind::Import { message, .. } => message.clone(),
+            DiagnosticKind::DuplicateDefinition { name, .. } => {
+                format!("'{}' is defined more than once", name)
+            }
+            DiagnosticKind::TooManyErrors { suppressed_count } => {
+                format!("too many errors, {} more not shown", suppressed_count)
+            }
+        }
+    }
+
+    /// Where in `source` the problem is, if it has a location
+    ///
+    /// A parse error at the end of input gets an empty span after the last
+    /// character.
+    pub fn span(&self, source: &str) -> Option<Span> {
+        match &self.kind {
+            DiagnosticKind::Parse(error) | DiagnosticKind::RecoveredStatement { error, .. }
+                if error.found().is_none() =>
+            {
+                Some(Span::empty(
+                    LineIndex::new(source).line_column(source.len()),
+                ))
+            }
+            _ => self.location(),
+        }
+    }
+
+    /// The span of the problem, when it can be found without the source
+    ///
+    /// `None` for diagnostics without a location and for parse errors at
+    /// the end of input.
+    fn location(&self) -> Option<Span> {
+        match &self.kind {
+            DiagnosticKind::Lex(error) => Some(error.span),
+            DiagnosticKind::Parse(error) | DiagnosticKind::RecoveredStatement { error, .. } => {
+                error.found().map(TokenTrait::span)
+            }
+            DiagnosticKind::Import { span, .. } => Some(*span),
+            DiagnosticKind::DuplicateDefinition { span, .. } => Some(*span),
+            DiagnosticKind::TooManyErrors { .. } => None,
+        }
+    }
+
+    /// What makes two diagnostics the same problem
+    ///
+    /// Parse errors that differ only in what the parser expected are the
+    /// same problem to the reader, so the expected set is left out.
+    fn key(&self) -> (Severity, Option<Span>, String) {
+        (self.severity, self.location(), self.message())
+    }
+
+    /// Build an Ariadne report for this diagnostic in `source`
+    pub fn report<'a>(
+        &self,
+        filename: &'a str,
+        source: &str,
+    ) -> Report<'a, (&'a str, Range<usize>)> {
+        let kind = self.severity.report_kind();
+        match &self.kind {
+            DiagnosticKind::Lex(error) => {
+                let range = LineIndex::new(source).range(error.span);
+                let label = match error.kind {
+                    LexErrorKind::UnexpectedCharacter(_) => "not valid here",
+                    LexErrorKind::IntegerOverflow => "the largest i32 is 2147483647",
+                    LexErrorKind::UnknownEscape(_) => "in this string literal",
+                };
+
+                Report::build(kind, filename, range.start)
+                    .with_config(report_config())
+                    .with_message(self.message())
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message(label)
+                            .with_color(Color::Red),
+                    )
+                    .finish()
+            }
+            DiagnosticKind::Parse(error) => {
+                parse_error_report(filename, source, kind, error).finish()
+            }
+            DiagnosticKind::RecoveredStatement { error, skipped } => {
+                let range = LineIndex::new(source).range(*skipped);
+
+                parse_error_report(filename, source, kind, error)
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message("skipped up to the next ';'")
+                            .with_color(Color::Yellow),
+                    )
+                    .with_help("parsing resumed after this statement")
+                    .finish()
+            }
+            DiagnosticKind::Import { span, .. } => {
+                let range = LineIndex::new(source).range(*span);
+
+                Report::build(kind, filename, range.start)
+                    .with_config(report_config())
+                    .with_message(self.message())
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message("imported here")
+                            .with_color(Color::Red),
+                    )
+                    .finish()
+            }
+            DiagnosticKind::DuplicateDefinition {
+                span,
+                first_file,
+                first_span,
+                ..
+            } => {
+                let range = LineIndex::new(source).range(*span);
+
+                Report::build(kind, filename, range.start)
+                    .with_config(report_config())
+                    .with_message(self.message())
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message("defined again here")
+                            .with_color(Color::Red),
+                    )
+                    .with_note(format!("first defined in {} at {}", first_file, first_span))
+                    .finish()
+            }
+            DiagnosticKind::TooManyErrors { suppressed_count } => Report::build(kind, filename, 0)
+                .with_config(report_config())
+                .with_message(format!(
+                    "too many errors, {} more not shown",
+                    suppressed_count
+                ))
+                .finish(),
+        }
+    }
+}
+
+impl<'src> From<LexError> for Diagnostic<'src> {
+    fn from(error: LexError) -> Self {
+        Self::error(DiagnosticKind::Lex(error))
+    }
+}
+
+impl<'src> From<Rich<'src, Token<'src>>> for Diagnostic<'src> {
+    fn from(error: Rich<'src, Token<'src>>) -> Self {
+        Self::error(DiagnosticKind::Parse(error))
+    }
+}
+
+// ============================================================================
+// Accumulator
+// ============================================================================
+
+/// Collects diagnostics from every phase of a compilation
+///
+/// A diagnostic with the same severity, span and message as one already
+/// collected is ignored, so a node visited by several checks is only
+/// reported once. With a limit set, errors past the limit are counted but
+/// not kept, and `finish` reports how many were dropped.
+///
+/// One accumulator can serve several files: `take` hands over the
+/// diagnostics of one file while the limit keeps counting towards the whole
+/// run.
+#[derive(Debug, Default)]
+pub struct DiagnosticAccumulator<'src> {
+    diagnostics: Vec<Diagnostic<'src>>,
+    /// Severity, span and message of the diagnostics collected since the
+    /// last `take`
+    seen: HashSet<(Severity, Option<Span>, String)>,
+    max_errors: Option<usize>,
+    /// Errors kept so far, including those already taken
+    error_count: usize,
+    suppressed: usize,
+}
+
+impl<'src> DiagnosticAccumulator<'src> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an accumulator that keeps at most `max_errors` errors
+    pub fn with_max_errors(max_errors: usize) -> Self {
+        Self {
+            max_errors: Some(max_errors),
+            ..Self::default()
+        }
+    }
+
+    /// Add a single diagnostic
+    pub fn push(&mut self, diagnostic: impl Into<Diagnostic<'src>>) {
+        let diagnostic = diagnostic.into();
+        if !self.seen.insert(diagnostic.key()) {
+            return;
+        }
+        if diagnostic.severity == Severity::Error {
+            if self.max_errors.is_some_and(|max| self.error_count >= max) {
+                self.suppressed += 1;
+                return;
+            }
+            self.error_count += 1;
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Add every diagnostic produced by a phase
+    pub fn extend<D: Into<Diagnostic<'src>>>(&mut self, diagnostics: impl IntoIterator<Item = D>) {
+        for diagnostic in diagnostics {
+            self.push(diagnostic);
+        }
+    }
+
+    /// Whether any collected diagnostic is an error, including taken and
+    /// dropped ones
+    pub fn has_errors(&self) -> bool {
+        self.error_count > 0 || self.suppressed > 0
+    }
+
+    /// Number of errors kept, including taken ones but not dropped ones
+    #[cfg_attr(not(test), allow(dead_code))] // The driver only asks `has_errors`
+    pub fn error_count(&self) -> usize {
+        self.error_count
+    }
+
+    /// Remove and return the diagnostics collected since the last `take`
+    ///
+    /// The errors taken still count towards the limit. Duplicates are only
+    /// looked for among the diagnostics collected after this call, since a
+    /// diagnostic in another file is a different problem.
+    pub fn take(&mut self) -> Vec<Diagnostic<'src>> {
+        self.seen.clear();
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// The remaining diagnostics, followed by a note on any dropped errors
+    pub fn finish(mut self) -> Vec<Diagnostic<'src>> {
+        if self.suppressed > 0 {
+            self.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::TooManyErrors {
+                    suppressed_count: self.suppressed,
+                },
+                severity: Severity::Note,
+            });
+        }
+        self.diagnostics
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))] // The driver renders via `take` and `finish`
+    pub fn diagnostics(&self) -> &[Diagnostic<'src>] {
+        &self.diagnostics
+    }
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Ariadne settings shared by every report
+///
+/// Label ranges come from `LineIndex`, which counts bytes, while Ariadne
+/// counts characters unless told otherwise.
+pub fn report_config() -> Config {
+    Config::default().with_index_type(IndexType::Byte)
+}
+
+/// Print all diagnostics for one source file to stderr
+pub fn render_all(source: &str, filename: &str, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        diagnostic
+            .report(filename, source)
+            .eprint((filename, Source::from(source)))
+            .unwrap();
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{LineColumn, tokenize};
+    use crate::parser::expr;
+    use chumsky::Parser;
+    use chumsky::error::{LabelError, RichPattern};
+    use chumsky::span::SimpleSpan;
+
+    fn lex_error(character: char, line: usize, column: usize) -> LexError {
+        LexError::new(
+            LexErrorKind::UnexpectedCharacter(character),
+            Span {
+                start: LineColumn { line, column },
+                lines: 0,
+                end_column: column + 1,
+            },
+        )
+    }
+
+    fn render_to_string(source: &str, diagnostic: &Diagnostic) -> String {
+        let mut buffer = Vec::new();
+        diagnostic
+            .report("test.cad", source)
+            .write(("test.cad", Source::from(source)), &mut buffer)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_accumulator_collects_all_phases() {
+        let source = "1 + + 2";
+        let Ok((tokens, _)) = tokenize(source);
+        let errors = expr().parse(&tokens).into_result().unwrap_err();
+        let parse_error_count = errors.len();
+
+        let mut diagnostics = DiagnosticAccumulator::new();
+        diagnostics.push(lex_error('@', 1, 1));
+        diagnostics.extend(errors);
+
+        assert_eq!(diagnostics.diagnostics().len(), parse_error_count + 1);
+        assert!(matches!(
+            diagnostics.diagnostics()[0].kind,
+            DiagnosticKind::Lex(_)
+        ));
+        assert!(matches!(
+            diagnostics.diagnostics()[1].kind,
+            DiagnosticKind::Parse(_)
+        ));
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_accumulator_warnings_are_not_errors() {
+        let mut diagnostics = DiagnosticAccumulator::new();
+        assert!(!diagnostics.has_errors());
+
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::Lex(lex_error('@', 1, 1)),
+            severity: Severity::Warning,
+        });
+        assert!(!diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_accumulator_ignores_duplicates() {
+        let mut diagnostics = DiagnosticAccumulator::new();
+        diagnostics.push(lex_error('$', 1, 1));
+        diagnostics.push(lex_error('$', 1, 1));
+        // Same character elsewhere is a different diagnostic
+        diagnostics.push(lex_error('$', 2, 1));
+        // As is the same problem at a different severity
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::Lex(lex_error('$', 1, 1)),
+            severity: Severity::Warning,
+        });
+
+        assert_eq!(diagnostics.diagnostics().len(), 3);
+        assert_eq!(diagnostics.error_count(), 2);
+    }
+
+    #[test]
+    fn test_accumulator_ignores_parse_errors_differing_in_expected() {
+        let Ok((tokens, _)) = tokenize("1 + ;");
+        let found = Some(tokens[2].clone().into());
+        let span = SimpleSpan::from(2..3);
+        let expecting = |label: &'static str| {
+            <Rich<Token> as LabelError<&[Token], _>>::expected_found(
+                [RichPattern::Label(label.into())],
+                found.clone(),
+                span,
+            )
+        };
+
+        let mut diagnostics = DiagnosticAccumulator::new();
+        diagnostics.push(expecting("number"));
+        diagnostics.push(expecting("variable"));
+
+        assert_eq!(diagnostics.diagnostics().len(), 1);
+        assert_eq!(diagnostics.diagnostics()[0].message(), "unexpected ';'");
+    }
+
+    #[test]
+    fn test_duplicates_do_not_count_towards_limit() {
+        let mut diagnostics = DiagnosticAccumulator::with_max_errors(1);
+        diagnostics.push(lex_error('$', 1, 1));
+        diagnostics.push(lex_error('$', 1, 1));
+
+        assert_eq!(diagnostics.finish().len(), 1);
+    }
+
+    #[test]
+    fn test_accumulator_drops_errors_past_limit() {
+        let mut diagnostics = DiagnosticAccumulator::with_max_errors(2);
+        diagnostics.extend([
+            lex_error('$', 1, 1),
+            lex_error('@', 1, 3),
+            lex_error('#', 1, 5),
+            lex_error('~', 1, 7),
+        ]);
+        // Non-errors are kept regardless of the limit
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::Lex(lex_error('`', 1, 9)),
+            severity: Severity::Warning,
+        });
+
+        assert_eq!(diagnostics.error_count(), 2);
+        assert!(diagnostics.has_errors());
+
+        let finished = diagnostics.finish();
+        assert_eq!(finished.len(), 4);
+        assert!(matches!(
+            finished[3].kind,
+            DiagnosticKind::TooManyErrors {
+                suppressed_count: 2
+            }
+        ));
+        assert_eq!(finished[3].severity, Severity::Note);
+    }
+
+    #[test]
+    fn test_limit_spans_taken_files() {
+        let mut diagnostics = DiagnosticAccumulator::with_max_errors(2);
+        diagnostics.extend([lex_error('$', 1, 1), lex_error('@', 1, 3)]);
+        assert_eq!(diagnostics.take().len(), 2);
+
+        // The same position in the next file is a new problem, but past the limit
+        diagnostics.extend([lex_error('$', 1, 1), lex_error('#', 1, 5)]);
+        assert!(diagnostics.take().is_empty());
+        diagnostics.push(lex_error('~', 1, 7));
+
+        assert_eq!(diagnostics.error_count(), 2);
+        let finished = diagnostics.finish();
+        assert_eq!(finished.len(), 1);
+        assert!(matches!(
+            finished[0].kind,
+            DiagnosticKind::TooManyErrors {
+                suppressed_count: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn test_accumulator_with_zero_limit_still_has_errors() {
+        let mut diagnostics = DiagnosticAccumulator::with_max_errors(0);
+        diagnostics.push(lex_error('$', 1, 1));
+
+        assert_eq!(diagnostics.error_count(), 0);
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_finish_without_limit_adds_nothing() {
+        let mut diagnostics = DiagnosticAccumulator::new();
+        diagnostics.push(lex_error('$', 1, 1));
+
+        assert_eq!(diagnostics.finish().len(), 1);
+    }
+
+    #[test]
+    fn test_report_contains_lex_message() {
+        let diagnostic = Diagnostic::from(lex_error('@', 1, 3));
+
+        let rendered = render_to_string("x @ y", &diagnostic);
+        assert!(rendered.contains("unexpected character '@'"));
+        assert!(rendered.contains("not valid here"));
+    }
+
+    #[test]
+    fn test_report_contains_parse_message() {
+        let source = "1 +";
+        let Ok((tokens, _)) = tokenize(source);
+        let errors = expr().parse(&tokens).into_result().unwrap_err();
+        let diagnostic = Diagnostic::from(errors[0].clone());
+
+        let rendered = render_to_string(source, &diagnostic);
+        assert!(rendered.contains("Parse error"));
+    }
+
+    #[test]
+    fn test_parse_report_points_at_found_token() {
+        // The error token is on the second line, after a multi-byte character
+        let source = "1 + \"é\" +\n  )";
+        let Ok((tokens, _)) = tokenize(source);
+        let errors = expr().parse(&tokens).into_result().unwrap_err();
+        let diagnostic = Diagnostic::from(errors[0].clone());
+
+        let rendered = render_to_string(source, &diagnostic);
+        assert!(rendered.contains("Unexpected ')'"), "{}", rendered);
+        assert!(!rendered.contains("RightParen"), "{}", rendered);
+        assert!(rendered.contains("test.cad:2:3"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_recovered_statement_report_shows_skipped_tokens() {
+        let source = "sketch Main { let a = 1 +; }";
+        let Ok((tokens, _)) = tokenize(source);
+        let (_, errors) = crate::parser::parse(&tokens);
+
+        let rendered = render_to_string(source, &errors[0]);
+        assert!(rendered.contains("Unexpected ';'"));
+        assert!(rendered.contains("skipped up to the next ';'"));
+        assert!(rendered.contains("parsing resumed after this statement"));
+    }
+
+    #[test]
+    fn test_note_renders_as_advice() {
+        let diagnostic = Diagnostic {
+            kind: DiagnosticKind::Lex(lex_error('$', 1, 5)),
+            severity: Severity::Note,
+        };
+
+        let rendered = render_to_string("let $ = 1", &diagnostic);
+        assert!(rendered.contains("Advice"));
+        assert!(rendered.contains("unexpected character '$'"));
+    }
+}
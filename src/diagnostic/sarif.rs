@@ -0,0 +1,495 @@
+//! SARIF 2.1.0 output for diagnostics
+//!
+//! SARIF (Static Analysis Results Interchange Format) is the JSON format CI
+//! systems and code scanning tools read analysis results from. Every
+//! diagnostic becomes one `result` with its level, rule id, message and,
+//! when it has one, location. Files are named by URI references relative to
+//! the working directory. Columns count UTF-16 code units, the SARIF default,
+//! so they are converted from the byte columns the lexer produces.
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::span::{LineColumn, LineIndex};
+use std::fmt::Write;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// ============================================================================
+// SARIF Log
+// ============================================================================
+
+/// Collects diagnostics from any number of files into one SARIF log
+///
+/// Results are rendered to JSON as they are added, so the log does not
+/// borrow the diagnostics or their sources.
+#[derive(Debug, Default)]
+pub struct SarifLog {
+    results: Vec<String>,
+}
+
+impl SarifLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the diagnostics reported for the file at `path` with contents
+    /// `source`, where `path` is `None` for source read from stdin
+    pub fn add(&mut self, path: Option<&str>, source: &str, diagnostics: &[Diagnostic]) {
+        let artifact_location = match path {
+            Some(path) => format!("{{ \"uri\": {} }}", string(&uri(path))),
+            // Standard input has no URI, so it is only described
+            None => "{ \"description\": { \"text\": \"standard input\" } }".to_string(),
+        };
+        let index = LineIndex::new(source);
+        for diagnostic in diagnostics {
+            self.results
+                .push(result(&artifact_location, source, &index, diagnostic));
+        }
+    }
+
+    /// The complete SARIF document
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{{").unwrap();
+        writeln!(out, "  \"$schema\": {},", string(SCHEMA)).unwrap();
+        writeln!(out, "  \"version\": \"2.1.0\",").unwrap();
+        writeln!(out, "  \"runs\": [").unwrap();
+        writeln!(out, "    {{").unwrap();
+        writeln!(out, "      \"tool\": {{").unwrap();
+        writeln!(out, "        \"driver\": {{").unwrap();
+        writeln!(out, "          \"name\": \"cad-dsl\",").unwrap();
+        writeln!(
+            out,
+            "          \"version\": {}",
+            string(env!("CARGO_PKG_VERSION"))
+        )
+        .unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "      }},").unwrap();
+        if self.results.is_empty() {
+            writeln!(out, "      \"results\": []").unwrap();
+        } else {
+            writeln!(out, "      \"results\": [").unwrap();
+            writeln!(out, "{}", self.results.join(",\n")).unwrap();
+            writeln!(out, "      ]").unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "  ]").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+/// A single SARIF result object, indented to sit in the results array
+fn result(
+    artifact_location: &str,
+    source: &str,
+    index: &LineIndex,
+    diagnostic: &Diagnostic,
+) -> String {
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+    };
+
+    let mut lines = vec![
+        "{".to_string(),
+        format!("  \"ruleId\": {},", string(diagnostic.code())),
+        format!("  \"level\": \"{}\",", level),
+    ];
+    let message = format!(
+        "  \"message\": {{ \"text\": {} }}",
+        string(&diagnostic.message())
+    );
+    match diagnostic.span(source) {
+        Some(span) => {
+            let end = span.end();
+            lines.push(format!("{},", message));
+            lines.push(format!(
+                "  \"locations\": [{{ \"physicalLocation\": {{ \"artifactLocation\": {}, \"region\": {{ \"startLine\": {}, \"startColumn\": {}, \"endLine\": {}, \"endColumn\": {} }} }} }}]",
+                artifact_location,
+                span.start.line,
+                utf16_column(source, index, span.start),
+                end.line,
+                utf16_column(source, index, end)
+            ));
+        }
+        // Diagnostics without a span, such as the note on errors dropped
+        // past --max-errors, are about the whole run rather than one file
+        None => lines.push(message),
+    }
+    lines.push("}".to_string());
+    indent(&lines.join("\n"), 8)
+}
+
+/// The 1-based column of `position` in UTF-16 code units
+///
+/// Falls back to the byte column if the position is not on a character
+/// boundary of `source`.
+fn utf16_column(source: &str, index: &LineIndex, position: LineColumn) -> usize {
+    let line_start = index.offset(LineColumn {
+        line: position.line,
+        column: 1,
+    });
+    match source.get(line_start..index.offset(position)) {
+        Some(prefix) => prefix.encode_utf16().count() + 1,
+        None => position.column,
+    }
+}
+
+/// Indent every line of `text` by `width` spaces
+fn indent(text: &str, width: usize) -> String {
+    let pad = " ".repeat(width);
+    text.lines()
+        .map(|line| format!("{}{}", pad, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The URI reference for a file path
+///
+/// Relative paths stay relative to the working directory and absolute
+/// paths become `file://` URIs. Every byte other than an unreserved
+/// character or `/` is percent-encoded, so spaces, `#` and a `:` in the
+/// first segment cannot change how the URI is read.
+fn uri(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    if path.starts_with('/') {
+        out.push_str("file://");
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                out.push(char::from(byte))
+            }
+            _ => write!(out, "%{:02X}", byte).unwrap(),
+        }
+    }
+    out
+}
+
+/// Quote and escape a string as a JSON string literal
+fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::DiagnosticKind;
+    use crate::error::{LexError, LexErrorKind};
+    use crate::span::Span;
+    use std::iter::Peekable;
+    use std::str::Chars;
+
+    /// A parsed JSON value, enough to check the structure of a log
+    #[derive(Debug, PartialEq)]
+    enum Json {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<Json>),
+        Object(Vec<(String, Json)>),
+    }
+
+    impl Json {
+        fn get(&self, key: &str) -> &Json {
+            match self {
+                Json::Object(fields) => fields
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .map(|(_, value)| value)
+                    .unwrap_or_else(|| panic!("Expected field {:?} in {:?}", key, self)),
+                other => panic!("Expected an object, got {:?}", other),
+            }
+        }
+
+        fn at(&self, index: usize) -> &Json {
+            match self {
+                Json::Array(items) => &items[index],
+                other => panic!("Expected an array, got {:?}", other),
+            }
+        }
+
+        fn has(&self, key: &str) -> bool {
+            matches!(self, Json::Object(fields) if fields.iter().any(|(name, _)| name == key))
+        }
+    }
+
+    /// Parse `text` as a single JSON document, panicking if it is not valid
+    fn parse_json(text: &str) -> Json {
+        let mut chars = text.chars().peekable();
+        let value = json_value(&mut chars);
+        skip_whitespace(&mut chars);
+        assert_eq!(chars.next(), None, "Expected the end of the document");
+        value
+    }
+
+    fn skip_whitespace(chars: &mut Peekable<Chars>) {
+        while chars
+            .next_if(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+            .is_some()
+        {}
+    }
+
+    fn expect(chars: &mut Peekable<Chars>, expected: char) {
+        skip_whitespace(chars);
+        assert_eq!(chars.next(), Some(expected));
+    }
+
+    fn json_value(chars: &mut Peekable<Chars>) -> Json {
+        skip_whitespace(chars);
+        match chars.peek().copied() {
+            Some('{') => {
+                chars.next();
+                let mut fields = Vec::new();
+                skip_whitespace(chars);
+                if chars.next_if_eq(&'}').is_some() {
+                    return Json::Object(fields);
+                }
+                loop {
+                    skip_whitespace(chars);
+                    let name = json_string(chars);
+                    expect(chars, ':');
+                    fields.push((name, json_value(chars)));
+                    skip_whitespace(chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some('}') => return Json::Object(fields),
+                        other => panic!("Expected ',' or '}}' in object, got {:?}", other),
+                    }
+                }
+            }
+            Some('[') => {
+                chars.next();
+                let mut items = Vec::new();
+                skip_whitespace(chars);
+                if chars.next_if_eq(&']').is_some() {
+                    return Json::Array(items);
+                }
+                loop {
+                    items.push(json_value(chars));
+                    skip_whitespace(chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some(']') => return Json::Array(items),
+                        other => panic!("Expected ',' or ']' in array, got {:?}", other),
+                    }
+                }
+            }
+            Some('"') => Json::String(json_string(chars)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) =
+                    chars.next_if(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+                {
+                    number.push(c);
+                }
+                Json::Number(number.parse().expect("Expected a valid number"))
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "true" => Json::Bool(true),
+                    "false" => Json::Bool(false),
+                    "null" => Json::Null,
+                    other => panic!("Unexpected {:?} in JSON", other),
+                }
+            }
+            None => panic!("Unexpected end of JSON"),
+        }
+    }
+
+    fn json_string(chars: &mut Peekable<Chars>) -> String {
+        assert_eq!(chars.next(), Some('"'));
+        let mut value = String::new();
+        loop {
+            match chars.next().expect("Unterminated JSON string") {
+                '"' => return value,
+                '\\' => match chars.next() {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('/') => value.push('/'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        let code = u32::from_str_radix(&hex, 16).expect("Expected 4 hex digits");
+                        value.push(char::from_u32(code).expect("Expected a scalar value"));
+                    }
+                    other => panic!("Invalid escape {:?} in JSON string", other),
+                },
+                c if u32::from(c) < 0x20 => panic!("Unescaped control character {:?}", c),
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn lex_error_at(line: usize, column: usize) -> Diagnostic<'static> {
+        Diagnostic::from(LexError::new(
+            LexErrorKind::UnexpectedCharacter('$'),
+            Span {
+                start: LineColumn { line, column },
+                lines: 0,
+                end_column: column + 1,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_string_escapes_json_specials() {
+        assert_eq!(string("a\"b\\c\nd\te"), r#""a\"b\\c\nd\te""#);
+        assert_eq!(string("\u{1}"), r#""\u0001""#);
+    }
+
+    #[test]
+    fn test_empty_log_has_empty_results() {
+        let log = parse_json(&SarifLog::new().render());
+        assert_eq!(log.get("version"), &Json::String("2.1.0".to_string()));
+        assert_eq!(log.get("runs").at(0).get("results"), &Json::Array(vec![]));
+    }
+
+    #[test]
+    fn test_result_has_rule_level_and_region() {
+        let mut log = SarifLog::new();
+        log.add(Some("dir/main.cad"), "let x;\n  $", &[lex_error_at(2, 3)]);
+        let log = parse_json(&log.render());
+
+        let result = log.get("runs").at(0).get("results").at(0);
+        assert_eq!(result.get("ruleId"), &Json::String("lex-error".to_string()));
+        assert_eq!(result.get("level"), &Json::String("error".to_string()));
+        assert_eq!(
+            result.get("message").get("text"),
+            &Json::String("unexpected character '$'".to_string())
+        );
+        let location = result.get("locations").at(0).get("physicalLocation");
+        assert_eq!(
+            location.get("artifactLocation").get("uri"),
+            &Json::String("dir/main.cad".to_string())
+        );
+        let region = location.get("region");
+        assert_eq!(region.get("startLine"), &Json::Number(2.0));
+        assert_eq!(region.get("startColumn"), &Json::Number(3.0));
+        assert_eq!(region.get("endLine"), &Json::Number(2.0));
+        assert_eq!(region.get("endColumn"), &Json::Number(4.0));
+    }
+
+    #[test]
+    fn test_region_columns_count_utf16_code_units() {
+        // `é` is two bytes but one UTF-16 code unit, `𝑥` four bytes but two
+        let source = "let s = \"é𝑥\"; $";
+        let mut log = SarifLog::new();
+        log.add(Some("main.cad"), source, &[lex_error_at(1, 19)]);
+        let log = parse_json(&log.render());
+
+        let region = log
+            .get("runs")
+            .at(0)
+            .get("results")
+            .at(0)
+            .get("locations")
+            .at(0)
+            .get("physicalLocation")
+            .get("region");
+        assert_eq!(region.get("startColumn"), &Json::Number(16.0));
+        assert_eq!(region.get("endColumn"), &Json::Number(17.0));
+    }
+
+    #[test]
+    fn test_message_with_json_specials_round_trips() {
+        let message = "cannot find imported file 'a\"b\\c\nd.cad'";
+        let diagnostic = Diagnostic::error(DiagnosticKind::Import {
+            message: message.to_string(),
+            span: Span {
+                start: LineColumn { line: 1, column: 1 },
+                lines: 0,
+                end_column: 7,
+            },
+        });
+        let mut log = SarifLog::new();
+        log.add(Some("main.cad"), "import \"a\";", &[diagnostic]);
+        let log = parse_json(&log.render());
+
+        assert_eq!(
+            log.get("runs")
+                .at(0)
+                .get("results")
+                .at(0)
+                .get("message")
+                .get("text"),
+            &Json::String(message.to_string())
+        );
+    }
+
+    #[test]
+    fn test_uri_is_percent_encoded() {
+        assert_eq!(uri("parts/gear v2.cad"), "parts/gear%20v2.cad");
+        assert_eq!(uri("a#b:c.cad"), "a%23b%3Ac.cad");
+        assert_eq!(uri("/home/me/main.cad"), "file:///home/me/main.cad");
+        assert_eq!(uri("caf\u{e9}.cad"), "caf%C3%A9.cad");
+    }
+
+    #[test]
+    fn test_stdin_has_description_instead_of_uri() {
+        let mut log = SarifLog::new();
+        log.add(None, "  $", &[lex_error_at(1, 3)]);
+        let log = parse_json(&log.render());
+
+        let artifact_location = log
+            .get("runs")
+            .at(0)
+            .get("results")
+            .at(0)
+            .get("locations")
+            .at(0)
+            .get("physicalLocation")
+            .get("artifactLocation");
+        assert!(!artifact_location.has("uri"));
+        assert_eq!(
+            artifact_location.get("description").get("text"),
+            &Json::String("standard input".to_string())
+        );
+    }
+
+    #[test]
+    fn test_result_without_span_has_no_locations() {
+        let note = Diagnostic {
+            kind: DiagnosticKind::TooManyErrors {
+                suppressed_count: 3,
+            },
+            severity: Severity::Note,
+        };
+        let mut log = SarifLog::new();
+        log.add(None, "", &[note]);
+        let log = parse_json(&log.render());
+
+        let result = log.get("runs").at(0).get("results").at(0);
+        assert_eq!(result.get("level"), &Json::String("note".to_string()));
+        assert_eq!(
+            result.get("message").get("text"),
+            &Json::String("too many errors, 3 more not shown".to_string())
+        );
+        assert!(!result.has("locations"));
+    }
+}
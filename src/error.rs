@@ -0,0 +1,56 @@
+//! Error types reported by the compiler phases
+//!
+//! Each error carries enough structure (position, offending input) for
+//! callers to inspect it; `crate::diagnostic` turns them into reports.
+
+use crate::span::Span;
+
+// ============================================================================
+// Lexer Errors
+// ============================================================================
+
+/// Input the lexer could not turn into a token
+///
+/// The lexer records the error, emits a `Token::Error` in its place and
+/// carries on after the rejected input. The line and column are the start
+/// of `span`; `Span::start_byte_offset` gives the byte position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+/// What was wrong with the rejected input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A character that does not start any token; the span covers just
+    /// that character
+    UnexpectedCharacter(char),
+    /// An integer literal too large for an i32; the span covers the whole
+    /// literal
+    IntegerOverflow,
+    /// A string literal containing an escape other than `\\`, `\"`, `\n`
+    /// and `\t`; holds the character after the backslash, and the span
+    /// covers the whole literal
+    UnknownEscape(char),
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            LexErrorKind::UnexpectedCharacter(character) => {
+                write!(f, "unexpected character {:?}", character)
+            }
+            LexErrorKind::IntegerOverflow => write!(f, "integer literal does not fit in i32"),
+            LexErrorKind::UnknownEscape(escape) => write!(f, "unknown escape `\\{}`", escape),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
@@ -0,0 +1,720 @@
+//! Source formatting: turning a parsed program back into source text
+//!
+//! The output is canonical: two spaces of indentation per level, opening
+//! braces on the same line, one statement per line and a blank line between
+//! top-level definitions, which keep their order in the source. Parsing the output yields the same AST as the
+//! original source, apart from spans, and source that is already in this
+//! layout comes back unchanged. Number literals keep their spelling (`0xFF`,
+//! `1_000`, `2.5e-3`).
+//!
+//! Operator precedence is already explicit in the AST (every parenthesised
+//! subexpression is a `Paren` node), so binary operators are written without
+//! extra parentheses.
+
+use crate::ast::{
+    Expr, FieldDef, FunctionDef, ImplBlock, ImportDecl, Item, Param, Pattern, Program, SketchDef,
+    SketchItem, Stmt, StructDef, Type, Visibility,
+};
+use crate::lexer::escape_string;
+use std::fmt::Write;
+
+/// Indentation for one nesting level
+const INDENT: &str = "  ";
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+/// Reconstruct source text for a whole program
+///
+/// Definitions are written in the order imports, structs, impls, functions,
+/// sketches, since the program does not record how they were interleaved.
+pub fn unparse(program: &Program) -> String {
+    let mut printer = Printer::default();
+    printer.program(program);
+    printer.out
+}
+
+// ============================================================================
+// Printer
+// ============================================================================
+
+/// Accumulates formatted source text
+#[derive(Debug, Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    /// Write one line at the current indentation
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    // ========================================================================
+    // Top-Level Definitions
+    // ========================================================================
+
+    fn program(&mut self, program: &Program) {
+        for import in &program.imports {
+            self.import(import);
+        }
+
+        let mut first = program.imports.is_empty();
+        let mut separate = |printer: &mut Self| {
+            if !first {
+                printer.out.push('\n');
+            }
+            first = false;
+        };
+        for item in &program.items {
+            separate(self);
+            match item {
+                Item::Sketch(sketch) => self.sketch(sketch),
+                Item::Struct(def) => self.struct_def(def),
+                Item::Impl(block) => self.impl_block(block),
+                Item::Function(function) => self.function(function),
+            }
+        }
+    }
+
+    fn import(&mut self, import: &ImportDecl) {
+        let mut text = format!("import \"{}\"", escape_string(&import.path));
+        if let Some(items) = &import.items {
+            let names: Vec<_> = items.iter().map(|(name, _)| *name).collect();
+            write!(text, " use {}", names.join(", ")).unwrap();
+        }
+        text.push(';');
+        self.line(&text);
+    }
+
+    fn sketch(&mut self, sketch: &SketchDef) {
+        let mut header = format!("{}sketch {}", modifier(sketch.visibility), sketch.name);
+        if !sketch.parameters.is_empty() {
+            header.push_str(&params(&sketch.parameters));
+        }
+        self.block(&header, |printer| {
+            // Functions are set off from their neighbours by a blank line
+            let mut after_function = false;
+            for (i, item) in sketch.items.iter().enumerate() {
+                let is_function = matches!(item, SketchItem::Function(_));
+                if i > 0 && (is_function || after_function) {
+                    printer.out.push('\n');
+                }
+                match item {
+                    SketchItem::Stmt(stmt) => printer.stmt(stmt),
+                    SketchItem::Test(stmt) => printer.test(stmt),
+                    SketchItem::Function(function) => printer.function(function),
+                }
+                after_function = is_function;
+            }
+        });
+    }
+
+    fn struct_def(&mut self, def: &StructDef) {
+        let header = format!("{}struct {}", modifier(def.visibility), def.name);
+        self.block(&header, |printer| {
+            for field in &def.fields {
+                printer.field(field);
+            }
+            printer.methods(&def.methods, !def.fields.is_empty());
+        });
+    }
+
+    fn impl_block(&mut self, block: &ImplBlock) {
+        self.block(&format!("impl {}", block.target), |printer| {
+            printer.methods(&block.methods, false);
+        });
+    }
+
+    /// Methods separated by blank lines, and from any preceding fields
+    fn methods(&mut self, methods: &[FunctionDef], after_fields: bool) {
+        for (i, method) in methods.iter().enumerate() {
+            if i > 0 || after_fields {
+                self.out.push('\n');
+            }
+            self.function(method);
+        }
+    }
+
+    fn field(&mut self, field: &FieldDef) {
+        self.line(&format!(
+            "{}{}: {},",
+            modifier(field.visibility),
+            field.name,
+            ty(&field.ty)
+        ));
+    }
+
+    fn function(&mut self, function: &FunctionDef) {
+        let mut header = format!(
+            "{}fn {}{}",
+            modifier(function.visibility),
+            function.name,
+            params(&function.params)
+        );
+        if let Some(return_type) = &function.return_type {
+            write!(header, " -> {}", ty(return_type)).unwrap();
+        }
+        self.block(&header, |printer| {
+            for stmt in &function.body {
+                printer.stmt(stmt);
+            }
+            if let Some(result) = &function.result {
+                printer.line(&expr(result));
+            }
+        });
+    }
+
+    /// Write `header {`, the indented body and `}`, or `header {}` if the
+    /// body writes nothing
+    fn block(&mut self, header: &str, body: impl FnOnce(&mut Self)) {
+        self.line(&format!("{} {{", header));
+        self.block_body(body);
+    }
+
+    /// Continue the line of the closing brace just written with `text {`,
+    /// then write the block as `block` does: `} else {`
+    fn continue_block(&mut self, text: &str, body: impl FnOnce(&mut Self)) {
+        self.out.pop();
+        writeln!(self.out, " {} {{", text).unwrap();
+        self.block_body(body);
+    }
+
+    /// The indented body and closing brace of a block whose opening line
+    /// has been written
+    fn block_body(&mut self, body: impl FnOnce(&mut Self)) {
+        let start = self.out.len();
+        self.indent += 1;
+        body(self);
+        self.indent -= 1;
+        if self.out.len() == start {
+            // Empty body: close the brace on the header line
+            self.out.truncate(start - 1);
+            self.out.push_str("}\n");
+        } else {
+            self.line("}");
+        }
+    }
+
+    // ========================================================================
+    // Statements
+    // ========================================================================
+
+    fn test(&mut self, stmt: &Stmt) {
+        self.line("#[test]");
+        self.stmt(stmt);
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let {
+                name,
+                type_annotation,
+                init,
+                ..
+            } => {
+                let mut text = format!("let {}", name);
+                if let Some(annotation) = type_annotation {
+                    write!(text, ": {}", ty(annotation)).unwrap();
+                }
+                if let Some(init) = init {
+                    write!(text, " = {}", expr(init)).unwrap();
+                }
+                text.push(';');
+                self.line(&text);
+            }
+            Stmt::Assert {
+                condition, message, ..
+            } => {
+                let mut text = format!("assert {}", expr(condition));
+                if let Some(message) = message {
+                    write!(text, ", {}", expr(message)).unwrap();
+                }
+                text.push(';');
+                self.line(&text);
+            }
+            Stmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => self.if_stmt(condition, then_body, else_body.as_deref(), false),
+            Stmt::For {
+                label,
+                var,
+                iterable,
+                body,
+                ..
+            } => {
+                let header = format!("{}for {} in {}", loop_label(*label), var, expr(iterable));
+                self.block(&header, |printer| printer.stmts(body))
+            }
+            Stmt::While {
+                label,
+                condition,
+                body,
+                ..
+            } => {
+                let header = format!("{}while {}", loop_label(*label), expr(condition));
+                self.block(&header, |printer| printer.stmts(body))
+            }
+            Stmt::With { context, body, .. } => self
+                .block(&format!("with {}", expr(context)), |printer| {
+                    printer.stmts(body)
+                }),
+            Stmt::CompoundAssign {
+                target, op, value, ..
+            } => self.line(&format!(
+                "{} {} {};",
+                expr(target),
+                op.symbol(),
+                expr(value)
+            )),
+            Stmt::Break { label, .. } => match label {
+                Some(label) => self.line(&format!("break '{};", label)),
+                None => self.line("break;"),
+            },
+            Stmt::Continue { label, .. } => match label {
+                Some(label) => self.line(&format!("continue '{};", label)),
+                None => self.line("continue;"),
+            },
+            // The skipped source is not kept, so there is nothing to print
+            Stmt::Error { .. } => self.line("/* statement with errors */"),
+        }
+    }
+
+    /// Write an if statement; `else_if` continues the closing brace line of
+    /// the previous branch
+    fn if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_body: &[Stmt],
+        else_body: Option<&[Stmt]>,
+        else_if: bool,
+    ) {
+        let header = format!("if {}", expr(condition));
+        let then = |printer: &mut Self| printer.stmts(then_body);
+        if else_if {
+            self.continue_block(&format!("else {}", header), then);
+        } else {
+            self.block(&header, then);
+        }
+
+        match else_body {
+            None => {}
+            // A lone nested if is written as an `else if` chain
+            Some(
+                [
+                    Stmt::If {
+                        condition,
+                        then_body,
+                        else_body,
+                        ..
+                    },
+                ],
+            ) => self.if_stmt(condition, then_body, else_body.as_deref(), true),
+            Some(body) => self.continue_block("else", |printer| printer.stmts(body)),
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+}
+
+/// The `'label: ` written in front of a labelled loop
+fn loop_label(label: Option<&str>) -> String {
+    label.map_or_else(String::new, |label| format!("'{}: ", label))
+}
+
+// ============================================================================
+// Types and Parameters
+// ============================================================================
+
+/// The `pub ` prefix of a public definition
+fn modifier(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "pub ",
+        Visibility::Private => "",
+    }
+}
+
+fn ty(ty: &Type) -> String {
+    match ty {
+        Type::Bool { .. } => "bool".to_string(),
+        Type::I32 { .. } => "i32".to_string(),
+        Type::F64 { .. } => "f64".to_string(),
+        Type::Real { .. } => "Real".to_string(),
+        Type::Algebraic { .. } => "Algebraic".to_string(),
+        Type::Named { name, .. } => name.to_string(),
+        Type::Ref { inner, .. } => format!("&{}", self::ty(inner)),
+        Type::ImplTrait { trait_name, .. } => format!("impl {}", trait_name),
+    }
+}
+
+fn params(params: &[Param]) -> String {
+    let params: Vec<_> = params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, ty(&param.ty)))
+        .collect();
+    format!("({})", params.join(", "))
+}
+
+// ============================================================================
+// Expressions
+// ============================================================================
+
+/// Format an expression on a single line
+///
+/// Operands are stored as precedence-specific subenums; they are converted
+/// back to `Expr` so every operand is formatted the same way.
+fn expr(expr: &Expr) -> String {
+    fn binary<'src>(lhs: impl Into<Expr<'src>>, op: &str, rhs: impl Into<Expr<'src>>) -> String {
+        format!(
+            "{} {} {}",
+            self::expr(&lhs.into()),
+            op,
+            self::expr(&rhs.into())
+        )
+    }
+
+    /// The parser wraps the right operand of `and`/`or` and of the bitwise
+    /// operators in a `Paren` of its own, so that one is left out to avoid
+    /// doubling it on every round trip
+    fn logical<'src>(lhs: impl Into<Expr<'src>>, op: &str, rhs: impl Into<Expr<'src>>) -> String {
+        match rhs.into() {
+            Expr::Paren { inner, .. } => binary(lhs, op, *inner),
+            rhs => binary(lhs, op, rhs),
+        }
+    }
+
+    fn list(exprs: &[Expr]) -> String {
+        let exprs: Vec<_> = exprs.iter().map(self::expr).collect();
+        exprs.join(", ")
+    }
+
+    match expr {
+        Expr::Pipe { lhs, rhs_fn, .. } => binary((**lhs).clone(), "|>", (**rhs_fn).clone()),
+        Expr::Range { start, end, .. } => format!("{}..{}", self::expr(start), self::expr(end)),
+        Expr::And { lhs, rhs, .. } => logical((**lhs).clone(), "and", (**rhs).clone()),
+        Expr::Or { lhs, rhs, .. } => logical((**lhs).clone(), "or", (**rhs).clone()),
+        Expr::BitAnd { lhs, rhs, .. } => logical((**lhs).clone(), "&", (**rhs).clone()),
+        Expr::BitOr { lhs, rhs, .. } => logical((**lhs).clone(), "|", (**rhs).clone()),
+        Expr::Shl { lhs, rhs, .. } => logical((**lhs).clone(), "<<", (**rhs).clone()),
+        Expr::Shr { lhs, rhs, .. } => logical((**lhs).clone(), ">>", (**rhs).clone()),
+        Expr::Eq { lhs, rhs, .. } => binary((**lhs).clone(), "==", (**rhs).clone()),
+        Expr::NotEq { lhs, rhs, .. } => binary((**lhs).clone(), "!=", (**rhs).clone()),
+        Expr::Lt { lhs, rhs, .. } => binary((**lhs).clone(), "<", (**rhs).clone()),
+        Expr::Gt { lhs, rhs, .. } => binary((**lhs).clone(), ">", (**rhs).clone()),
+        Expr::LtEq { lhs, rhs, .. } => binary((**lhs).clone(), "<=", (**rhs).clone()),
+        Expr::GtEq { lhs, rhs, .. } => binary((**lhs).clone(), ">=", (**rhs).clone()),
+        Expr::In { lhs, rhs, .. } => binary((**lhs).clone(), "in", (**rhs).clone()),
+        Expr::NotIn { lhs, rhs, .. } => binary((**lhs).clone(), "not in", (**rhs).clone()),
+        Expr::Add { lhs, rhs, .. } => binary((**lhs).clone(), "+", (**rhs).clone()),
+        Expr::Sub { lhs, rhs, .. } => binary((**lhs).clone(), "-", (**rhs).clone()),
+        Expr::Mul { lhs, rhs, .. } => binary((**lhs).clone(), "*", (**rhs).clone()),
+        Expr::Div { lhs, rhs, .. } => binary((**lhs).clone(), "/", (**rhs).clone()),
+        Expr::Mod { lhs, rhs, .. } => binary((**lhs).clone(), "%", (**rhs).clone()),
+        Expr::Pow { lhs, rhs, .. } => binary((**lhs).clone(), "^", (**rhs).clone()),
+        Expr::Paren { inner, .. } => format!("({})", self::expr(inner)),
+        Expr::Neg { inner, .. } => format!("-{}", self::expr(&(**inner).clone().into())),
+        Expr::BitNot { inner, .. } => format!("~{}", self::expr(&(**inner).clone().into())),
+        Expr::Ref { inner, .. } => format!("&{}", self::expr(&(**inner).clone().into())),
+        Expr::Var { name, .. } => name.to_string(),
+        // Number literals are written as they were spelled in the source
+        Expr::IntLit { text, .. } | Expr::FloatLit { text, .. } => text.to_string(),
+        Expr::BoolLit { value, .. } => value.to_string(),
+        Expr::StrLit { value, .. } => format!("\"{}\"", escape_string(value)),
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            let else_branch = match &**else_branch {
+                nested @ Expr::If { .. } => self::expr(nested),
+                other => format!("{{ {} }}", self::expr(other)),
+            };
+            format!(
+                "if {} {{ {} }} else {}",
+                self::expr(condition),
+                self::expr(then_branch),
+                else_branch
+            )
+        }
+        Expr::Call { name, args, .. } => format!("{}({})", name, list(args)),
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => format!("{}.{}({})", self::expr(receiver), method, list(args)),
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => format!("{}.{}", self::expr(receiver), field),
+        Expr::ArrayLit { elements, .. } => format!("[{}]", list(elements)),
+        Expr::StructLit { name, fields, .. } if fields.is_empty() => format!("{} {{}}", name),
+        Expr::StructLit { name, fields, .. } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(field, value)| format!("{}: {}", field, self::expr(value)))
+                .collect();
+            format!("{} {{ {} }}", name, fields.join(", "))
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            let arms: Vec<_> = arms
+                .iter()
+                .map(|arm| format!("{} => {}", pattern(&arm.pattern), self::expr(&arm.body)))
+                .collect();
+            format!("match {} {{ {} }}", self::expr(scrutinee), arms.join(", "))
+        }
+    }
+}
+
+/// Write a match arm's pattern, using the `Circle { r }` shorthand for a
+/// field bound to a name of its own
+fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard { .. } => "_".to_string(),
+        Pattern::Literal { value } => expr(&value.clone().into()),
+        Pattern::Binding { name, .. } => name.to_string(),
+        Pattern::Struct { name, fields, .. } if fields.is_empty() => format!("{} {{}}", name),
+        Pattern::Struct { name, fields, .. } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(field, field_pattern)| match field_pattern {
+                    Pattern::Binding { name, .. } if name == field => field.to_string(),
+                    other => format!("{}: {}", field, self::pattern(other)),
+                })
+                .collect();
+            format!("{} {{ {} }}", name, fields.join(", "))
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+    use crate::parser::program;
+    use chumsky::Parser;
+
+    /// Debug output of the program parsed from `source`, with spans removed
+    fn parse_without_spans(source: &str) -> String {
+        let Ok((tokens, errors)) = tokenize(source);
+        assert_eq!(errors, [], "lex errors in {:?}", source);
+        let program = program()
+            .parse(&tokens)
+            .into_result()
+            .unwrap_or_else(|e| panic!("parse errors in {:?}: {:?}", source, e));
+        strip_spans(&format!("{:?}", program))
+    }
+
+    /// Replace every `Span { .. }` in debug output with `_`
+    fn strip_spans(debug: &str) -> String {
+        let mut out = String::new();
+        let mut rest = debug;
+        while let Some(start) = rest.find("Span {") {
+            out.push_str(&rest[..start]);
+            out.push('_');
+            let mut depth = 0;
+            let mut end = start;
+            for (i, c) in rest[start..].char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = start + i + 1;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn format_source(source: &str) -> String {
+        let Ok((tokens, _)) = tokenize(source);
+        let program = program().parse(&tokens).into_result().unwrap();
+        unparse(&program)
+    }
+
+    /// Formatting must preserve the AST and be stable once formatted
+    fn assert_round_trip(source: &str) {
+        let formatted = format_source(source);
+        assert_eq!(
+            parse_without_spans(&formatted),
+            parse_without_spans(source),
+            "formatted source:\n{}",
+            formatted
+        );
+        assert_eq!(format_source(&formatted), formatted);
+    }
+
+    #[test]
+    fn test_strip_spans() {
+        assert_eq!(
+            strip_spans(
+                "Var { name: \"x\", span: Span { start: LineColumn { line: 1, column: 1 }, lines: 0, end_column: 2 } }"
+            ),
+            "Var { name: \"x\", span: _ }"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_snippets() {
+        let snippets = [
+            "",
+            "sketch Main {}",
+            "sketch Main { let x = 1; let y: f64 = 2.0; let z: bool; }",
+            "sketch Main { let x = 1 + 2 * 3 - 4 / 5 % 6; }",
+            "sketch Main { let x = (1 + 2) * (3 - 4); }",
+            "sketch Main { let x = 2 ^ 3 ^ 4; let y = (2 ^ 3) ^ 4; }",
+            "sketch Main { let x = -a + --b * &c; let y = -(a + b); }",
+            "sketch Main { let c = a == b and c != d or e < f; }",
+            "sketch Main { let c = a <= b and (c >= d or e > f); }",
+            "sketch Main { let c = (a and b) == true; }",
+            r#"sketch Main { let a = match s { Circle { r } => r * r, Rect { w, h: 0 } => 0, Empty {} => 1, "x" => 2, _ => area(s) }; }"#,
+            "sketch Main { let b = 1 + match n { 0 => zero, other => other * 2.5 }; }",
+            "sketch Main { let m = a & b | c << 2 >> n; let f = ~flags & (x | ~y) and ok; }",
+            "sketch Main { let p = flags | mask & x << 2; let q = (a | b) & c << 1; }",
+            "sketch Main { assert p in points; assert q + 1 not in [1, 2] and r in s; }",
+            "sketch Main { let p = Point { x: 1.5, y: -2.0 }; let e = Empty {}; }",
+            "sketch Main { let a = [1, 2, [3, 4], []]; }",
+            "sketch Main { if w > 10 { assert h > 2; } if a { let x = 1; } else if b { let x = 2; } else { let x = 3; } if ready {} else {} }",
+            "sketch Main { for i in 0..5 { assert i < 5; } for p in points {} let r = [0..10]; }",
+            "sketch Main { while dist > tol { assert dist > 0; } while a and b {} }",
+            "sketch Main { for i in 0..5 { if i > 3 { break; } continue; } }",
+            "sketch Main { with shift { let p: Point = line.start; with sketch.plane {} } }",
+            "sketch Main { total += w * 2; p.x -= 1; s *= 2; s /= 3; n %= 4; e ^= 2; }",
+            "sketch Main { let r = 1.0; #[test] assert r > 0; #[test] for i in 0..3 {} fn f() {} }",
+            "sketch Main { 'rows: for r in rows { 'cells: while true { continue 'rows; break 'cells; } } }",
+            "fn sign(x: f64) -> f64 { let y = 2 * if x < 0 { -1 } else if x == 0 { 0 } else { 1 }; if x < 0 { -1 } else { 1 } }",
+            r#"sketch Main { let label = "side A"; let q = "say \"hi\"\n\t\\"; let e = ""; }"#,
+            "sketch Main { let d = distance(p.start, p.end.x, origin()); }",
+            "sketch Main { let n = line.direction().normalize().scale(2.0, 1); }",
+            "sketch Main { assert width > 0; assert length >= 2 * d, length; }",
+            "sketch Main { let s = base |> fillet(2.0) |> lift; let t = (a or b |> f) * 2; }",
+            "sketch Bolt(diameter: f64, head: &Point) { let r = diameter / 2.0; fn half() -> f64 { r / 2.0 } }",
+            "struct Point { x: f64, y: f64 }\nstruct Empty {}\nstruct Line { start: &Point, end: &Point, }",
+            "struct Circle { radius: Real, fn area() -> Real { self.radius ^ 2 } fn grow(by: f64) { let r = self.radius + by; } }",
+            "impl Circle { fn diameter() -> f64 { self.radius * 2.0 } }\nimpl Circle {}",
+            "fn origin() -> Point { Point { x: 0, y: 0 } }\nfn noop() {}\nfn check(a: i32, b: Algebraic) { assert a > 0; }",
+            "fn unit() -> impl Shape { Circle { radius: 1.0 } }",
+            "pub struct Point { pub x: f64, y: f64, pub fn len() -> f64 { self.x } }\nimpl Point { pub fn zero() {} }\npub fn origin() {}\npub sketch Main { fn f() {} }",
+            "import \"lib/shapes.cad\";\nimport \"util.cad\" use Point, Line;\nsketch Main { let p = Point { x: 1, y: 2 }; }",
+            r#"import "parts/\"gear\"\tv2.cad";"#,
+            "sketch Main { let m = 0xFF & 0o17 | 0B1010; let n = 1_000_000 + 3.141_592; let e = 2.5e-3 * 1E6 + 7e0; }",
+        ];
+
+        for source in snippets {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn test_canonical_source_is_unchanged() {
+        let sources = [
+            "sketch Main {\n\
+             \x20 let mask = 0xFF_FF & 0b1010 | 0o755;\n\
+             \x20 let big = 1_000_000;\n\
+             \x20 let tol = 2.5e-3 + 1E6 + 3.141_592 + 1.50;\n\
+             }\n",
+            "import \"a.cad\" use Point;\n\
+             \n\
+             struct Point {\n\
+             \x20 x: f64,\n\
+             }\n\
+             \n\
+             pub sketch Main(d: f64) {\n\
+             \x20 let r = d / 2.0;\n\
+             \x20 #[test]\n\
+             \x20 assert r > 0;\n\
+             \n\
+             \x20 fn half() -> f64 {\n\
+             \x20   r / 2\n\
+             \x20 }\n\
+             }\n",
+        ];
+
+        for source in sources {
+            assert_eq!(format_source(source), source);
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        let formatted = format_source(
+            "import \"a.cad\"; struct Point { x: f64, fn len() -> f64 { x } } sketch Main { let p = Point { x: 1.0 }; fn f() {} }",
+        );
+
+        assert_eq!(
+            formatted,
+            "import \"a.cad\";\n\
+             \n\
+             struct Point {\n\
+             \x20 x: f64,\n\
+             \n\
+             \x20 fn len() -> f64 {\n\
+             \x20   x\n\
+             \x20 }\n\
+             }\n\
+             \n\
+             sketch Main {\n\
+             \x20 let p = Point { x: 1.0 };\n\
+             \n\
+             \x20 fn f() {}\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_sketch_items_keep_source_order() {
+        let formatted = format_source(
+            "sketch Main { let a = 1; #[test] assert a == 1; fn f() {} let b = 2; #[test] assert b > a; }",
+        );
+
+        assert_eq!(
+            formatted,
+            "sketch Main {\n\
+             \x20 let a = 1;\n\
+             \x20 #[test]\n\
+             \x20 assert a == 1;\n\
+             \n\
+             \x20 fn f() {}\n\
+             \n\
+             \x20 let b = 2;\n\
+             \x20 #[test]\n\
+             \x20 assert b > a;\n\
+             }\n"
+        );
+        assert_round_trip(&formatted);
+    }
+
+    #[test]
+    fn test_logical_rhs_paren_is_not_doubled() {
+        assert_eq!(
+            format_source("fn f() { a and (b or c) }"),
+            "fn f() {\n  a and (b or c)\n}\n"
+        );
+    }
+}
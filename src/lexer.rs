@@ -1,24 +1,15 @@
 use logos::Lexer;
 use logos::Logos;
 use logos::Skip;
+use std::convert::Infallible;
+
+use crate::error::{LexError, LexErrorKind};
+pub use crate::span::{LineColumn, Span};
 
 // ============================================================================
-// Position and Span Types
+// Line Tracking
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct LineColumn {
-    pub line: usize,
-    pub column: usize,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Span {
-    pub start: LineColumn,
-    pub lines: usize,
-    pub end_column: usize,
-}
-
 pub struct NewLineTracer {
     pub line: usize,
     pub last_newline_char_index: usize,
@@ -52,6 +43,18 @@ pub trait TokenTrait {
     /// For fixed tokens (keywords, operators), this is the literal text
     /// For dynamic tokens (identifiers, literals), this is the parsed value
     fn value_str(&self) -> &str;
+
+    /// Get the span covered by this token
+    /// The default covers `value_str()` on a single line, which is exact for
+    /// fixed tokens; dynamic tokens return their recorded span instead
+    fn span(&self) -> Span {
+        let start = self.position();
+        Span {
+            start,
+            lines: 0,
+            end_column: start.column + self.value_str().len(),
+        }
+    }
 }
 
 // ============================================================================
@@ -110,13 +113,23 @@ fixed_token!(TokenContainer, Container, "container");
 fixed_token!(TokenFn, Fn, "fn");
 fixed_token!(TokenLet, Let, "let");
 fixed_token!(TokenFor, For, "for");
+fixed_token!(TokenWhile, While, "while");
+fixed_token!(TokenBreak, Break, "break");
+fixed_token!(TokenContinue, Continue, "continue");
+fixed_token!(TokenMatch, Match, "match");
 fixed_token!(TokenIn, In, "in");
+fixed_token!(TokenNot, Not, "not");
 fixed_token!(TokenWith, With, "with");
 fixed_token!(TokenIf, If, "if");
 fixed_token!(TokenElse, Else, "else");
 fixed_token!(TokenOr, Or, "or");
 fixed_token!(TokenAnd, And, "and");
 fixed_token!(TokenReturn, Return, "return");
+fixed_token!(TokenAssert, Assert, "assert");
+fixed_token!(TokenImport, Import, "import");
+fixed_token!(TokenUse, Use, "use");
+fixed_token!(TokenImpl, Impl, "impl");
+fixed_token!(TokenPub, Pub, "pub");
 fixed_token!(TokenTrue, True, "true");
 fixed_token!(TokenFalse, False, "false");
 fixed_token!(TokenSelf, SelfKw, "self");
@@ -139,6 +152,16 @@ fixed_token!(TokenDivide, Divide, "/");
 fixed_token!(TokenPower, Power, "^");
 fixed_token!(TokenModulo, Modulo, "%");
 fixed_token!(TokenAmpersand, Ampersand, "&");
+fixed_token!(TokenHash, Hash, "#");
+fixed_token!(TokenBitwiseNot, BitwiseNot, "~");
+fixed_token!(TokenShiftLeft, ShiftLeft, "<<");
+fixed_token!(TokenShiftRight, ShiftRight, ">>");
+fixed_token!(TokenPlusAssign, PlusAssign, "+=");
+fixed_token!(TokenMinusAssign, MinusAssign, "-=");
+fixed_token!(TokenMulAssign, MulAssign, "*=");
+fixed_token!(TokenDivAssign, DivAssign, "/=");
+fixed_token!(TokenModAssign, ModAssign, "%=");
+fixed_token!(TokenPowAssign, PowAssign, "^=");
 
 // ============================================================================
 // Punctuation Tokens
@@ -156,7 +179,9 @@ fixed_token!(TokenRightBracket, RightBracket, "]");
 fixed_token!(TokenLeftBrace, LeftBrace, "{");
 fixed_token!(TokenRightBrace, RightBrace, "}");
 fixed_token!(TokenPipe, Pipe, "|");
+fixed_token!(TokenPipeArrow, PipeArrow, "|>");
 fixed_token!(TokenArrow, Arrow, "->");
+fixed_token!(TokenFatArrow, FatArrow, "=>");
 
 // ============================================================================
 // Built-in Type Tokens
@@ -172,83 +197,110 @@ fixed_token!(TokenAlgebraicType, AlgebraicType, "Algebraic");
 // Dynamic Content Tokens (Literals and Identifiers)
 // ============================================================================
 
+/// Float literal with a fraction (`2.5`), an exponent (`1e6`) or both
+/// (`2.3E-4`); `text` is the literal as written
 #[derive(Debug, Clone, PartialEq)]
-pub struct TokenFloatLiteral {
+pub struct TokenFloatLiteral<'src> {
     pub value: f64,
+    pub text: &'src str,
     pub span: Span,
 }
 
-impl TokenFloatLiteral {
-    pub fn new(value: f64, span: Span) -> Self {
-        Self { value, span }
+impl<'src> TokenFloatLiteral<'src> {
+    pub fn new(value: f64, text: &'src str, span: Span) -> Self {
+        Self { value, text, span }
     }
 
-    pub fn from_lexer<'src>(lex: &mut Lexer<'src, Token<'src>>) -> Option<Self> {
-        let value = lex.slice().parse::<f64>().ok()?;
+    pub fn from_lexer(lex: &mut Lexer<'src, Token<'src>>) -> Option<Self> {
+        let text = lex.slice();
+        let value = text.replace('_', "").parse::<f64>().ok()?;
         let span = derive_span_no_newline(lex);
-        Some(Self::new(value, span))
+        Some(Self::new(value, text, span))
     }
 }
 
-impl TokenTrait for TokenFloatLiteral {
+impl<'src> TokenTrait for TokenFloatLiteral<'src> {
     fn position(&self) -> LineColumn {
         self.span.start
     }
 
     fn value_str(&self) -> &str {
-        "float_literal"
+        self.text
+    }
+
+    fn span(&self) -> Span {
+        self.span
     }
 }
 
-impl<'src> From<TokenFloatLiteral> for Token<'src> {
-    fn from(token: TokenFloatLiteral) -> Self {
+impl<'src> From<TokenFloatLiteral<'src>> for Token<'src> {
+    fn from(token: TokenFloatLiteral<'src>) -> Self {
         Token::FloatLiteral(token)
     }
 }
 
-impl std::fmt::Display for TokenFloatLiteral {
+impl<'src> std::fmt::Display for TokenFloatLiteral<'src> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.value)
+        write!(f, "{}", self.text)
     }
 }
 
+/// Integer literal in decimal, hex (`0xFF`), octal (`0o755`) or binary
+/// (`0b1010`); `text` is the literal as written
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TokenIntLiteral {
+pub struct TokenIntLiteral<'src> {
     pub value: i32,
+    pub text: &'src str,
     pub span: Span,
 }
 
-impl TokenIntLiteral {
-    pub fn new(value: i32, span: Span) -> Self {
-        Self { value, span }
+impl<'src> TokenIntLiteral<'src> {
+    pub fn new(value: i32, text: &'src str, span: Span) -> Self {
+        Self { value, text, span }
     }
 
-    pub fn from_lexer<'src>(lex: &mut Lexer<'src, Token<'src>>) -> Option<Self> {
-        let value = lex.slice().parse::<i32>().ok()?;
+    pub fn from_lexer(lex: &mut Lexer<'src, Token<'src>>) -> Result<Self, InvalidToken> {
+        let text = lex.slice();
+        let (digits, radix) = match text.get(..2) {
+            Some("0x" | "0X") => (&text[2..], 16),
+            Some("0o" | "0O") => (&text[2..], 8),
+            Some("0b" | "0B") => (&text[2..], 2),
+            _ => (text, 10),
+        };
+        // The regexes only admit valid digits, so parsing can only fail on
+        // overflow. Parse wider than i32 so the conversion catches the rest.
+        let value = i64::from_str_radix(&digits.replace('_', ""), radix)
+            .ok()
+            .and_then(|value| i32::try_from(value).ok())
+            .ok_or(InvalidToken::IntegerOverflow)?;
         let span = derive_span_no_newline(lex);
-        Some(Self::new(value, span))
+        Ok(Self::new(value, text, span))
     }
 }
 
-impl TokenTrait for TokenIntLiteral {
+impl<'src> TokenTrait for TokenIntLiteral<'src> {
     fn position(&self) -> LineColumn {
         self.span.start
     }
 
     fn value_str(&self) -> &str {
-        "int_literal"
+        self.text
+    }
+
+    fn span(&self) -> Span {
+        self.span
     }
 }
 
-impl<'src> From<TokenIntLiteral> for Token<'src> {
-    fn from(token: TokenIntLiteral) -> Self {
+impl<'src> From<TokenIntLiteral<'src>> for Token<'src> {
+    fn from(token: TokenIntLiteral<'src>) -> Self {
         Token::IntLiteral(token)
     }
 }
 
-impl std::fmt::Display for TokenIntLiteral {
+impl<'src> std::fmt::Display for TokenIntLiteral<'src> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.value)
+        write!(f, "{}", self.text)
     }
 }
 
@@ -278,6 +330,10 @@ impl<'src> TokenTrait for TokenIdentifier<'src> {
     fn value_str(&self) -> &str {
         self.name
     }
+
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl<'src> From<TokenIdentifier<'src>> for Token<'src> {
@@ -292,13 +348,198 @@ impl<'src> std::fmt::Display for TokenIdentifier<'src> {
     }
 }
 
+/// Loop label; `name` is the text after the leading quote, and `text`
+/// includes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenLabel<'src> {
+    pub name: &'src str,
+    pub text: &'src str,
+    pub span: Span,
+}
+
+impl<'src> TokenLabel<'src> {
+    pub fn new(text: &'src str, span: Span) -> Self {
+        Self {
+            name: &text[1..],
+            text,
+            span,
+        }
+    }
+
+    pub fn from_lexer(lex: &mut Lexer<'src, Token<'src>>) -> Self {
+        let text = lex.slice();
+        let span = derive_span_no_newline(lex);
+        Self::new(text, span)
+    }
+}
+
+impl<'src> TokenTrait for TokenLabel<'src> {
+    fn position(&self) -> LineColumn {
+        self.span.start
+    }
+
+    fn value_str(&self) -> &str {
+        self.text
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<'src> From<TokenLabel<'src>> for Token<'src> {
+    fn from(token: TokenLabel<'src>) -> Self {
+        Token::Label(token)
+    }
+}
+
+impl<'src> std::fmt::Display for TokenLabel<'src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// String literal; `value` is the text between the quotes, with escape
+/// sequences left as written, and `text` includes the quotes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStringLiteral<'src> {
+    pub value: &'src str,
+    pub text: &'src str,
+    pub span: Span,
+}
+
+impl<'src> TokenStringLiteral<'src> {
+    pub fn new(text: &'src str, span: Span) -> Self {
+        Self {
+            value: &text[1..text.len() - 1],
+            text,
+            span,
+        }
+    }
+
+    pub fn from_lexer(lex: &mut Lexer<'src, Token<'src>>) -> Result<Self, InvalidToken> {
+        let text = lex.slice();
+        // Reject unknown escapes here so `unescaped` cannot fail later
+        unescape(&text[1..text.len() - 1]).map_err(InvalidToken::UnknownEscape)?;
+        let span = derive_span_no_newline(lex);
+        Ok(Self::new(text, span))
+    }
+
+    /// The string's contents with escape sequences replaced
+    pub fn unescaped(&self) -> String {
+        unescape(self.value).unwrap_or_else(|_| self.value.to_string())
+    }
+}
+
+/// Replace the escape sequences `\\`, `\"`, `\n` and `\t`
+///
+/// Any other escape is an error holding the character after the backslash.
+/// The literal's regex guarantees a character follows every backslash.
+fn unescape(raw: &str) -> Result<String, char> {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next().unwrap_or('\\') {
+            '\\' => out.push('\\'),
+            '"' => out.push('"'),
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            escape => return Err(escape),
+        }
+    }
+    Ok(out)
+}
+
+/// Write `value` as the contents of a string literal, the inverse of
+/// `TokenStringLiteral::unescaped`
+pub fn escape_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl<'src> TokenTrait for TokenStringLiteral<'src> {
+    fn position(&self) -> LineColumn {
+        self.span.start
+    }
+
+    fn value_str(&self) -> &str {
+        self.text
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<'src> From<TokenStringLiteral<'src>> for Token<'src> {
+    fn from(token: TokenStringLiteral<'src>) -> Self {
+        Token::StringLiteral(token)
+    }
+}
+
+impl<'src> std::fmt::Display for TokenStringLiteral<'src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// ============================================================================
+// Lexer Errors
+// ============================================================================
+
+impl TokenTrait for LexError {
+    fn position(&self) -> LineColumn {
+        self.span.start
+    }
+
+    fn value_str(&self) -> &str {
+        "error"
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<'src> From<LexError> for Token<'src> {
+    fn from(error: LexError) -> Self {
+        Token::Error(error)
+    }
+}
+
 // ============================================================================
 // Main Token Enum
 // ============================================================================
 
+/// Why Logos rejected a slice of input; `tokenize` turns it into a `LexError`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum InvalidToken {
+    /// No token starts here
+    #[default]
+    NoMatch,
+    /// An integer literal that does not fit in an i32
+    IntegerOverflow,
+    /// A string literal with an unknown escape after its backslash
+    UnknownEscape(char),
+}
+
 /// Token enum with direct Logos integration
 #[derive(Logos, Debug, Clone, PartialEq)]
 #[logos(extras = NewLineTracer)]
+#[logos(error = InvalidToken)]
 #[logos(skip(r"[ \t\f]+"))]
 #[logos(skip(r"\n", newline_callback))]
 #[logos(skip(r"//[^\n]*"))]
@@ -315,8 +556,18 @@ pub enum Token<'src> {
     Let(TokenLet),
     #[token("for", TokenFor::from_lexer)]
     For(TokenFor),
+    #[token("while", TokenWhile::from_lexer)]
+    While(TokenWhile),
+    #[token("break", TokenBreak::from_lexer)]
+    Break(TokenBreak),
+    #[token("continue", TokenContinue::from_lexer)]
+    Continue(TokenContinue),
+    #[token("match", TokenMatch::from_lexer)]
+    Match(TokenMatch),
     #[token("in", TokenIn::from_lexer)]
     In(TokenIn),
+    #[token("not", TokenNot::from_lexer)]
+    Not(TokenNot),
     #[token("with", TokenWith::from_lexer)]
     With(TokenWith),
     #[token("if", TokenIf::from_lexer)]
@@ -329,6 +580,16 @@ pub enum Token<'src> {
     And(TokenAnd),
     #[token("return", TokenReturn::from_lexer)]
     Return(TokenReturn),
+    #[token("assert", TokenAssert::from_lexer)]
+    Assert(TokenAssert),
+    #[token("import", TokenImport::from_lexer)]
+    Import(TokenImport),
+    #[token("use", TokenUse::from_lexer)]
+    Use(TokenUse),
+    #[token("impl", TokenImpl::from_lexer)]
+    Impl(TokenImpl),
+    #[token("pub", TokenPub::from_lexer)]
+    Pub(TokenPub),
     #[token("true", TokenTrue::from_lexer)]
     True(TokenTrue),
     #[token("false", TokenFalse::from_lexer)]
@@ -365,6 +626,26 @@ pub enum Token<'src> {
     Modulo(TokenModulo),
     #[token("&", TokenAmpersand::from_lexer)]
     Ampersand(TokenAmpersand),
+    #[token("#", TokenHash::from_lexer)]
+    Hash(TokenHash),
+    #[token("~", TokenBitwiseNot::from_lexer)]
+    BitwiseNot(TokenBitwiseNot),
+    #[token("<<", TokenShiftLeft::from_lexer)]
+    ShiftLeft(TokenShiftLeft),
+    #[token(">>", TokenShiftRight::from_lexer)]
+    ShiftRight(TokenShiftRight),
+    #[token("+=", TokenPlusAssign::from_lexer)]
+    PlusAssign(TokenPlusAssign),
+    #[token("-=", TokenMinusAssign::from_lexer)]
+    MinusAssign(TokenMinusAssign),
+    #[token("*=", TokenMulAssign::from_lexer)]
+    MulAssign(TokenMulAssign),
+    #[token("/=", TokenDivAssign::from_lexer)]
+    DivAssign(TokenDivAssign),
+    #[token("%=", TokenModAssign::from_lexer)]
+    ModAssign(TokenModAssign),
+    #[token("^=", TokenPowAssign::from_lexer)]
+    PowAssign(TokenPowAssign),
 
     // Punctuation
     #[token(":", TokenColon::from_lexer)]
@@ -391,8 +672,12 @@ pub enum Token<'src> {
     RightBrace(TokenRightBrace),
     #[token("|", TokenPipe::from_lexer)]
     Pipe(TokenPipe),
+    #[token("|>", TokenPipeArrow::from_lexer)]
+    PipeArrow(TokenPipeArrow),
     #[token("->", TokenArrow::from_lexer)]
     Arrow(TokenArrow),
+    #[token("=>", TokenFatArrow::from_lexer)]
+    FatArrow(TokenFatArrow),
 
     // Built-in types (must come before Identifier regex)
     #[token("bool", TokenBoolType::from_lexer)]
@@ -407,12 +692,27 @@ pub enum Token<'src> {
     AlgebraicType(TokenAlgebraicType),
 
     // Literals (order matters - float must come before int, identifiers must be last)
-    #[regex(r"\d+\.\d+", TokenFloatLiteral::from_lexer)]
-    FloatLiteral(TokenFloatLiteral),
-    #[regex(r"\d+", TokenIntLiteral::from_lexer)]
-    IntLiteral(TokenIntLiteral),
+    // A fraction, an exponent or both, so plain integers stay integers.
+    // Underscores may only separate digits.
+    #[regex(
+        r"\d+(_\d+)*\.\d+(_\d+)*([eE][+-]?\d+(_\d+)*)?",
+        TokenFloatLiteral::from_lexer
+    )]
+    #[regex(r"\d+(_\d+)*[eE][+-]?\d+(_\d+)*", TokenFloatLiteral::from_lexer)]
+    FloatLiteral(TokenFloatLiteral<'src>),
+    #[regex(r"\d+(_\d+)*", TokenIntLiteral::from_lexer)]
+    #[regex(r"0[xX][0-9a-fA-F]+(_[0-9a-fA-F]+)*", TokenIntLiteral::from_lexer)]
+    #[regex(r"0[oO][0-7]+(_[0-7]+)*", TokenIntLiteral::from_lexer)]
+    #[regex(r"0[bB][01]+(_[01]+)*", TokenIntLiteral::from_lexer)]
+    IntLiteral(TokenIntLiteral<'src>),
+    #[regex(r#""([^"\\\n]|\\[^\n])*""#, TokenStringLiteral::from_lexer)]
+    StringLiteral(TokenStringLiteral<'src>),
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", TokenIdentifier::from_lexer)]
     Identifier(TokenIdentifier<'src>),
+    #[regex(r"'[a-zA-Z_][a-zA-Z0-9_]*", TokenLabel::from_lexer)]
+    Label(TokenLabel<'src>),
+    // Produced by `tokenize` for input no other token matches
+    Error(LexError),
 }
 
 // Implement TokenTrait for Token enum - delegates to inner token structs
@@ -424,13 +724,23 @@ impl<'src> TokenTrait for Token<'src> {
             Token::Fn(t) => t.position(),
             Token::Let(t) => t.position(),
             Token::For(t) => t.position(),
+            Token::While(t) => t.position(),
+            Token::Break(t) => t.position(),
+            Token::Continue(t) => t.position(),
+            Token::Match(t) => t.position(),
             Token::In(t) => t.position(),
+            Token::Not(t) => t.position(),
             Token::With(t) => t.position(),
             Token::If(t) => t.position(),
             Token::Else(t) => t.position(),
             Token::Or(t) => t.position(),
             Token::And(t) => t.position(),
             Token::Return(t) => t.position(),
+            Token::Assert(t) => t.position(),
+            Token::Import(t) => t.position(),
+            Token::Use(t) => t.position(),
+            Token::Impl(t) => t.position(),
+            Token::Pub(t) => t.position(),
             Token::True(t) => t.position(),
             Token::False(t) => t.position(),
             Token::SelfKw(t) => t.position(),
@@ -448,6 +758,16 @@ impl<'src> TokenTrait for Token<'src> {
             Token::Power(t) => t.position(),
             Token::Modulo(t) => t.position(),
             Token::Ampersand(t) => t.position(),
+            Token::Hash(t) => t.position(),
+            Token::BitwiseNot(t) => t.position(),
+            Token::ShiftLeft(t) => t.position(),
+            Token::ShiftRight(t) => t.position(),
+            Token::PlusAssign(t) => t.position(),
+            Token::MinusAssign(t) => t.position(),
+            Token::MulAssign(t) => t.position(),
+            Token::DivAssign(t) => t.position(),
+            Token::ModAssign(t) => t.position(),
+            Token::PowAssign(t) => t.position(),
             Token::Colon(t) => t.position(),
             Token::SemiColon(t) => t.position(),
             Token::Comma(t) => t.position(),
@@ -460,7 +780,9 @@ impl<'src> TokenTrait for Token<'src> {
             Token::LeftBrace(t) => t.position(),
             Token::RightBrace(t) => t.position(),
             Token::Pipe(t) => t.position(),
+            Token::PipeArrow(t) => t.position(),
             Token::Arrow(t) => t.position(),
+            Token::FatArrow(t) => t.position(),
             Token::BoolType(t) => t.position(),
             Token::I32Type(t) => t.position(),
             Token::F64Type(t) => t.position(),
@@ -468,7 +790,10 @@ impl<'src> TokenTrait for Token<'src> {
             Token::AlgebraicType(t) => t.position(),
             Token::FloatLiteral(t) => t.position(),
             Token::IntLiteral(t) => t.position(),
+            Token::StringLiteral(t) => t.position(),
             Token::Identifier(t) => t.position(),
+            Token::Label(t) => t.position(),
+            Token::Error(t) => t.position(),
         }
     }
 
@@ -479,13 +804,23 @@ impl<'src> TokenTrait for Token<'src> {
             Token::Fn(t) => t.value_str(),
             Token::Let(t) => t.value_str(),
             Token::For(t) => t.value_str(),
+            Token::While(t) => t.value_str(),
+            Token::Break(t) => t.value_str(),
+            Token::Continue(t) => t.value_str(),
+            Token::Match(t) => t.value_str(),
             Token::In(t) => t.value_str(),
+            Token::Not(t) => t.value_str(),
             Token::With(t) => t.value_str(),
             Token::If(t) => t.value_str(),
             Token::Else(t) => t.value_str(),
             Token::Or(t) => t.value_str(),
             Token::And(t) => t.value_str(),
             Token::Return(t) => t.value_str(),
+            Token::Assert(t) => t.value_str(),
+            Token::Import(t) => t.value_str(),
+            Token::Use(t) => t.value_str(),
+            Token::Impl(t) => t.value_str(),
+            Token::Pub(t) => t.value_str(),
             Token::True(t) => t.value_str(),
             Token::False(t) => t.value_str(),
             Token::SelfKw(t) => t.value_str(),
@@ -503,6 +838,16 @@ impl<'src> TokenTrait for Token<'src> {
             Token::Power(t) => t.value_str(),
             Token::Modulo(t) => t.value_str(),
             Token::Ampersand(t) => t.value_str(),
+            Token::Hash(t) => t.value_str(),
+            Token::BitwiseNot(t) => t.value_str(),
+            Token::ShiftLeft(t) => t.value_str(),
+            Token::ShiftRight(t) => t.value_str(),
+            Token::PlusAssign(t) => t.value_str(),
+            Token::MinusAssign(t) => t.value_str(),
+            Token::MulAssign(t) => t.value_str(),
+            Token::DivAssign(t) => t.value_str(),
+            Token::ModAssign(t) => t.value_str(),
+            Token::PowAssign(t) => t.value_str(),
             Token::Colon(t) => t.value_str(),
             Token::SemiColon(t) => t.value_str(),
             Token::Comma(t) => t.value_str(),
@@ -515,7 +860,9 @@ impl<'src> TokenTrait for Token<'src> {
             Token::LeftBrace(t) => t.value_str(),
             Token::RightBrace(t) => t.value_str(),
             Token::Pipe(t) => t.value_str(),
+            Token::PipeArrow(t) => t.value_str(),
             Token::Arrow(t) => t.value_str(),
+            Token::FatArrow(t) => t.value_str(),
             Token::BoolType(t) => t.value_str(),
             Token::I32Type(t) => t.value_str(),
             Token::F64Type(t) => t.value_str(),
@@ -523,7 +870,89 @@ impl<'src> TokenTrait for Token<'src> {
             Token::AlgebraicType(t) => t.value_str(),
             Token::FloatLiteral(t) => t.value_str(),
             Token::IntLiteral(t) => t.value_str(),
+            Token::StringLiteral(t) => t.value_str(),
             Token::Identifier(t) => t.value_str(),
+            Token::Label(t) => t.value_str(),
+            Token::Error(t) => t.value_str(),
+        }
+    }
+    fn span(&self) -> Span {
+        match self {
+            Token::Struct(t) => t.span(),
+            Token::Container(t) => t.span(),
+            Token::Fn(t) => t.span(),
+            Token::Let(t) => t.span(),
+            Token::For(t) => t.span(),
+            Token::While(t) => t.span(),
+            Token::Break(t) => t.span(),
+            Token::Continue(t) => t.span(),
+            Token::Match(t) => t.span(),
+            Token::In(t) => t.span(),
+            Token::Not(t) => t.span(),
+            Token::With(t) => t.span(),
+            Token::If(t) => t.span(),
+            Token::Else(t) => t.span(),
+            Token::Or(t) => t.span(),
+            Token::And(t) => t.span(),
+            Token::Return(t) => t.span(),
+            Token::Assert(t) => t.span(),
+            Token::Import(t) => t.span(),
+            Token::Use(t) => t.span(),
+            Token::Impl(t) => t.span(),
+            Token::Pub(t) => t.span(),
+            Token::True(t) => t.span(),
+            Token::False(t) => t.span(),
+            Token::SelfKw(t) => t.span(),
+            Token::Equals(t) => t.span(),
+            Token::EqualsEquals(t) => t.span(),
+            Token::NotEquals(t) => t.span(),
+            Token::LessThan(t) => t.span(),
+            Token::GreaterThan(t) => t.span(),
+            Token::LessEquals(t) => t.span(),
+            Token::GreaterEquals(t) => t.span(),
+            Token::Plus(t) => t.span(),
+            Token::Minus(t) => t.span(),
+            Token::Multiply(t) => t.span(),
+            Token::Divide(t) => t.span(),
+            Token::Power(t) => t.span(),
+            Token::Modulo(t) => t.span(),
+            Token::Ampersand(t) => t.span(),
+            Token::Hash(t) => t.span(),
+            Token::BitwiseNot(t) => t.span(),
+            Token::ShiftLeft(t) => t.span(),
+            Token::ShiftRight(t) => t.span(),
+            Token::PlusAssign(t) => t.span(),
+            Token::MinusAssign(t) => t.span(),
+            Token::MulAssign(t) => t.span(),
+            Token::DivAssign(t) => t.span(),
+            Token::ModAssign(t) => t.span(),
+            Token::PowAssign(t) => t.span(),
+            Token::Colon(t) => t.span(),
+            Token::SemiColon(t) => t.span(),
+            Token::Comma(t) => t.span(),
+            Token::Dot(t) => t.span(),
+            Token::DotDot(t) => t.span(),
+            Token::LeftParen(t) => t.span(),
+            Token::RightParen(t) => t.span(),
+            Token::LeftBracket(t) => t.span(),
+            Token::RightBracket(t) => t.span(),
+            Token::LeftBrace(t) => t.span(),
+            Token::RightBrace(t) => t.span(),
+            Token::Pipe(t) => t.span(),
+            Token::PipeArrow(t) => t.span(),
+            Token::Arrow(t) => t.span(),
+            Token::FatArrow(t) => t.span(),
+            Token::BoolType(t) => t.span(),
+            Token::I32Type(t) => t.span(),
+            Token::F64Type(t) => t.span(),
+            Token::RealType(t) => t.span(),
+            Token::AlgebraicType(t) => t.span(),
+            Token::FloatLiteral(t) => t.span(),
+            Token::IntLiteral(t) => t.span(),
+            Token::StringLiteral(t) => t.span(),
+            Token::Identifier(t) => t.span(),
+            Token::Label(t) => t.span(),
+            Token::Error(t) => t.span(),
         }
     }
 }
@@ -571,21 +1000,68 @@ fn derive_span_no_newline<'src>(lex: &mut Lexer<'src, Token<'src>>) -> Span {
 // ============================================================================
 
 /// Tokenize input source code into a vector of Token
-pub fn tokenize<'src>(input: &'src str) -> Result<Vec<Token<'src>>, String> {
+///
+/// Lexing does not stop at invalid input: each character that starts no
+/// token, and each literal that is out of range or badly escaped, becomes
+/// a `Token::Error` in the token list and a `LexError` in the error list.
+/// Lexing resumes right after the character or the whole literal.
+pub fn tokenize<'src>(input: &'src str) -> Result<(Vec<Token<'src>>, Vec<LexError>), Infallible> {
     let mut lexer = Token::lexer(input);
     let mut tokens = Vec::new();
+    let mut errors = Vec::new();
 
     while let Some(result) = lexer.next() {
-        match result {
-            Ok(token) => tokens.push(token),
-            Err(_) => {
-                let span_start = lexer.span().start;
-                return Err(format!("Lexing error at position {}", span_start));
+        let invalid = match result {
+            Ok(token) => {
+                tokens.push(token);
+                continue;
             }
-        }
+            Err(invalid) => invalid,
+        };
+
+        let error = match invalid {
+            InvalidToken::NoMatch => {
+                let (error, character) = unexpected_character(&mut lexer);
+                if lexer.span().len() > character.len_utf8() {
+                    // Logos can reject several characters at once (e.g. an
+                    // unterminated string); resume right after the first one
+                    let resume = lexer.span().start + character.len_utf8();
+                    let extras = std::mem::take(&mut lexer.extras);
+                    lexer = Token::lexer_with_extras(input, extras);
+                    lexer.bump(resume);
+                }
+                error
+            }
+            // The rejected literals are well formed, so skip all of them
+            InvalidToken::IntegerOverflow => LexError::new(
+                LexErrorKind::IntegerOverflow,
+                derive_span_no_newline(&mut lexer),
+            ),
+            InvalidToken::UnknownEscape(escape) => LexError::new(
+                LexErrorKind::UnknownEscape(escape),
+                derive_span_no_newline(&mut lexer),
+            ),
+        };
+        tokens.push(Token::Error(error.clone()));
+        errors.push(error);
     }
 
-    Ok(tokens)
+    Ok((tokens, errors))
+}
+
+/// Build the error for the first character of the rejected slice
+fn unexpected_character<'src>(lex: &mut Lexer<'src, Token<'src>>) -> (LexError, char) {
+    let start = derive_position(lex);
+    let character = lex.slice().chars().next().unwrap_or('\0');
+    let span = Span {
+        start,
+        lines: 0,
+        end_column: start.column + character.len_utf8(),
+    };
+    (
+        LexError::new(LexErrorKind::UnexpectedCharacter(character), span),
+        character,
+    )
 }
 
 // ============================================================================
@@ -597,11 +1073,18 @@ mod tests {
     use super::*;
     use assert_matches::assert_matches;
 
+    /// Tokenize input that is expected to lex without errors
+    fn lex(input: &str) -> Vec<Token<'_>> {
+        let Ok((tokens, errors)) = tokenize(input);
+        assert_eq!(errors, []);
+        tokens
+    }
+
     #[test]
     fn test_keywords() {
-        let input = "struct container fn let for in with if else or and return true false self";
-        let tokens = tokenize(input).unwrap();
-        assert_eq!(tokens.len(), 15);
+        let input = "struct container fn let for in not with if else or and return assert import use impl pub true false self while break continue match";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 25);
 
         assert_matches!(tokens[0], Token::Struct(_));
         assert_matches!(tokens[1], Token::Container(_));
@@ -609,22 +1092,32 @@ mod tests {
         assert_matches!(tokens[3], Token::Let(_));
         assert_matches!(tokens[4], Token::For(_));
         assert_matches!(tokens[5], Token::In(_));
-        assert_matches!(tokens[6], Token::With(_));
-        assert_matches!(tokens[7], Token::If(_));
-        assert_matches!(tokens[8], Token::Else(_));
-        assert_matches!(tokens[9], Token::Or(_));
-        assert_matches!(tokens[10], Token::And(_));
-        assert_matches!(tokens[11], Token::Return(_));
-        assert_matches!(tokens[12], Token::True(_));
-        assert_matches!(tokens[13], Token::False(_));
-        assert_matches!(tokens[14], Token::SelfKw(_));
+        assert_matches!(tokens[6], Token::Not(_));
+        assert_matches!(tokens[7], Token::With(_));
+        assert_matches!(tokens[8], Token::If(_));
+        assert_matches!(tokens[9], Token::Else(_));
+        assert_matches!(tokens[10], Token::Or(_));
+        assert_matches!(tokens[11], Token::And(_));
+        assert_matches!(tokens[12], Token::Return(_));
+        assert_matches!(tokens[13], Token::Assert(_));
+        assert_matches!(tokens[14], Token::Import(_));
+        assert_matches!(tokens[15], Token::Use(_));
+        assert_matches!(tokens[16], Token::Impl(_));
+        assert_matches!(tokens[17], Token::Pub(_));
+        assert_matches!(tokens[18], Token::True(_));
+        assert_matches!(tokens[19], Token::False(_));
+        assert_matches!(tokens[20], Token::SelfKw(_));
+        assert_matches!(tokens[21], Token::While(_));
+        assert_matches!(tokens[22], Token::Break(_));
+        assert_matches!(tokens[23], Token::Continue(_));
+        assert_matches!(tokens[24], Token::Match(_));
     }
 
     #[test]
     fn test_operators() {
-        let input = "= == != < > <= >= + - * / ^ % &";
-        let tokens = tokenize(input).unwrap();
-        assert_eq!(tokens.len(), 14);
+        let input = "= == != < > <= >= + - * / ^ % & ~ << >> += -= *= /= %= ^=";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 23);
 
         assert_matches!(tokens[0], Token::Equals(_));
         assert_matches!(tokens[1], Token::EqualsEquals(_));
@@ -640,13 +1133,22 @@ mod tests {
         assert_matches!(tokens[11], Token::Power(_));
         assert_matches!(tokens[12], Token::Modulo(_));
         assert_matches!(tokens[13], Token::Ampersand(_));
+        assert_matches!(tokens[14], Token::BitwiseNot(_));
+        assert_matches!(tokens[15], Token::ShiftLeft(_));
+        assert_matches!(tokens[16], Token::ShiftRight(_));
+        assert_matches!(tokens[17], Token::PlusAssign(_));
+        assert_matches!(tokens[18], Token::MinusAssign(_));
+        assert_matches!(tokens[19], Token::MulAssign(_));
+        assert_matches!(tokens[20], Token::DivAssign(_));
+        assert_matches!(tokens[21], Token::ModAssign(_));
+        assert_matches!(tokens[22], Token::PowAssign(_));
     }
 
     #[test]
     fn test_punctuation() {
-        let input = ": ; , . .. ( ) [ ] { } | ->";
-        let tokens = tokenize(input).unwrap();
-        assert_eq!(tokens.len(), 13);
+        let input = ": ; , . .. ( ) [ ] { } | |> -> =>";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 15);
 
         assert_matches!(tokens[0], Token::Colon(_));
         assert_matches!(tokens[1], Token::SemiColon(_));
@@ -660,13 +1162,15 @@ mod tests {
         assert_matches!(tokens[9], Token::LeftBrace(_));
         assert_matches!(tokens[10], Token::RightBrace(_));
         assert_matches!(tokens[11], Token::Pipe(_));
-        assert_matches!(tokens[12], Token::Arrow(_));
+        assert_matches!(tokens[12], Token::PipeArrow(_));
+        assert_matches!(tokens[13], Token::Arrow(_));
+        assert_matches!(tokens[14], Token::FatArrow(_));
     }
 
     #[test]
     fn test_literals() {
         let input = "123 3.45 identifier_name _private";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 4);
 
         assert_matches!(tokens[0], Token::IntLiteral(ref t) if t.value == 123);
@@ -675,10 +1179,225 @@ mod tests {
         assert_matches!(tokens[3], Token::Identifier(ref t) if t.name == "_private");
     }
 
+    #[test]
+    fn test_scientific_float_literals() {
+        let input = "1.5e10 2.3E-4 1e6 7e+2 1.25";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 5);
+
+        assert_matches!(
+            tokens[0],
+            Token::FloatLiteral(TokenFloatLiteral { value: 1.5e10, .. })
+        );
+        assert_matches!(tokens[1], Token::FloatLiteral(ref t) if t.value == 2.3e-4);
+        assert_matches!(tokens[2], Token::FloatLiteral(ref t) if t.value == 1e6);
+        assert_matches!(tokens[3], Token::FloatLiteral(ref t) if t.value == 700.0);
+        assert_matches!(tokens[4], Token::FloatLiteral(ref t) if t.value == 1.25);
+
+        // The span and Display cover the whole literal as written
+        assert_eq!(tokens[1].span().start.column, 8);
+        assert_eq!(tokens[1].span().end_column, 14);
+        let Token::FloatLiteral(ref t) = tokens[1] else {
+            panic!("expected a float literal, got {:?}", tokens[1]);
+        };
+        assert_eq!(t.to_string(), "2.3E-4");
+    }
+
+    #[test]
+    fn test_exponent_needs_digits() {
+        let tokens = lex("1e x");
+        assert_matches!(tokens[0], Token::IntLiteral(ref t) if t.value == 1);
+        assert_matches!(tokens[1], Token::Identifier(ref t) if t.name == "e");
+    }
+
+    #[test]
+    fn test_digit_separators() {
+        let input = "1_000 0xFF_AB_CD 0b1010_0101 1_000.000_5 1e1_0";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 5);
+
+        assert_matches!(tokens[0], Token::IntLiteral(ref t) if t.value == 1000);
+        assert_matches!(tokens[1], Token::IntLiteral(ref t) if t.value == 0xFFABCD);
+        assert_matches!(tokens[2], Token::IntLiteral(ref t) if t.value == 0b1010_0101);
+        assert_matches!(tokens[3], Token::FloatLiteral(ref t) if t.value == 1000.0005);
+        assert_matches!(tokens[4], Token::FloatLiteral(ref t) if t.value == 1e10);
+
+        // The span covers the underscores
+        assert_eq!(tokens[0].span().end_column, 6);
+        assert_eq!(tokens[0].value_str(), "1_000");
+    }
+
+    #[test]
+    fn test_misplaced_digit_separators() {
+        let tokens = lex("_1 1_ 1_.5");
+        assert_matches!(tokens[0], Token::Identifier(ref t) if t.name == "_1");
+        assert_matches!(tokens[1], Token::IntLiteral(ref t) if t.value == 1);
+        assert_matches!(tokens[2], Token::Identifier(ref t) if t.name == "_");
+        assert_matches!(tokens[3], Token::IntLiteral(ref t) if t.value == 1);
+        assert_matches!(tokens[4], Token::Identifier(ref t) if t.name == "_");
+        assert_matches!(tokens[5], Token::Dot(_));
+    }
+
+    #[test]
+    fn test_radix_int_literals() {
+        let input = "0xFF 0Xa0 0o755 0b1010 0x7FFFFFFF";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 5);
+
+        assert_matches!(tokens[0], Token::IntLiteral(ref t) if t.value == 255 && t.text == "0xFF");
+        assert_matches!(tokens[1], Token::IntLiteral(ref t) if t.value == 160);
+        assert_matches!(tokens[2], Token::IntLiteral(ref t) if t.value == 0o755);
+        assert_matches!(tokens[3], Token::IntLiteral(ref t) if t.value == 10);
+        assert_matches!(tokens[4], Token::IntLiteral(ref t) if t.value == i32::MAX);
+
+        // Error messages show the literal as written
+        assert_eq!(tokens[0].value_str(), "0xFF");
+        assert_eq!(tokens[0].span().end_column, 5);
+    }
+
+    #[test]
+    fn test_radix_int_literal_overflow() {
+        for literal in [
+            "0x80000000",
+            "0o2_000_000_0000",
+            "0b1_0000000000000000000000000000000",
+        ] {
+            let Ok((tokens, errors)) = tokenize(literal);
+
+            // One error covering the whole literal and nothing lexed after it
+            assert_eq!(errors.len(), 1, "{}", literal);
+            assert_eq!(errors[0].kind, LexErrorKind::IntegerOverflow);
+            assert_eq!(errors[0].span.start.column, 1);
+            assert_eq!(errors[0].span.end_column, literal.len() + 1);
+            assert_eq!(tokens.len(), 1, "{}", literal);
+        }
+    }
+
+    #[test]
+    fn test_int_literal_overflow_skips_whole_literal() {
+        let Ok((tokens, errors)) = tokenize("let x = 3000000000;");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::IntegerOverflow);
+        assert_eq!(errors[0].to_string(), "integer literal does not fit in i32");
+        // The span covers all ten digits
+        assert_eq!(errors[0].span.start.column, 9);
+        assert_eq!(errors[0].span.end_column, 19);
+
+        // Lexing carries on after the literal, not inside it
+        assert_eq!(tokens.len(), 5);
+        assert_matches!(tokens[3], Token::Error(_));
+        assert_matches!(tokens[4], Token::SemiColon(_));
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let input = r#"import "shapes/point.cad" """#;
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 3);
+
+        assert_matches!(tokens[1], Token::StringLiteral(ref t) if t.value == "shapes/point.cad");
+        assert_matches!(tokens[2], Token::StringLiteral(ref t) if t.value.is_empty());
+        assert_eq!(tokens[1].value_str(), "\"shapes/point.cad\"");
+        assert_eq!(tokens[2].value_str(), "\"\"");
+
+        // Span covers the quotes
+        let span = tokens[1].span();
+        assert_eq!(span.start.column, 8);
+        assert_eq!(span.end_column, 26);
+    }
+
+    #[test]
+    fn test_string_literal_escapes() {
+        let input = r#""say \"hi\"\n\t\\""#;
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 1);
+
+        let Token::StringLiteral(ref t) = tokens[0] else {
+            panic!("expected a string literal, got {:?}", tokens[0]);
+        };
+        assert_eq!(t.value, r#"say \"hi\"\n\t\\"#);
+        assert_eq!(t.unescaped(), "say \"hi\"\n\t\\");
+        assert_eq!(escape_string(&t.unescaped()), t.value);
+        assert_eq!(t.span().end_column, input.len() + 1);
+    }
+
+    #[test]
+    fn test_unknown_string_escape() {
+        let Ok((tokens, errors)) = tokenize(r#"let s = "abc\q";"#);
+
+        // One error for the whole literal, then lexing carries on after it
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::UnknownEscape('q'));
+        assert_eq!(errors[0].to_string(), "unknown escape `\\q`");
+        assert_eq!(errors[0].span.start.column, 9);
+        assert_eq!(errors[0].span.end_column, 16);
+
+        assert_eq!(tokens.len(), 5);
+        assert_matches!(tokens[3], Token::Error(_));
+        assert_matches!(tokens[4], Token::SemiColon(_));
+    }
+
+    #[test]
+    fn test_unterminated_string_literal() {
+        let Ok((tokens, errors)) = tokenize("\"no end\nlet x;");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::UnexpectedCharacter('"'));
+        assert_matches!(tokens[0], Token::Error(_));
+        // Lexing resumes right after the stray quote
+        assert_matches!(&tokens[1], Token::Identifier(t) if t.name == "no");
+    }
+
+    #[test]
+    fn test_invalid_character_position() {
+        let source = "let x\n  = $;";
+        let Ok((_, errors)) = tokenize(source);
+
+        assert_eq!(errors.len(), 1);
+        let error = &errors[0];
+        assert_eq!(error.kind, LexErrorKind::UnexpectedCharacter('$'));
+        assert_eq!(error.span.start, LineColumn { line: 2, column: 5 });
+        assert_eq!(error.span.start_byte_offset(source), 10);
+        assert_eq!(error.to_string(), "unexpected character '$'");
+    }
+
+    #[test]
+    fn test_invalid_characters_are_collected() {
+        let Ok((tokens, errors)) = tokenize("let $ = 1 @\nx ? y;");
+
+        let kinds: Vec<LexErrorKind> = errors.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            ['$', '@', '?'].map(LexErrorKind::UnexpectedCharacter)
+        );
+        assert_eq!(errors[0].span.start, LineColumn { line: 1, column: 5 });
+        assert_eq!(
+            errors[1].span.start,
+            LineColumn {
+                line: 1,
+                column: 11
+            }
+        );
+        assert_eq!(errors[2].span.start, LineColumn { line: 2, column: 3 });
+        assert_eq!(errors[2].span.end_column, 4);
+
+        // Every error leaves a sentinel and the valid tokens around it survive
+        assert_eq!(tokens.len(), 9);
+        assert_matches!(tokens[0], Token::Let(_));
+        assert_matches!(tokens[1], Token::Error(_));
+        assert_matches!(tokens[2], Token::Equals(_));
+        assert_matches!(tokens[3], Token::IntLiteral(_));
+        assert_matches!(tokens[4], Token::Error(_));
+        assert_matches!(&tokens[5], Token::Identifier(t) if t.name == "x");
+        assert_matches!(tokens[6], Token::Error(_));
+        assert_matches!(&tokens[7], Token::Identifier(t) if t.name == "y");
+        assert_matches!(tokens[8], Token::SemiColon(_));
+    }
+
     #[test]
     fn test_types() {
         let input = "bool i32 f64 Real Algebraic";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 5);
 
         assert_matches!(tokens[0], Token::BoolType(_));
@@ -691,7 +1410,7 @@ mod tests {
     #[test]
     fn test_simple_expression() {
         let input = "let x: i32 = 42;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 7);
 
         assert_matches!(tokens[0], Token::Let(_));
@@ -706,7 +1425,7 @@ mod tests {
     #[test]
     fn test_struct_definition() {
         let input = "struct Point { x: f64, y: f64 }";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 11);
 
         assert_matches!(tokens[0], Token::Struct(_));
@@ -725,7 +1444,7 @@ mod tests {
     #[test]
     fn test_function_definition() {
         let input = "fn distance(p1: &Point, p2: &Point) -> f64";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 15);
 
         assert_matches!(tokens[0], Token::Fn(_));
@@ -736,7 +1455,7 @@ mod tests {
     #[test]
     fn test_range_syntax() {
         let input = "for i in 0..5";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 6);
 
         assert_matches!(tokens[0], Token::For(_));
@@ -747,10 +1466,25 @@ mod tests {
         assert_matches!(tokens[5], Token::IntLiteral(ref t) if t.value == 5);
     }
 
+    #[test]
+    fn test_loop_labels() {
+        let input = "'outer: for i in xs { break 'outer; }";
+        let tokens = lex(input);
+        assert_eq!(tokens.len(), 11);
+
+        assert_matches!(tokens[0], Token::Label(ref t) if t.name == "outer" && t.text == "'outer");
+        assert_matches!(tokens[1], Token::Colon(_));
+        assert_matches!(tokens[2], Token::For(_));
+        assert_matches!(tokens[7], Token::Break(_));
+        assert_matches!(tokens[8], Token::Label(ref t) if t.name == "outer");
+        assert_eq!(tokens[8].span().start.column, 29);
+        assert_eq!(tokens[8].span().end_column, 35);
+    }
+
     #[test]
     fn test_with_statement() {
         let input = "with transform { .point = p1; }";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 9);
 
         assert_matches!(tokens[0], Token::With(_));
@@ -761,7 +1495,7 @@ mod tests {
     #[test]
     fn test_comments_are_skipped() {
         let input = "let x = 42; // This is a comment\nlet y = 3.45;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         // Should have: let x = 42 ; let y = 3.45 ;
         assert_eq!(tokens.len(), 10);
@@ -781,7 +1515,7 @@ mod tests {
     #[test]
     fn test_whitespace_is_skipped() {
         let input = "  let    x  =  42  ;  ";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 5);
 
         assert_matches!(tokens[0], Token::Let(_));
@@ -794,7 +1528,7 @@ mod tests {
     #[test]
     fn test_array_syntax() {
         let input = "let points: [Point; 5] = [];";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 12);
 
         assert_matches!(tokens[0], Token::Let(_));
@@ -814,7 +1548,7 @@ mod tests {
     #[test]
     fn test_method_call() {
         let input = "circle.area()";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 5);
 
         assert_matches!(tokens[0], Token::Identifier(ref t) if t.name == "circle");
@@ -827,7 +1561,7 @@ mod tests {
     #[test]
     fn test_closure_syntax() {
         let input = "points.map(|p| p.x)";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
         assert_eq!(tokens.len(), 11);
 
         assert_matches!(tokens[0], Token::Identifier(ref t) if t.name == "points");
@@ -850,7 +1584,7 @@ mod tests {
     #[test]
     fn test_position_single_line() {
         let input = "let x = 42;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         assert_eq!(tokens[0].position(), LineColumn { line: 1, column: 1 }); // let
         assert_eq!(tokens[1].position(), LineColumn { line: 1, column: 5 }); // x
@@ -868,7 +1602,7 @@ mod tests {
     #[test]
     fn test_position_multiple_lines() {
         let input = "let x = 10;\nlet y = 20;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         assert_eq!(tokens[0].position(), LineColumn { line: 1, column: 1 }); // let (line 1)
         assert_eq!(tokens[1].position(), LineColumn { line: 1, column: 5 }); // x
@@ -879,7 +1613,7 @@ mod tests {
     #[test]
     fn test_position_after_single_line_comment() {
         let input = "let x = 10; // comment\nlet y = 20;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         assert_eq!(tokens[0].position(), LineColumn { line: 1, column: 1 }); // let
         assert_eq!(tokens[5].position(), LineColumn { line: 2, column: 1 }); // let (after comment)
@@ -889,7 +1623,7 @@ mod tests {
     #[test]
     fn test_position_after_multiline_comment() {
         let input = "let x = 10;\n/* comment\n   on multiple\n   lines */\nlet y = 20;";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         assert_eq!(tokens[0].position(), LineColumn { line: 1, column: 1 }); // let
         assert_eq!(tokens[1].position(), LineColumn { line: 1, column: 5 }); // x
@@ -900,7 +1634,7 @@ mod tests {
     #[test]
     fn test_position_complex_multiline_comment() {
         let input = "struct Point {\n/* This is a\n * multi-line\n * comment\n */\nx: f64\n}";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         assert_eq!(tokens[0].position(), LineColumn { line: 1, column: 1 }); // struct
         assert_eq!(tokens[1].position(), LineColumn { line: 1, column: 8 }); // Point
@@ -919,7 +1653,7 @@ mod tests {
     #[test]
     fn test_span_for_literals() {
         let input = "123 45.67";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         if let Token::IntLiteral(ref t) = tokens[0] {
             assert_eq!(t.span.start, LineColumn { line: 1, column: 1 });
@@ -938,10 +1672,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fixed_token_span() {
+        let tokens = lex("  Algebraic ==");
+
+        let span = tokens[0].span();
+        assert_eq!(span.start, LineColumn { line: 1, column: 3 });
+        assert_eq!(span.end_column, 12);
+
+        let span = tokens[1].span();
+        assert_eq!(
+            span.start,
+            LineColumn {
+                line: 1,
+                column: 13
+            }
+        );
+        assert_eq!(span.end_column, 15);
+    }
+
     #[test]
     fn test_span_for_identifiers() {
         let input = "hello world";
-        let tokens = tokenize(input).unwrap();
+        let tokens = lex(input);
 
         if let Token::Identifier(ref t) = tokens[0] {
             assert_eq!(t.name, "hello");
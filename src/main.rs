@@ -1,15 +1,38 @@
 mod ast;
+mod codegen;
+mod diagnostic;
+mod error;
+mod formatter;
 mod lexer;
+mod module_resolver;
 mod parser;
+mod span;
 
-use chumsky::Parser as _;
-use clap::{Parser, Subcommand};
+use ast::Program;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use codegen::DotGen;
+use diagnostic::sarif::SarifLog;
+use diagnostic::{Diagnostic, DiagnosticAccumulator, DiagnosticKind};
 use lexer::TokenTrait;
+use module_resolver::ModuleResolver;
+use span::Span;
+use std::collections::HashMap;
 use std::fs;
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+/// Version of this build, as reported by `--version` and in error output
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name diagnostics report source read from stdin under
+const STDIN_NAME: &str = "<stdin>";
 
 #[derive(Parser)]
 #[command(name = "cad-dsl")]
 #[command(about = "A DSL for CAD operations")]
+#[command(version = VERSION)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -17,8 +40,75 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    Lex { file: String },
-    Parse { file: String },
+    Lex {
+        /// Source file; reads stdin when omitted or `-`
+        file: Option<String>,
+    },
+    Parse {
+        /// Source files, parsed into one program together with the files they
+        /// import; reads stdin when omitted or `-`
+        files: Vec<String>,
+
+        /// Print the AST as a Graphviz DOT graph instead of debug output
+        #[arg(long)]
+        emit_dot: bool,
+
+        /// Print the given file as formatted source instead of debug output;
+        /// the files it imports are loaded and checked but not printed
+        #[arg(long, conflicts_with = "emit_dot")]
+        emit_source: bool,
+
+        /// Print diagnostics as a SARIF 2.1.0 log on stdout instead of
+        /// rendering them, and print nothing else
+        #[arg(long, conflicts_with_all = ["emit_dot", "emit_source"])]
+        emit_sarif: bool,
+
+        /// Stop reporting errors after this many
+        #[arg(long, value_name = "N")]
+        max_errors: Option<NonZeroUsize>,
+    },
+    /// Print the version and the git commit this binary was built from
+    Version,
+}
+
+/// Read the source for a subcommand, returning the name to report it under
+/// together with its contents
+fn read_source(file: Option<&str>) -> (String, String) {
+    match file {
+        None | Some("-") => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .expect("Failed to read stdin");
+            (STDIN_NAME.to_string(), content)
+        }
+        Some(path) => {
+            let content = fs::read_to_string(path).expect("Failed to read file");
+            (path.to_string(), content)
+        }
+    }
+}
+
+/// Render diagnostics for one file, or add them to the SARIF log if the
+/// command is collecting one
+fn report(sarif: &mut Option<SarifLog>, file: &str, source: &str, diagnostics: &[Diagnostic]) {
+    match sarif {
+        Some(log) => log.add((file != STDIN_NAME).then_some(file), source, diagnostics),
+        None => diagnostic::render_all(source, file, diagnostics),
+    }
+}
+
+/// Report the note on errors dropped by `--max-errors`, then print the
+/// SARIF log if there is one, or a footer naming the version under rendered
+/// diagnostics, and exit with a failure status
+fn fail(sarif: &mut Option<SarifLog>, diagnostics: DiagnosticAccumulator) -> ! {
+    // Only the note is left, and it belongs to no file
+    report(sarif, "", "", &diagnostics.finish());
+    match sarif {
+        Some(log) => print!("{}", log.render()),
+        None => eprintln!("error reported by cad-dsl {}", VERSION),
+    }
+    std::process::exit(1);
 }
 
 fn main() {
@@ -26,49 +116,150 @@ fn main() {
 
     match &cli.command {
         Commands::Lex { file } => {
-            let content = fs::read_to_string(file).expect("Failed to read file");
-
-            match lexer::tokenize(&content) {
-                Ok(tokens) => {
-                    for token in tokens {
-                        println!(
-                            "{:?} at {:?} - value: {}",
-                            token,
-                            token.position(),
-                            token.value_str()
-                        );
-                    }
-                }
-                Err(error) => eprintln!("Lexing error: {}", error),
+            let (name, content) = read_source(file.as_deref());
+
+            let Ok((tokens, errors)) = lexer::tokenize(&content);
+            for token in tokens {
+                println!(
+                    "{:?} at {} - value: {}",
+                    token,
+                    token.position(),
+                    token.value_str()
+                );
+            }
+            if !errors.is_empty() {
+                let diagnostics: Vec<Diagnostic> = errors.into_iter().map(Into::into).collect();
+                diagnostic::render_all(&content, &name, &diagnostics);
+                fail(&mut None, DiagnosticAccumulator::new());
             }
         }
-        Commands::Parse { file } => {
-            let content = fs::read_to_string(file).expect("Failed to read file");
-
-            // First tokenize
-            let tokens = match lexer::tokenize(&content) {
-                Ok(tokens) => tokens,
-                Err(error) => {
-                    eprintln!("Lexing error: {}", error);
-                    std::process::exit(1);
+        Commands::Parse {
+            files,
+            emit_dot,
+            emit_source,
+            emit_sarif,
+            max_errors,
+        } => {
+            if *emit_source && files.len() > 1 {
+                Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "--emit-source formats a single file",
+                    )
+                    .exit();
+            }
+            let mut sarif = emit_sarif.then(SarifLog::new);
+
+            // Load the given files and everything they import
+            let mut resolver = ModuleResolver::new();
+            let roots = if files.is_empty() {
+                vec![None]
+            } else {
+                files.iter().map(|file| Some(file.as_str())).collect()
+            };
+            for root in roots {
+                let (name, content) = read_source(root);
+                let path = root.filter(|file| *file != "-").map(Path::new);
+                if let Err(error) = resolver.add_root(name, content, path) {
+                    let diagnostics = [Diagnostic::error(DiagnosticKind::Import {
+                        message: error.message,
+                        span: error.span,
+                    })];
+                    report(
+                        &mut sarif,
+                        &error.file.name,
+                        &error.file.content,
+                        &diagnostics,
+                    );
+                    fail(&mut sarif, DiagnosticAccumulator::new());
                 }
+            }
+            let sources = resolver.into_files();
+
+            // One accumulator for every file, so --max-errors counts errors
+            // across the whole run
+            let mut diagnostics = match max_errors {
+                Some(max) => DiagnosticAccumulator::with_max_errors(max.get()),
+                None => DiagnosticAccumulator::new(),
             };
 
-            // Then parse as a let statement
-            match parser::let_stmt(parser::expr_inner())
-                .parse(&tokens)
-                .into_result()
-            {
-                Ok(stmt) => {
-                    println!("Successfully parsed!");
-                    println!("Statement: {:?}", stmt);
+            // Tokenize every file first; the parsed program borrows from the tokens
+            let mut token_lists = Vec::new();
+            for source in &sources {
+                let Ok((tokens, errors)) = lexer::tokenize(&source.content);
+                if !errors.is_empty() {
+                    diagnostics.extend(errors);
+                    report(
+                        &mut sarif,
+                        &source.name,
+                        &source.content,
+                        &diagnostics.take(),
+                    );
+                }
+                token_lists.push(tokens);
+            }
+            if diagnostics.has_errors() {
+                fail(&mut sarif, diagnostics);
+            }
+
+            // Then parse each file and merge the results into a single program.
+            // The root file comes last, after the files it imports; it is also
+            // kept on its own for --emit-source
+            let mut program = Program::default();
+            let mut root = None;
+            let mut struct_origins: HashMap<&str, (&str, Span)> = HashMap::new();
+            for (source, tokens) in sources.iter().zip(&token_lists) {
+                let file = source.name.as_str();
+                let mut errors = Vec::new();
+
+                match parser::parse(tokens) {
+                    (Some(mut parsed), parse_errors) if parse_errors.is_empty() => {
+                        for def in parsed.structs() {
+                            if let Some(&(first_file, first_span)) = struct_origins.get(def.name) {
+                                errors.push(Diagnostic::error(
+                                    DiagnosticKind::DuplicateDefinition {
+                                        name: def.name,
+                                        span: def.name_span,
+                                        first_file: first_file.to_string(),
+                                        first_span,
+                                    },
+                                ));
+                            } else {
+                                struct_origins.insert(def.name, (file, def.name_span));
+                            }
+                        }
+                        parsed.source_files.extend(source.path.clone());
+                        if *emit_source {
+                            root = Some(parsed.clone());
+                        }
+                        program = program.merge(parsed);
+                    }
+                    (_, parse_errors) => errors = parse_errors,
                 }
-                Err(errors) => {
-                    eprintln!("Parse errors:");
-                    parser::report_parse_errors(file, &content, errors);
-                    std::process::exit(1);
+
+                if !errors.is_empty() {
+                    diagnostics.extend(errors);
+                    report(&mut sarif, file, &source.content, &diagnostics.take());
                 }
             }
+            if diagnostics.has_errors() {
+                fail(&mut sarif, diagnostics);
+            }
+
+            if let Some(log) = &sarif {
+                print!("{}", log.render());
+            } else if *emit_dot {
+                print!("{}", DotGen::render(&program));
+            } else if let Some(root) = &root {
+                print!("{}", formatter::unparse(root));
+            } else {
+                println!("Successfully parsed!");
+                println!("Program: {:?}", program);
+            }
+        }
+        Commands::Version => {
+            println!("cad-dsl {}", VERSION);
+            println!("commit {}", env!("CAD_DSL_GIT_COMMIT"));
         }
     }
 }
@@ -0,0 +1,178 @@
+//! Loading of source files referenced by `import` declarations
+//!
+//! Imports are followed depth first, so every file is listed after the files
+//! it imports. Each file is loaded once however often it is imported, and an
+//! import that leads back to a file still being loaded is reported as a cycle.
+
+use crate::lexer::tokenize;
+use crate::parser::import_decl;
+use crate::span::Span;
+use chumsky::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A loaded source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Name used in diagnostics
+    pub name: String,
+    /// Path the file was read from; `None` for stdin
+    pub path: Option<PathBuf>,
+    pub content: String,
+}
+
+/// An import that could not be followed
+///
+/// Carries the importing file so the error can be rendered against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub file: Box<SourceFile>,
+    pub span: Span,
+    pub message: String,
+}
+
+/// Collects root files together with everything they import
+#[derive(Debug, Default)]
+pub struct ModuleResolver {
+    files: Vec<SourceFile>,
+    /// Canonical paths of files already added to `files`
+    loaded: HashSet<PathBuf>,
+    /// Canonical paths of the files currently being loaded, outermost first
+    in_progress: Vec<PathBuf>,
+}
+
+// ============================================================================
+// Resolution
+// ============================================================================
+
+impl ModuleResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a root file, preceded by all files it imports
+    ///
+    /// `path` is `None` for sources without a file (stdin); their imports are
+    /// resolved relative to the current directory.
+    pub fn add_root(
+        &mut self,
+        name: String,
+        content: String,
+        path: Option<&Path>,
+    ) -> Result<(), ImportError> {
+        let canonical = path.and_then(|path| path.canonicalize().ok());
+        let file = SourceFile {
+            name,
+            path: path.map(Path::to_path_buf),
+            content,
+        };
+        self.visit(file, canonical)
+    }
+
+    /// All loaded files, each after the files it imports
+    pub fn into_files(self) -> Vec<SourceFile> {
+        self.files
+    }
+
+    fn visit(&mut self, file: SourceFile, canonical: Option<PathBuf>) -> Result<(), ImportError> {
+        if let Some(path) = &canonical {
+            if self.loaded.contains(path) {
+                return Ok(());
+            }
+            self.in_progress.push(path.clone());
+        }
+
+        let base_dir = match canonical.as_deref().and_then(Path::parent) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+
+        for (import_path, span) in import_paths(&file.content) {
+            let target = base_dir.join(&import_path);
+            let error = |message: String| ImportError {
+                file: Box::new(file.clone()),
+                span,
+                message,
+            };
+
+            let Ok(target_canonical) = target.canonicalize() else {
+                return Err(error(format!(
+                    "cannot find imported file '{}'",
+                    import_path
+                )));
+            };
+            if self.in_progress.contains(&target_canonical) {
+                return Err(error(format!("import of '{}' forms a cycle", import_path)));
+            }
+            if self.loaded.contains(&target_canonical) {
+                continue;
+            }
+
+            let content = fs::read_to_string(&target_canonical)
+                .map_err(|e| error(format!("cannot read '{}': {}", import_path, e)))?;
+            let imported = SourceFile {
+                name: target.display().to_string(),
+                path: Some(target),
+                content,
+            };
+            self.visit(imported, Some(target_canonical))?;
+        }
+
+        if let Some(path) = canonical {
+            self.in_progress.pop();
+            self.loaded.insert(path);
+        }
+        self.files.push(file);
+        Ok(())
+    }
+}
+
+/// Paths and spans of the imports at the top of a source file
+///
+/// Files that fail to lex or parse yield no imports here; the errors are
+/// reported when the file itself is parsed.
+fn import_paths(content: &str) -> Vec<(String, Span)> {
+    let Ok((tokens, errors)) = tokenize(content);
+    if !errors.is_empty() {
+        return Vec::new();
+    }
+
+    import_decl()
+        .repeated()
+        .collect::<Vec<_>>()
+        .then_ignore(any().repeated())
+        .parse(&tokens)
+        .into_result()
+        .map(|imports| {
+            imports
+                .into_iter()
+                .map(|import| (import.path, import.path_span))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_paths_are_unescaped() {
+        let imports = import_paths(r#"import "parts/\"gear\"\tv2.cad"; sketch Main {}"#);
+
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].0, "parts/\"gear\"\tv2.cad");
+        // The span still covers the literal as written, quotes included
+        assert_eq!(imports[0].1.start.column, 8);
+        assert_eq!(imports[0].1.end_column, 32);
+    }
+}
@@ -9,8 +9,12 @@
 //! - **atoms**: Primitive parsers for literals and variables
 //! - **arithmetic**: Arithmetic operators (power, multiplication, division, modulo, addition, subtraction)
 //! - **comparison**: Comparison operators (equality, inequality)
+//! - **bitwise**: Bitwise operators (and, or, shifts)
 //! - **logical**: Logical operators (and, or)
-//! - **stmt**: Statement parsers (let statements, type annotations)
+//! - **pipe**: Pipeline operator (|>)
+//! - **range**: Range operator (..)
+//! - **stmt**: Statement parsers (let, assert, if, loop, with, break, continue and compound assignment statements, type annotations)
+//! - **program**: Top-level parsers (sketch, struct and function definitions, whole programs)
 //! - **error**: Error reporting with Ariadne
 //!
 //! # Error Reporting
@@ -30,7 +34,7 @@
 //! use crate::lexer::tokenize;
 //!
 //! let source = "1 + 2 * 3";
-//! let tokens = tokenize(source)?;
+//! let Ok((tokens, _)) = tokenize(source);
 //!
 //! match expr().parse(&tokens).into_result() {
 //!     Ok(ast) => println!("Parsed: {:?}", ast),
@@ -39,7 +43,9 @@
 //! ```
 
 use crate::ast::*;
-use crate::lexer::Token;
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::lexer::{Token, TokenTrait};
+use crate::span::Span;
 use chumsky::prelude::*;
 
 // ============================================================================
@@ -48,17 +54,27 @@ use chumsky::prelude::*;
 
 mod arithmetic;
 mod atoms;
+mod bitwise;
 mod comparison;
 mod error;
 mod logical;
+mod pipe;
+mod program;
+mod range;
 mod stmt;
 
 // ============================================================================
 // Re-exports
 // ============================================================================
 
+pub use error::parse_error_report;
+#[cfg_attr(not(test), allow(unused_imports))]
 pub use error::report_parse_errors;
-pub use stmt::let_stmt;
+#[cfg_attr(not(test), allow(unused_imports))]
+pub use program::{function_def, impl_block, sketch_def, struct_def};
+pub use program::{import_decl, program};
+#[cfg_attr(not(test), allow(unused_imports))]
+pub use stmt::{assert_stmt, let_stmt, stmt};
 
 // ============================================================================
 // Parser Type Definitions
@@ -76,28 +92,157 @@ pub type ParseError<'src> = extra::Err<Rich<'src, Token<'src>>>;
 pub fn expr_inner<'src>()
 -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone {
     recursive(|expr_rec| {
-        let pow_lhs = arithmetic::pow_lhs_parser(expr_rec.clone());
-        let pow_rhs = arithmetic::pow_rhs_parser(expr_rec.clone(), pow_lhs.clone());
-        let mul_rhs = arithmetic::mul_rhs_parser(expr_rec.clone(), pow_rhs.clone());
-        let mul_lhs = arithmetic::mul_lhs_parser(expr_rec, mul_rhs.clone(), pow_rhs);
-        let add_rhs = arithmetic::add_rhs_parser(mul_lhs.clone());
-        let add_lhs = arithmetic::add_lhs_parser(mul_lhs, add_rhs);
-        let cmp_rhs = comparison::cmp_rhs_parser(add_lhs.clone());
-        let cmp_lhs = comparison::cmp_lhs_parser(add_lhs, cmp_rhs);
-        let log_lhs = logical::log_parser(cmp_lhs);
-
-        // Convert CmpLhs<'src> (with logical operators) to Expr
-        log_lhs.map(Into::into)
+        let condition = condition_expr(expr_rec.clone()).boxed();
+        expr_levels(expr_rec, condition, true)
     })
 }
 
+/// Parser for the condition of an if statement or if expression
+///
+/// Like the expression parser, but a struct literal is only allowed inside
+/// parentheses, brackets or call arguments, as in Rust. Otherwise the body
+/// of `if ready { ... }` would be read as the fields of a struct `ready`.
+/// Subexpressions are parsed with `expr_parser`.
+pub fn condition_expr<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone {
+    recursive(|condition| expr_levels(expr_parser, condition, false))
+}
+
+/// The precedence hierarchy above the atoms, with `expr_rec` for
+/// subexpressions and `condition` for the conditions of if expressions
+fn expr_levels<'src>(
+    expr_rec: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+    condition: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+    struct_literals: bool,
+) -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone {
+    // Each precedence level is boxed: the fully nested parser type is large
+    // enough to push rustc's memory use past several gigabytes
+    let atom = atoms::atom(expr_rec.clone(), condition, struct_literals).boxed();
+    let pow_lhs = arithmetic::pow_lhs_parser(expr_rec.clone(), atom.clone()).boxed();
+    let pow_rhs = arithmetic::pow_rhs_parser(expr_rec.clone(), pow_lhs.clone()).boxed();
+    let mul_rhs = arithmetic::mul_rhs_parser(expr_rec.clone(), pow_rhs.clone()).boxed();
+    let mul_lhs = arithmetic::mul_lhs_parser(expr_rec.clone(), mul_rhs.clone(), pow_rhs).boxed();
+    let add_rhs = arithmetic::add_rhs_parser(mul_lhs.clone()).boxed();
+    let add_lhs = arithmetic::add_lhs_parser(mul_lhs, add_rhs).boxed();
+    let cmp_rhs = comparison::cmp_rhs_parser(add_lhs.clone()).boxed();
+    let cmp_lhs = comparison::cmp_lhs_parser(add_lhs, cmp_rhs).boxed();
+    let bit_lhs = bitwise::bit_parser(cmp_lhs).boxed();
+    let log_lhs = logical::log_parser(bit_lhs).boxed();
+
+    // Pipelines and ranges sit above everything else and produce a plain Expr
+    let pipe = pipe::pipe_parser(log_lhs, atom).boxed();
+    range::range_parser(pipe)
+}
+
 /// Parse a complete expression with end-of-input validation
-#[cfg_attr(not(test), allow(dead_code))] // Used in expression tests
 pub fn expr<'src>() -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone
 {
     expr_inner().then_ignore(end())
 }
 
+/// Parse a single expression, such as a constraint typed into an editor
+///
+/// Unlike the chumsky parsers above, this reports errors as diagnostics,
+/// so callers do not depend on chumsky. The expression is `None` only when
+/// the input could not be parsed at all.
+///
+/// # Example
+///
+/// A calculator reading one expression per line:
+///
+/// ```ignore
+/// use crate::lexer::tokenize;
+/// use crate::parser::parse_expression;
+///
+/// let source = "(1 + 2) * 3";
+/// let Ok((tokens, _)) = tokenize(source);
+/// match parse_expression(&tokens) {
+///     (Some(expr), errors) if errors.is_empty() => println!("{}", expr),
+///     (_, errors) => {
+///         for error in &errors {
+///             error.report("<input>", source).eprint(("<input>", Source::from(source))).unwrap();
+///         }
+///     }
+/// }
+/// ```
+#[cfg_attr(not(test), allow(dead_code))] // The CLI only parses whole files
+pub fn parse_expression<'src>(
+    tokens: &'src [Token<'src>],
+) -> (Option<Expr<'src>>, Vec<Diagnostic<'src>>) {
+    let (expr, errors) = expr().parse(tokens).into_output_errors();
+    (expr, errors.into_iter().map(Into::into).collect())
+}
+
+/// Parse a whole source file, skipping statements that fail to parse
+///
+/// Every error is returned as a diagnostic. An error inside a skipped
+/// statement is reported as recovered, so the report can say where parsing
+/// resumed. The program is `None` only when parsing could not get past an
+/// error.
+pub fn parse<'src>(tokens: &'src [Token<'src>]) -> (Option<Program<'src>>, Vec<Diagnostic<'src>>) {
+    let (program, errors) = program().parse(tokens).into_output_errors();
+    let skipped = program.as_ref().map(skipped_statements).unwrap_or_default();
+
+    let diagnostics = errors
+        .into_iter()
+        .map(|error| {
+            let position = error.found().map(TokenTrait::position);
+            let statement = position.and_then(|position| {
+                skipped
+                    .iter()
+                    .find(|span| span.contains_position(position.line, position.column))
+            });
+            match statement {
+                Some(&skipped) => {
+                    Diagnostic::error(DiagnosticKind::RecoveredStatement { error, skipped })
+                }
+                None => error.into(),
+            }
+        })
+        .collect();
+    (program, diagnostics)
+}
+
+/// Spans of the statements in `program` that were skipped after an error
+fn skipped_statements(program: &Program) -> Vec<Span> {
+    let functions = program
+        .functions()
+        .chain(program.sketches().flat_map(|sketch| sketch.functions()))
+        .chain(program.structs().flat_map(|def| &def.methods))
+        .chain(program.impls().flat_map(|block| &block.methods));
+    let sketch_stmts = program.sketches().flat_map(|sketch| {
+        sketch.items.iter().filter_map(|item| match item {
+            SketchItem::Stmt(stmt) | SketchItem::Test(stmt) => Some(stmt),
+            SketchItem::Function(_) => None,
+        })
+    });
+
+    let mut pending: Vec<&Stmt> = sketch_stmts
+        .chain(functions.flat_map(|function| &function.body))
+        .collect();
+    let mut spans = Vec::new();
+    while let Some(stmt) = pending.pop() {
+        match stmt {
+            Stmt::Error { span } => spans.push(*span),
+            Stmt::If {
+                then_body,
+                else_body,
+                ..
+            } => pending.extend(then_body.iter().chain(else_body.iter().flatten())),
+            Stmt::For { body, .. } | Stmt::While { body, .. } | Stmt::With { body, .. } => {
+                pending.extend(body)
+            }
+            Stmt::Let { .. }
+            | Stmt::Assert { .. }
+            | Stmt::CompoundAssign { .. }
+            | Stmt::Break { .. }
+            | Stmt::Continue { .. } => {}
+        }
+    }
+    spans
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
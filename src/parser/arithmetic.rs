@@ -4,102 +4,69 @@
 //! - Power (^) - right-associative, highest precedence
 //! - Multiplication (*), Division (/), Modulo (%) - left-associative
 //! - Addition (+), Subtraction (-) - left-associative, lowest precedence
-//! - Unary negation (-) and reference (&) - highest precedence
+//! - Unary negation (-), bitwise NOT (~) and reference (&) - highest precedence
 
 use crate::ast::HasSpan;
 use crate::ast::*;
-use crate::lexer::{Span, Token};
+use crate::lexer::{Token, TokenTrait};
 use chumsky::prelude::*;
 
 use super::ParseError;
-use super::atoms::atom;
-
-// ============================================================================
-// Helper functions for span management
-// ============================================================================
-
-/// Combine a position (from operator token) with a span (from inner expression)
-fn combine_span_from_pos(pos: crate::lexer::LineColumn, inner: Span) -> Span {
-    Span {
-        start: pos,
-        lines: if inner.start.line != pos.line {
-            inner.start.line - pos.line + inner.lines
-        } else {
-            inner.lines
-        },
-        end_column: inner.end_column,
-    }
-}
-
-/// Combine two spans into a larger span that encompasses both
-fn combine_spans(left: Span, right: Span) -> Span {
-    Span {
-        start: left.start,
-        lines: if right.lines > 0 {
-            left.lines + right.lines
-        } else {
-            left.lines
-        },
-        end_column: right.end_column,
-    }
-}
 
 // ============================================================================
 // Power Parsers (Highest precedence arithmetic operator)
 // ============================================================================
 
 /// Parser for power base (PowLhs<'src>) - atoms, parens, and unary operators
-pub fn pow_lhs_parser<'src, E>(
+pub fn pow_lhs_parser<'src, E, A>(
     expr_rec: E,
+    atom: A,
 ) -> impl Parser<'src, &'src [Token<'src>], PowLhs<'src>, ParseError<'src>> + Clone
 where
     E: Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+    A: Parser<'src, &'src [Token<'src>], Atom<'src>, ParseError<'src>> + Clone + 'src,
 {
-    // Recursive parser for unary operators (allows stacking like --x or &-x)
+    // Recursive parser for unary operators (allows stacking like --x, ~-x or &-x)
     recursive(|unary_rec| {
         choice((
             // Unary negation: -<expr>
-            select! { Token::Minus(t) => t.position }
+            select! { Token::Minus(t) => t.span() }
                 .then(unary_rec.clone())
-                .map(|(op_pos, inner): (_, PowLhs<'src>)| {
-                    let inner_span = inner.span();
-                    let span = combine_span_from_pos(op_pos, inner_span);
+                .map(|(op_span, inner): (_, PowLhs<'src>)| {
+                    let span = op_span | inner.span();
                     PowLhs::Neg {
                         inner: Box::new(inner),
                         span,
                     }
                 }),
+            // Bitwise NOT: ~<expr>
+            select! { Token::BitwiseNot(t) => t.span() }
+                .then(unary_rec.clone())
+                .map(|(op_span, inner): (_, PowLhs<'src>)| {
+                    let span = op_span | inner.span();
+                    PowLhs::BitNot {
+                        inner: Box::new(inner),
+                        span,
+                    }
+                }),
             // Unary reference: &<expr>
-            select! { Token::Ampersand(t) => t.position }
+            select! { Token::Ampersand(t) => t.span() }
                 .then(unary_rec)
-                .map(|(op_pos, inner): (_, PowLhs<'src>)| {
-                    let inner_span = inner.span();
-                    let span = combine_span_from_pos(op_pos, inner_span);
+                .map(|(op_span, inner): (_, PowLhs<'src>)| {
+                    let span = op_span | inner.span();
                     PowLhs::Ref {
                         inner: Box::new(inner),
                         span,
                     }
                 }),
             // Atom (base case)
-            atom(expr_rec.clone()).map(Into::into),
+            atom.map(Into::into),
             // Parenthesized expression
-            select! { Token::LeftParen(t) => t.position }
+            select! { Token::LeftParen(t) => t.span() }
                 .then(expr_rec)
-                .then(select! { Token::RightParen(t) => t.position })
-                .map(|((lparen_pos, e), rparen_pos)| {
-                    let span = if lparen_pos.line == rparen_pos.line {
-                        Span {
-                            start: lparen_pos,
-                            lines: 0,
-                            end_column: rparen_pos.column + 1,
-                        }
-                    } else {
-                        Span {
-                            start: lparen_pos,
-                            lines: rparen_pos.line - lparen_pos.line,
-                            end_column: rparen_pos.column + 1,
-                        }
-                    };
+                .then(select! { Token::RightParen(t) => t.span() })
+                .map(|((lparen_span, e), rparen_span)| {
+                    let span = lparen_span | rparen_span;
                     PowLhs::Paren {
                         inner: Box::new(e),
                         span,
@@ -129,9 +96,7 @@ where
                     None => base.into(), // No power operator, just return base as PowRhs
                     Some((_, rhs)) => {
                         // Build Pow node - combine spans from base and rhs
-                        let lhs_span = base.span();
-                        let rhs_span = rhs.span();
-                        let span = combine_spans(lhs_span, rhs_span);
+                        let span = base.span() | rhs.span();
                         PowRhs::Pow {
                             lhs: Box::new(base),
                             rhs: Box::new(rhs),
@@ -163,11 +128,13 @@ where
                 PowRhs::Pow { lhs, rhs, span } => MulRhs::Pow { lhs, rhs, span },
                 PowRhs::Paren { inner, span } => MulRhs::Paren { inner, span },
                 PowRhs::Neg { inner, span } => MulRhs::Neg { inner, span },
+                PowRhs::BitNot { inner, span } => MulRhs::BitNot { inner, span },
                 PowRhs::Ref { inner, span } => MulRhs::Ref { inner, span },
                 PowRhs::Var { name, span } => MulRhs::Var { name, span },
-                PowRhs::IntLit { value, span } => MulRhs::IntLit { value, span },
-                PowRhs::FloatLit { value, span } => MulRhs::FloatLit { value, span },
+                PowRhs::IntLit { value, text, span } => MulRhs::IntLit { value, text, span },
+                PowRhs::FloatLit { value, text, span } => MulRhs::FloatLit { value, text, span },
                 PowRhs::BoolLit { value, span } => MulRhs::BoolLit { value, span },
+                PowRhs::StrLit { value, span } => MulRhs::StrLit { value, span },
                 PowRhs::Call { name, args, span } => MulRhs::Call { name, args, span },
                 PowRhs::MethodCall {
                     receiver,
@@ -193,25 +160,33 @@ where
                 PowRhs::StructLit { name, fields, span } => {
                     MulRhs::StructLit { name, fields, span }
                 }
+                PowRhs::Match {
+                    scrutinee,
+                    arms,
+                    span,
+                } => MulRhs::Match {
+                    scrutinee,
+                    arms,
+                    span,
+                },
+                PowRhs::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    span,
+                } => MulRhs::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    span,
+                },
             }
         }),
-        select! { Token::LeftParen(t) => t.position }
+        select! { Token::LeftParen(t) => t.span() }
             .then(expr_rec.clone())
-            .then(select! { Token::RightParen(t) => t.position })
-            .map(|((lparen_pos, e), rparen_pos)| {
-                let span = if lparen_pos.line == rparen_pos.line {
-                    Span {
-                        start: lparen_pos,
-                        lines: 0,
-                        end_column: rparen_pos.column + 1,
-                    }
-                } else {
-                    Span {
-                        start: lparen_pos,
-                        lines: rparen_pos.line - lparen_pos.line,
-                        end_column: rparen_pos.column + 1,
-                    }
-                };
+            .then(select! { Token::RightParen(t) => t.span() })
+            .map(|((lparen_span, e), rparen_span)| {
+                let span = lparen_span | rparen_span;
                 MulRhs::Paren {
                     inner: Box::new(e),
                     span,
@@ -243,11 +218,13 @@ where
                 PowRhs::Pow { lhs, rhs, span } => MulLhs::Pow { lhs, rhs, span },
                 PowRhs::Paren { inner, span } => MulLhs::Paren { inner, span },
                 PowRhs::Neg { inner, span } => MulLhs::Neg { inner, span },
+                PowRhs::BitNot { inner, span } => MulLhs::BitNot { inner, span },
                 PowRhs::Ref { inner, span } => MulLhs::Ref { inner, span },
                 PowRhs::Var { name, span } => MulLhs::Var { name, span },
-                PowRhs::IntLit { value, span } => MulLhs::IntLit { value, span },
-                PowRhs::FloatLit { value, span } => MulLhs::FloatLit { value, span },
+                PowRhs::IntLit { value, text, span } => MulLhs::IntLit { value, text, span },
+                PowRhs::FloatLit { value, text, span } => MulLhs::FloatLit { value, text, span },
                 PowRhs::BoolLit { value, span } => MulLhs::BoolLit { value, span },
+                PowRhs::StrLit { value, span } => MulLhs::StrLit { value, span },
                 PowRhs::Call { name, args, span } => MulLhs::Call { name, args, span },
                 PowRhs::MethodCall {
                     receiver,
@@ -273,25 +250,33 @@ where
                 PowRhs::StructLit { name, fields, span } => {
                     MulLhs::StructLit { name, fields, span }
                 }
+                PowRhs::Match {
+                    scrutinee,
+                    arms,
+                    span,
+                } => MulLhs::Match {
+                    scrutinee,
+                    arms,
+                    span,
+                },
+                PowRhs::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    span,
+                } => MulLhs::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    span,
+                },
             }
         }),
-        select! { Token::LeftParen(t) => t.position }
+        select! { Token::LeftParen(t) => t.span() }
             .then(expr_rec)
-            .then(select! { Token::RightParen(t) => t.position })
-            .map(|((lparen_pos, e), rparen_pos)| {
-                let span = if lparen_pos.line == rparen_pos.line {
-                    Span {
-                        start: lparen_pos,
-                        lines: 0,
-                        end_column: rparen_pos.column + 1,
-                    }
-                } else {
-                    Span {
-                        start: lparen_pos,
-                        lines: rparen_pos.line - lparen_pos.line,
-                        end_column: rparen_pos.column + 1,
-                    }
-                };
+            .then(select! { Token::RightParen(t) => t.span() })
+            .map(|((lparen_span, e), rparen_span)| {
+                let span = lparen_span | rparen_span;
                 MulLhs::Paren {
                     inner: Box::new(e),
                     span,
@@ -303,9 +288,7 @@ where
     mul_atom.foldl(
         choice((mul_op, div_op, mod_op)).then(mul_rhs).repeated(),
         |lhs: MulLhs<'src>, (op, rhs): (char, MulRhs<'src>)| {
-            let lhs_span = lhs.span();
-            let rhs_span = rhs.span();
-            let span = combine_spans(lhs_span, rhs_span);
+            let span = lhs.span() | rhs.span();
 
             if op == '*' {
                 MulLhs::Mul {
@@ -362,9 +345,7 @@ where
     add_atom.foldl(
         choice((add_op, sub_op)).then(add_rhs).repeated(),
         |lhs: AddLhs<'src>, (op, rhs): (char, AddRhs<'src>)| {
-            let lhs_span = lhs.span();
-            let rhs_span = rhs.span();
-            let span = combine_spans(lhs_span, rhs_span);
+            let span = lhs.span() | rhs.span();
 
             if op == '+' {
                 AddLhs::Add {
@@ -4,13 +4,16 @@
 //! - Integer literals
 //! - Float literals
 //! - Boolean literals
+//! - String literals
+//! - If expressions
+//! - Match expressions and their patterns
 //! - Variable identifiers
 //! - Function calls
 //! - Method calls
 //! - Atomic expressions (combination of all primitives)
 
-use crate::ast::{Atom, Expr};
-use crate::lexer::{Span, Token};
+use crate::ast::{Atom, Expr, HasSpan, MatchArm, Pattern};
+use crate::lexer::{Token, TokenTrait};
 use chumsky::prelude::*;
 
 use super::ParseError;
@@ -20,111 +23,160 @@ use super::ParseError;
 // ============================================================================
 
 /// Parse an atomic expression (Atom enum)
-/// Takes an expression parser for parsing function call arguments
+///
+/// Takes an expression parser for parsing function call arguments and a
+/// condition parser for the conditions of if expressions and the scrutinees
+/// of match expressions. Struct literals
+/// are only parsed when `struct_literals` is set, so that the name in
+/// `if ready { ... }` is not taken for the start of a struct literal.
 pub fn atom<'src>(
-    expr: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone,
+    expr: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+    condition: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+    struct_literals: bool,
 ) -> impl Parser<'src, &'src [Token<'src>], Atom<'src>, ParseError<'src>> + Clone {
+    // Match expression: match <expr> { <pattern> => <expr>, ... }
+    let match_arm = pattern()
+        .then_ignore(select! { Token::FatArrow(_) => () })
+        .then(expr.clone())
+        .map(|(pattern, body)| MatchArm {
+            span: pattern.span() | body.span(),
+            pattern,
+            body,
+        });
+    let match_expr = select! { Token::Match(t) => t.span() }
+        .then(condition.clone().labelled("scrutinee"))
+        .then_ignore(select! { Token::LeftBrace(_) => () })
+        .then(
+            match_arm
+                .separated_by(select! { Token::Comma(_) => () })
+                .allow_trailing()
+                .collect::<Vec<_>>(),
+        )
+        .then(select! { Token::RightBrace(t) => t.span() })
+        .map(
+            |(((match_span, scrutinee), arms), rbrace_span)| Atom::Match {
+                scrutinee: Box::new(scrutinee),
+                arms,
+                span: match_span | rbrace_span,
+            },
+        )
+        .labelled("match expression");
+
+    // If expression: if <cond> { <expr> } else { <expr> }, where the else
+    // branch may be another if expression
+    let if_expr = recursive(|if_expr| {
+        let branch = select! { Token::LeftBrace(_) => () }
+            .ignore_then(expr.clone())
+            .then(select! { Token::RightBrace(t) => t.span() });
+
+        select! { Token::If(t) => t.span() }
+            .then(condition.labelled("condition"))
+            .then(branch.clone())
+            .then_ignore(select! { Token::Else(_) => () })
+            .then(choice((
+                branch,
+                if_expr.map(|nested: Atom<'src>| {
+                    let span = nested.span();
+                    (nested.into(), span)
+                }),
+            )))
+            .map(
+                |(((if_span, condition), (then_branch, _)), (else_branch, else_span))| Atom::If {
+                    condition: Box::new(condition),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                    span: if_span | else_span,
+                },
+            )
+            .labelled("if expression")
+    });
+
+    // Struct literal: StructName { field1: value1, field2: value2, ... }
+    let struct_lit = select! {
+        Token::Identifier(t) => (t.name, t.span),
+    }
+    .then_ignore(select! { Token::LeftBrace(_) => () })
+    .then(
+        select! { Token::Identifier(t) => t.name }
+            .then_ignore(select! { Token::Colon(_) => () })
+            .then(expr.clone())
+            .separated_by(select! { Token::Comma(_) => () })
+            .allow_trailing()
+            .collect::<Vec<_>>(),
+    )
+    .then(select! { Token::RightBrace(t) => t.span() })
+    .map(
+        |(((name, name_span), fields), rbrace_span)| Atom::StructLit {
+            name,
+            fields,
+            span: name_span | rbrace_span,
+        },
+    );
+
     // First, parse a base atom (literal, variable, or function call)
     let base_atom = choice((
+        if_expr,
+        match_expr,
         // Try float first (it's more specific)
         select! {
-            Token::FloatLiteral(t) => Atom::FloatLit { value: t.value, span: t.span },
+            Token::FloatLiteral(t) => Atom::FloatLit { value: t.value, text: t.text, span: t.span },
         },
         // Then integer
         select! {
-            Token::IntLiteral(t) => Atom::IntLit { value: t.value, span: t.span },
+            Token::IntLiteral(t) => Atom::IntLit { value: t.value, text: t.text, span: t.span },
         },
         // Then boolean
         select! {
-            Token::True(t) => Atom::BoolLit { value: true, span: Span { start: t.position, lines: 0, end_column: t.position.column + 4 } },
-            Token::False(t) => Atom::BoolLit { value: false, span: Span { start: t.position, lines: 0, end_column: t.position.column + 5 } },
+            Token::True(t) => Atom::BoolLit { value: true, span: t.span() },
+            Token::False(t) => Atom::BoolLit { value: false, span: t.span() },
+        },
+        // Then string
+        select! {
+            Token::StringLiteral(t) => Atom::StrLit { value: t.unescaped(), span: t.span },
         },
         // Array literal: [elem1, elem2, ...]
-        expr.clone()
-            .separated_by(select! { Token::Comma(_) => () })
-            .allow_trailing()
-            .collect::<Vec<_>>()
-            .delimited_by(
-                select! { Token::LeftBracket(_) => () },
-                select! { Token::RightBracket(_) => () },
+        select! { Token::LeftBracket(t) => t.span() }
+            .then(
+                expr.clone()
+                    .separated_by(select! { Token::Comma(_) => () })
+                    .allow_trailing()
+                    .collect::<Vec<_>>(),
             )
-            .map_with(|elements, e| {
-                let span_range = e.span();
-                Atom::ArrayLit {
+            .then(select! { Token::RightBracket(t) => t.span() })
+            .map(
+                |((lbracket_span, elements), rbracket_span)| Atom::ArrayLit {
                     elements,
-                    span: Span {
-                        start: crate::lexer::LineColumn {
-                            line: 1,
-                            column: span_range.start + 1,
-                        },
-                        lines: 0,
-                        end_column: span_range.end + 1,
-                    },
-                }
-            }),
-        // Struct literal: StructName { field1: value1, field2: value2, ... }
-        select! {
-            Token::Identifier(t) => (t.name, t.span),
-        }
-        .then(
-            select! { Token::Identifier(t) => t.name }
-                .then_ignore(select! { Token::Colon(_) => () })
-                .then(expr.clone())
-                .separated_by(select! { Token::Comma(_) => () })
-                .allow_trailing()
-                .collect::<Vec<_>>()
-                .delimited_by(
-                    select! { Token::LeftBrace(_) => () },
-                    select! { Token::RightBrace(_) => () },
-                ),
-        )
-        .map_with(|((name, name_span), fields), e| {
-            let span_range = e.span();
-            Atom::StructLit {
-                name,
-                fields,
-                span: Span {
-                    start: name_span.start,
-                    lines: 0,
-                    end_column: span_range.end + 1,
+                    span: lbracket_span | rbracket_span,
                 },
-            }
-        }),
+            ),
         // Function call: identifier followed by parentheses with comma-separated arguments
         select! {
             Token::Identifier(t) => (t.name, t.span),
         }
+        .then_ignore(select! { Token::LeftParen(_) => () })
         .then(
             expr.clone()
                 .separated_by(select! { Token::Comma(_) => () })
                 .allow_trailing()
-                .collect::<Vec<_>>()
-                .delimited_by(
-                    select! { Token::LeftParen(t) => t.position },
-                    select! { Token::RightParen(t) => t.position },
-                )
-                .map_with(|args, e| {
-                    let span_range = e.span();
-                    (args, span_range)
-                }),
+                .collect::<Vec<_>>(),
         )
-        .map(|((name, name_span), (args, call_span))| {
-            // Combine name span and call span for full function call span
-            Atom::Call {
-                name,
-                args,
-                span: Span {
-                    start: name_span.start,
-                    lines: 0, // Assuming single line for now
-                    end_column: name_span.end_column + (call_span.end - call_span.start),
-                },
-            }
+        .then(select! { Token::RightParen(t) => t.span() })
+        .map(|(((name, name_span), args), rparen_span)| Atom::Call {
+            name,
+            args,
+            span: name_span | rparen_span,
         }),
-        // Finally plain variable (no function call)
+        // Finally plain variable (no function call), including `self` in methods
         select! {
             Token::Identifier(t) => Atom::Var { name: t.name, span: t.span },
+            Token::SelfKw(t) => Atom::Var { name: "self", span: t.span() },
         },
     ));
+    let base_atom = if struct_literals {
+        struct_lit.or(base_atom).boxed()
+    } else {
+        base_atom.boxed()
+    };
 
     // Then parse zero or more method calls or field accesses as postfix operations
     // Method call: .identifier(args)
@@ -134,18 +186,14 @@ pub fn atom<'src>(
             Token::Identifier(t) => (t.name, t.span),
         })
         .then(
-            expr.clone()
-                .separated_by(select! { Token::Comma(_) => () })
-                .allow_trailing()
-                .collect::<Vec<_>>()
-                .delimited_by(
-                    select! { Token::LeftParen(_) => () },
-                    select! { Token::RightParen(t) => t.position },
+            select! { Token::LeftParen(_) => () }
+                .ignore_then(
+                    expr.clone()
+                        .separated_by(select! { Token::Comma(_) => () })
+                        .allow_trailing()
+                        .collect::<Vec<_>>(),
                 )
-                .map_with(|args, e| {
-                    let span_range = e.span();
-                    (args, span_range)
-                })
+                .then(select! { Token::RightParen(t) => t.span() })
                 .or_not(),
         );
 
@@ -155,39 +203,21 @@ pub fn atom<'src>(
         .map(|(mut atom, suffixes)| {
             // Apply each suffix (method call or field access) in sequence
             for ((name, name_span), args_and_span) in suffixes {
-                let start = match &atom {
-                    Atom::Var { span, .. } => span.start,
-                    Atom::IntLit { span, .. } => span.start,
-                    Atom::FloatLit { span, .. } => span.start,
-                    Atom::BoolLit { span, .. } => span.start,
-                    Atom::Call { span, .. } => span.start,
-                    Atom::MethodCall { span, .. } => span.start,
-                    Atom::FieldAccess { span, .. } => span.start,
-                    Atom::ArrayLit { span, .. } => span.start,
-                    Atom::StructLit { span, .. } => span.start,
-                };
+                let receiver_span = atom.span();
 
                 atom = match args_and_span {
                     // Method call: has arguments
-                    Some((args, call_span)) => Atom::MethodCall {
+                    Some((args, rparen_span)) => Atom::MethodCall {
                         receiver: Box::new(atom.into()),
                         method: name,
                         args,
-                        span: Span {
-                            start,
-                            lines: 0,
-                            end_column: call_span.end,
-                        },
+                        span: receiver_span | rparen_span,
                     },
                     // Field access: no arguments
                     None => Atom::FieldAccess {
                         receiver: Box::new(atom.into()),
                         field: name,
-                        span: Span {
-                            start,
-                            lines: 0,
-                            end_column: name_span.end_column,
-                        },
+                        span: receiver_span | name_span,
                     },
                 };
             }
@@ -196,6 +226,70 @@ pub fn atom<'src>(
         .labelled("atom")
 }
 
+/// Parse the pattern of a match arm
+///
+/// Syntax:
+///   _
+///   <literal>
+///   <name>
+///   <Struct> { <field>[: <pattern>], ... }
+pub fn pattern<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Pattern<'src>, ParseError<'src>> + Clone {
+    recursive(|pattern| {
+        let literal = select! {
+            Token::FloatLiteral(t) => Atom::FloatLit { value: t.value, text: t.text, span: t.span },
+            Token::IntLiteral(t) => Atom::IntLit { value: t.value, text: t.text, span: t.span },
+            Token::True(t) => Atom::BoolLit { value: true, span: t.span() },
+            Token::False(t) => Atom::BoolLit { value: false, span: t.span() },
+            Token::StringLiteral(t) => Atom::StrLit { value: t.unescaped(), span: t.span },
+        }
+        .map(|value| Pattern::Literal { value });
+
+        // A field without a pattern binds a name of its own
+        let field = select! {
+            Token::Identifier(t) => (t.name, t.span),
+        }
+        .then(
+            select! { Token::Colon(_) => () }
+                .ignore_then(pattern)
+                .or_not(),
+        )
+        .map(|((field, field_span), pattern)| {
+            let pattern = pattern.unwrap_or(Pattern::Binding {
+                name: field,
+                span: field_span,
+            });
+            (field, pattern)
+        });
+        let struct_pattern = select! {
+            Token::Identifier(t) => (t.name, t.span),
+        }
+        .then_ignore(select! { Token::LeftBrace(_) => () })
+        .then(
+            field
+                .separated_by(select! { Token::Comma(_) => () })
+                .allow_trailing()
+                .collect::<Vec<_>>(),
+        )
+        .then(select! { Token::RightBrace(t) => t.span() })
+        .map(
+            |(((name, name_span), fields), rbrace_span)| Pattern::Struct {
+                name,
+                fields,
+                span: name_span | rbrace_span,
+            },
+        );
+
+        let binding = select! {
+            Token::Identifier(t) if t.name == "_" => Pattern::Wildcard { span: t.span },
+            Token::Identifier(t) => Pattern::Binding { name: t.name, span: t.span },
+        };
+
+        choice((literal, struct_pattern, binding))
+    })
+    .labelled("pattern")
+}
+
 // ============================================================================
 // Value-only parsers (for testing)
 // ============================================================================
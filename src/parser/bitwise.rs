@@ -0,0 +1,75 @@
+//! Bitwise expression parsers
+//!
+//! This module contains parsers for bitwise operations:
+//! - Shifts (<<, >>)
+//! - AND (&)
+//! - OR (|)
+//!
+//! As in C, shifts bind tighter than AND, which binds tighter than OR, so
+//! `flags | mask & x << 2` is `flags | (mask & (x << 2))`. Each level is
+//! left-associative, and all three sit below comparison and above the
+//! logical operators. Bitwise NOT (~) is a unary operator and is parsed with
+//! the arithmetic ones.
+
+use crate::ast::HasSpan;
+use crate::ast::*;
+use crate::lexer::Token;
+use chumsky::prelude::*;
+
+use super::ParseError;
+
+// ============================================================================
+// Bitwise Operators Parser
+// ============================================================================
+
+/// Parser for bitwise operators (lower precedence than comparison)
+pub fn bit_parser<'src, C>(
+    cmp_lhs: C,
+) -> impl Parser<'src, &'src [Token<'src>], CmpLhs<'src>, ParseError<'src>> + Clone
+where
+    C: Parser<'src, &'src [Token<'src>], CmpLhs<'src>, ParseError<'src>> + Clone + 'src,
+{
+    let shift_op = select! {
+        Token::ShiftLeft(_) => "<<",
+        Token::ShiftRight(_) => ">>",
+    };
+    let and_op = select! { Token::Ampersand(_) => "&" };
+    let or_op = select! { Token::Pipe(_) => "|" };
+
+    let shift = bit_level(cmp_lhs, shift_op).boxed();
+    let and = bit_level(shift, and_op).boxed();
+    bit_level(and, or_op)
+}
+
+/// One left-associative bitwise level over `operand`, the next tighter level
+///
+/// Like `and`/`or`, the rhs is wrapped in a Paren so it fits in CmpRhs.
+fn bit_level<'src, O, P>(
+    operand: O,
+    op: P,
+) -> impl Parser<'src, &'src [Token<'src>], CmpLhs<'src>, ParseError<'src>> + Clone
+where
+    O: Parser<'src, &'src [Token<'src>], CmpLhs<'src>, ParseError<'src>> + Clone,
+    P: Parser<'src, &'src [Token<'src>], &'static str, ParseError<'src>> + Clone,
+{
+    operand.clone().foldl(
+        op.then(operand).repeated(),
+        |lhs: CmpLhs<'src>, (op, rhs): (&str, CmpLhs<'src>)| {
+            let paren_span = rhs.span();
+            let span = lhs.span() | paren_span;
+            let lhs = Box::new(lhs);
+            let rhs = Box::new(CmpRhs::Paren {
+                inner: Box::new(Expr::from(rhs)),
+                span: paren_span,
+            });
+
+            match op {
+                "&" => CmpLhs::BitAnd { lhs, rhs, span },
+                "|" => CmpLhs::BitOr { lhs, rhs, span },
+                "<<" => CmpLhs::Shl { lhs, rhs, span },
+                ">>" => CmpLhs::Shr { lhs, rhs, span },
+                _ => unreachable!(),
+            }
+        },
+    )
+}
@@ -3,34 +3,19 @@
 //! This module contains parsers for comparison operations:
 //! - Equality (==)
 //! - Inequality (!=)
+//! - Ordering (<, >, <=, >=)
+//! - Membership (in, not in)
 //!
 //! These operators have lower precedence than arithmetic operators
 //! but higher precedence than logical operators.
 
 use crate::ast::HasSpan;
 use crate::ast::*;
-use crate::lexer::{Span, Token};
+use crate::lexer::Token;
 use chumsky::prelude::*;
 
 use super::ParseError;
 
-// ============================================================================
-// Helper functions for span management
-// ============================================================================
-
-/// Combine two spans into a larger span that encompasses both
-fn combine_spans(left: Span, right: Span) -> Span {
-    Span {
-        start: left.start,
-        lines: if right.lines > 0 {
-            left.lines + right.lines
-        } else {
-            left.lines
-        },
-        end_column: right.end_column,
-    }
-}
-
 // ============================================================================
 // Comparison Parsers
 // ============================================================================
@@ -56,16 +41,28 @@ where
 {
     let eq_op = select! { Token::EqualsEquals(_) => "==" };
     let neq_op = select! { Token::NotEquals(_) => "!=" };
+    let ord_op = select! {
+        Token::LessThan(_) => "<",
+        Token::GreaterThan(_) => ">",
+        Token::LessEquals(_) => "<=",
+        Token::GreaterEquals(_) => ">=",
+    };
+    let in_op = choice((
+        select! { Token::In(_) => "in" },
+        select! { Token::Not(_) => () }
+            .then(select! { Token::In(_) => () })
+            .to("not in"),
+    ));
 
     let cmp_atom = add_lhs.map(Into::into);
 
-    // Left-associative equality and not-equal operators (higher precedence than logical)
+    // Left-associative comparison operators (higher precedence than logical)
     cmp_atom.foldl(
-        choice((eq_op, neq_op)).then(cmp_rhs).repeated(),
+        choice((eq_op, neq_op, ord_op, in_op))
+            .then(cmp_rhs)
+            .repeated(),
         |lhs: CmpLhs<'src>, (op, rhs): (&str, CmpRhs<'src>)| {
-            let lhs_span = lhs.span();
-            let rhs_span = rhs.span();
-            let span = combine_spans(lhs_span, rhs_span);
+            let span = lhs.span() | rhs.span();
 
             match op {
                 "==" => CmpLhs::Eq {
@@ -78,6 +75,36 @@ where
                     rhs: Box::new(rhs),
                     span,
                 },
+                "<" => CmpLhs::Lt {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+                ">" => CmpLhs::Gt {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+                "<=" => CmpLhs::LtEq {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+                ">=" => CmpLhs::GtEq {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+                "in" => CmpLhs::In {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
+                "not in" => CmpLhs::NotIn {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    span,
+                },
                 _ => unreachable!(),
             }
         },
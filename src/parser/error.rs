@@ -3,57 +3,87 @@
 //! This module provides beautiful error reporting for parser errors
 //! using the Ariadne library to create colored, contextual error messages.
 
-use crate::lexer::Token;
-use ariadne::{Color, Label, Report, ReportKind, Source};
+use crate::diagnostic::report_config;
+use crate::lexer::{Token, TokenTrait};
+use crate::span::LineIndex;
+use ariadne::{Color, Label, Report, ReportBuilder, ReportKind, Source};
+use chumsky::error::RichPattern;
 use chumsky::prelude::*;
+use std::ops::Range;
 
 // ============================================================================
 // Error Reporting with Ariadne
 // ============================================================================
 
-/// Convert parser errors to beautiful Ariadne reports
+/// Start an Ariadne report for a single parser error in `source`
 ///
-/// This function converts Chumsky's Rich errors into Ariadne reports with
-/// proper spans and helpful error messages. All necessary information
-/// (spans, labels, expected tokens) is preserved from the parser.
+/// The error's token carries a line/column span, which is converted to a
+/// byte range of `source` for Ariadne. An error at the end of input points
+/// just past the last character.
+pub fn parse_error_report<'a>(
+    filename: &'a str,
+    source: &str,
+    kind: ReportKind<'a>,
+    error: &Rich<'_, Token<'_>>,
+) -> ReportBuilder<'a, (&'a str, Range<usize>)> {
+    let (range, found) = match error.found() {
+        Some(token) => (
+            LineIndex::new(source).range(token.span()),
+            format!("Unexpected '{}'", token.value_str()),
+        ),
+        None => (
+            source.len()..source.len(),
+            "Unexpected end of input".to_string(),
+        ),
+    };
+
+    let mut report = Report::build(kind, filename, range.start)
+        .with_config(report_config())
+        .with_message("Parse error");
+
+    // Add the main error label
+    report = report.with_label(
+        Label::new((filename, range))
+            .with_message(found)
+            .with_color(Color::Red),
+    );
+
+    // Add expected tokens if available
+    let expected = error
+        .expected()
+        .filter_map(expected_description)
+        .collect::<Vec<_>>();
+    if !expected.is_empty() {
+        report = report.with_note(format!("Expected one of: {}", expected.join(", ")));
+    }
+
+    report
+}
+
+/// Describe an expected pattern the way it is written in source
+///
+/// Patterns that name no concrete alternative, such as the catch-all a
+/// guarded `select!` reports, are skipped.
+fn expected_description(pattern: &RichPattern<'_, Token<'_>>) -> Option<String> {
+    match pattern {
+        RichPattern::Token(token) => Some(format!("'{}'", token.value_str())),
+        RichPattern::Label(label) => Some(label.to_string()),
+        RichPattern::Identifier(name) => Some(format!("'{}'", name)),
+        RichPattern::Any => Some("any token".to_string()),
+        RichPattern::EndOfInput => Some("end of input".to_string()),
+        _ => None,
+    }
+}
+
+/// Convert parser errors to beautiful Ariadne reports and print them
+#[cfg_attr(not(test), allow(dead_code))] // The CLI reports through diagnostic::render_all
 pub fn report_parse_errors<'src>(
     filename: &str,
     source: &'src str,
     errors: Vec<Rich<'src, Token<'src>>>,
 ) {
     for error in errors {
-        let span = error.span();
-
-        // Calculate byte offset from token position
-        // Note: This requires the tokens to have proper span information
-        let offset = span.start;
-
-        let mut report =
-            Report::build(ReportKind::Error, filename, offset).with_message("Parse error");
-
-        // Add the main error label
-        report = report.with_label(
-            Label::new((filename, offset..offset + 1))
-                .with_message(format!("Unexpected token: {:?}", error.found()))
-                .with_color(Color::Red),
-        );
-
-        // Add expected tokens if available
-        if !error.expected().collect::<Vec<_>>().is_empty() {
-            let expected = error
-                .expected()
-                .map(|e| format!("{:?}", e))
-                .collect::<Vec<_>>()
-                .join(", ");
-            report = report.with_note(format!("Expected one of: {}", expected));
-        }
-
-        // Add help message based on error context
-        if error.found().is_none() {
-            report = report.with_help("Unexpected end of input");
-        }
-
-        report
+        parse_error_report(filename, source, ReportKind::Error, &error)
             .finish()
             .eprint((filename, Source::from(source)))
             .unwrap();
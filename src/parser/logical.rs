@@ -4,32 +4,16 @@
 //! - AND (and)
 //! - OR (or)
 //!
-//! These operators have the lowest precedence of all binary operators.
+//! These operators have the lowest precedence of all binary operators,
+//! below the bitwise ones.
 
 use crate::ast::HasSpan;
 use crate::ast::*;
-use crate::lexer::{Span, Token};
+use crate::lexer::Token;
 use chumsky::prelude::*;
 
 use super::ParseError;
 
-// ============================================================================
-// Helper functions for span management
-// ============================================================================
-
-/// Combine two spans into a larger span that encompasses both
-fn combine_spans(left: Span, right: Span) -> Span {
-    Span {
-        start: left.start,
-        lines: if right.lines > 0 {
-            left.lines + right.lines
-        } else {
-            left.lines
-        },
-        end_column: right.end_column,
-    }
-}
-
 // ============================================================================
 // Logical Operators Parser
 // ============================================================================
@@ -50,10 +34,8 @@ where
     log_atom.foldl(
         choice((and_op, or_op)).then(cmp_lhs).repeated(),
         |lhs: CmpLhs<'src>, (op, rhs): (&str, CmpLhs<'src>)| {
-            let lhs_span = lhs.span();
-            let rhs_span = rhs.span();
-            let span = combine_spans(lhs_span, rhs_span);
-            let paren_span = rhs_span; // Use rhs span for Paren
+            let paren_span = rhs.span(); // Use rhs span for Paren
+            let span = lhs.span() | paren_span;
 
             match op {
                 "and" => CmpLhs::And {
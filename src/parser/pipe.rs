@@ -0,0 +1,53 @@
+//! Pipeline expression parser
+//!
+//! This module contains the parser for the pipeline operator:
+//! - Pipe (|>) - left-associative, lower precedence than every operator but ranges
+//!
+//! `value |> f |> g(2)` feeds `value` into `f`, and the result into `g` as
+//! its first argument. The right-hand side of every `|>` must be a function
+//! name or a function call.
+
+use crate::ast::HasSpan;
+use crate::ast::*;
+use crate::lexer::Token;
+use chumsky::prelude::*;
+
+use super::ParseError;
+
+// ============================================================================
+// Pipe Parser
+// ============================================================================
+
+/// Parser for pipelines (lower precedence than logical operators)
+pub fn pipe_parser<'src, L, A>(
+    log_lhs: L,
+    atom: A,
+) -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone
+where
+    L: Parser<'src, &'src [Token<'src>], CmpLhs<'src>, ParseError<'src>> + Clone,
+    A: Parser<'src, &'src [Token<'src>], Atom<'src>, ParseError<'src>> + Clone,
+{
+    let pipe_op = select! { Token::PipeArrow(_) => () };
+
+    // Only a bare function name or a call can receive the piped value
+    let rhs_fn = atom.try_map(|rhs_fn, span| match rhs_fn {
+        Atom::Var { .. } | Atom::Call { .. } => Ok(rhs_fn),
+        _ => Err(Rich::custom(
+            span,
+            "expected a function name or call after '|>'",
+        )),
+    });
+
+    // Left-associative: `a |> f |> g` is `(a |> f) |> g`
+    log_lhs.map(Expr::from).foldl(
+        pipe_op.ignore_then(rhs_fn).repeated(),
+        |lhs: Expr<'src>, rhs_fn: Atom<'src>| {
+            let span = lhs.span() | rhs_fn.span();
+            Expr::Pipe {
+                lhs: Box::new(lhs),
+                rhs_fn: Box::new(rhs_fn),
+                span,
+            }
+        },
+    )
+}
@@ -0,0 +1,379 @@
+//! Top-level definition parsers (sketches, structs, impls, functions and whole programs)
+
+use crate::ast::{
+    Expr, FieldDef, FunctionDef, HasSpan, ImplBlock, ImportDecl, Item, Param, Program, SketchDef,
+    SketchItem, StructDef, Visibility,
+};
+use crate::lexer::{Token, TokenTrait};
+use crate::parser::stmt::{return_type, stmt, type_annotation};
+use crate::parser::{ParseError, expr_inner};
+use crate::span::Span;
+use chumsky::prelude::*;
+
+// ============================================================================
+// Import Parser
+// ============================================================================
+
+/// Parse an import declaration
+///
+/// Syntax:
+///   import "<path>";
+///   import "<path>" use <name>, ...;
+pub fn import_decl<'src>()
+-> impl Parser<'src, &'src [Token<'src>], ImportDecl<'src>, ParseError<'src>> + Clone {
+    let items = select! { Token::Use(_) => () }.ignore_then(
+        select! {
+            Token::Identifier(t) => (t.name, t.span),
+        }
+        .separated_by(select! { Token::Comma(_) => () })
+        .at_least(1)
+        .collect::<Vec<_>>(),
+    );
+
+    select! {
+        Token::Import(t) => t.span(),
+    }
+    .then(
+        select! {
+            Token::StringLiteral(t) => (t.unescaped(), t.span),
+        }
+        .labelled("import path"),
+    )
+    .then(items.or_not())
+    .then(select! {
+        Token::SemiColon(t) => t.span(),
+    })
+    .map(
+        |(((import_span, (path, path_span)), items), semi_span)| ImportDecl {
+            path,
+            path_span,
+            items,
+            span: import_span | semi_span,
+        },
+    )
+    .labelled("import")
+}
+
+// ============================================================================
+// Visibility Parser
+// ============================================================================
+
+/// Parse an optional `pub` modifier
+///
+/// Also returns the span of `pub` when present, so that a definition's span
+/// can start at the modifier.
+fn visibility<'src>()
+-> impl Parser<'src, &'src [Token<'src>], (Visibility, Option<Span>), ParseError<'src>> + Clone {
+    select! {
+        Token::Pub(t) => t.span(),
+    }
+    .or_not()
+    .map(|pub_span| match pub_span {
+        Some(_) => (Visibility::Public, pub_span),
+        None => (Visibility::Private, None),
+    })
+}
+
+// ============================================================================
+// Parameter Parser
+// ============================================================================
+
+/// Parse a parenthesised parameter list
+///
+/// Syntax:
+///   (<name>: <type>, ...)
+fn param_list<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Vec<Param<'src>>, ParseError<'src>> + Clone {
+    let param = select! {
+        Token::Identifier(t) => (t.name, t.span),
+    }
+    .then_ignore(select! { Token::Colon(_) => () })
+    .then(type_annotation())
+    .map(|((name, name_span), ty)| Param {
+        name,
+        name_span,
+        span: name_span | ty.span(),
+        ty,
+    })
+    .labelled("parameter");
+
+    param
+        .separated_by(select! { Token::Comma(_) => () })
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(
+            select! { Token::LeftParen(_) => () },
+            select! { Token::RightParen(_) => () },
+        )
+}
+
+// ============================================================================
+// Sketch Parser
+// ============================================================================
+
+/// Parse a `#[test]` attribute
+///
+/// `test` is the only attribute, so it is matched as an identifier rather
+/// than reserved as a keyword.
+///
+/// Syntax:
+///   #[test]
+fn test_attribute<'src>() -> impl Parser<'src, &'src [Token<'src>], (), ParseError<'src>> + Clone {
+    select! { Token::Hash(_) => () }
+        .ignore_then(select! { Token::LeftBracket(_) => () })
+        .ignore_then(select! { Token::Identifier(t) if t.name == "test" => () })
+        .ignore_then(select! { Token::RightBracket(_) => () })
+        .labelled("test attribute")
+}
+
+/// Parse a sketch block
+///
+/// Parametric sketches list their parameters like a function. Statements
+/// and local functions may be interleaved in any order, and keep that order.
+///
+/// Syntax:
+///   pub? sketch <name> { (<stmt> | #[test] <stmt> | <fn>)* }
+///   pub? sketch <name>(<param>: <type>, ...) { (<stmt> | #[test] <stmt> | <fn>)* }
+pub fn sketch_def<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], SketchDef<'src>, ParseError<'src>> + Clone {
+    let lbrace = select! { Token::LeftBrace(_) => () };
+    // Functions come first: statement recovery would otherwise skip a
+    // whole function definition looking for a `;`
+    let item = choice((
+        function_def(expr_parser.clone()).map(SketchItem::Function),
+        test_attribute()
+            .ignore_then(stmt(expr_parser.clone()))
+            .map(SketchItem::Test),
+        stmt(expr_parser).map(SketchItem::Stmt),
+    ));
+
+    // `sketch` is a contextual keyword: the spec uses it as a variable name
+    // (`let sketch: Sketch = ...`), so it is lexed as an identifier
+    visibility()
+        .then(select! {
+            Token::Identifier(t) if t.name == "sketch" => t.span,
+        })
+        .then(
+            select! {
+                Token::Identifier(t) => (t.name, t.span),
+            }
+            .labelled("sketch name"),
+        )
+        .then(param_list().or_not())
+        .then_ignore(lbrace)
+        .then(item.repeated().collect::<Vec<_>>())
+        .then(select! {
+            Token::RightBrace(t) => t.span(),
+        })
+        .map(
+            |(
+                (((((visibility, pub_span), sketch_span), (name, name_span)), parameters), items),
+                rbrace_span,
+            )| SketchDef {
+                name,
+                name_span,
+                parameters: parameters.unwrap_or_default(),
+                items,
+                visibility,
+                // Sketch span runs from `pub` or the sketch keyword to the closing brace
+                span: pub_span.unwrap_or(sketch_span) | rbrace_span,
+            },
+        )
+        .labelled("sketch definition")
+}
+
+// ============================================================================
+// Function Parser
+// ============================================================================
+
+/// Parse a function or method definition
+///
+/// Syntax:
+///   pub? fn <name>(<param>: <type>, ...) -> <type> { <stmt>* <expr>? }
+///   pub? fn <name>(<param>: <type>, ...) -> impl <trait> { <stmt>* <expr>? }
+///   pub? fn <name>(<param>: <type>, ...) { <stmt>* <expr>? }
+pub fn function_def<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], FunctionDef<'src>, ParseError<'src>> + Clone {
+    let return_type = select! { Token::Arrow(_) => () }
+        .ignore_then(return_type())
+        .or_not();
+
+    let body = select! { Token::LeftBrace(_) => () }
+        .ignore_then(stmt(expr_parser.clone()).repeated().collect::<Vec<_>>())
+        .then(expr_parser.or_not())
+        .then(select! { Token::RightBrace(t) => t.span() });
+
+    visibility()
+        .then(select! {
+            Token::Fn(t) => t.span(),
+        })
+        .then(
+            select! {
+                Token::Identifier(t) => (t.name, t.span),
+            }
+            .labelled("function name"),
+        )
+        .then(param_list())
+        .then(return_type)
+        .then(body)
+        .map(
+            |(
+                (((((visibility, pub_span), fn_span), (name, name_span)), params), return_type),
+                ((body, result), rbrace_span),
+            )| {
+                FunctionDef {
+                    name,
+                    name_span,
+                    params,
+                    return_type,
+                    body,
+                    result,
+                    visibility,
+                    // Function span runs from `pub` or the fn keyword to the closing brace
+                    span: pub_span.unwrap_or(fn_span) | rbrace_span,
+                }
+            },
+        )
+        .labelled("function definition")
+}
+
+// ============================================================================
+// Struct Parser
+// ============================================================================
+
+/// Parse a struct definition
+///
+/// Fields come first, separated by commas, followed by any methods.
+///
+/// Syntax:
+///   pub? struct <name> { pub? <field>: <type>, ... <fn>* }
+pub fn struct_def<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], StructDef<'src>, ParseError<'src>> + Clone {
+    let field = visibility()
+        .then(select! {
+            Token::Identifier(t) => (t.name, t.span),
+        })
+        .then_ignore(select! { Token::Colon(_) => () })
+        .then(type_annotation())
+        .map(
+            |(((visibility, pub_span), (name, name_span)), ty)| FieldDef {
+                name,
+                name_span,
+                visibility,
+                span: pub_span.unwrap_or(name_span) | ty.span(),
+                ty,
+            },
+        )
+        .labelled("field");
+
+    visibility()
+        .then(select! {
+            Token::Struct(t) => t.span(),
+        })
+        .then(
+            select! {
+                Token::Identifier(t) => (t.name, t.span),
+            }
+            .labelled("struct name"),
+        )
+        .then_ignore(select! { Token::LeftBrace(_) => () })
+        .then(
+            field
+                .separated_by(select! { Token::Comma(_) => () })
+                .allow_trailing()
+                .collect::<Vec<_>>(),
+        )
+        .then(function_def(expr_parser).repeated().collect::<Vec<_>>())
+        .then(select! {
+            Token::RightBrace(t) => t.span(),
+        })
+        .map(
+            |(
+                (((((visibility, pub_span), struct_span), (name, name_span)), fields), methods),
+                rbrace_span,
+            )| {
+                StructDef {
+                    name,
+                    name_span,
+                    fields,
+                    methods,
+                    visibility,
+                    // Struct span runs from `pub` or the struct keyword to the closing brace
+                    span: pub_span.unwrap_or(struct_span) | rbrace_span,
+                }
+            },
+        )
+        .labelled("struct definition")
+}
+
+// ============================================================================
+// Impl Parser
+// ============================================================================
+
+/// Parse an impl block adding methods to a struct
+///
+/// Syntax:
+///   impl <name> { <fn>* }
+pub fn impl_block<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], ImplBlock<'src>, ParseError<'src>> + Clone {
+    select! {
+        Token::Impl(t) => t.span(),
+    }
+    .then(
+        select! {
+            Token::Identifier(t) => (t.name, t.span),
+        }
+        .labelled("struct name"),
+    )
+    .then_ignore(select! { Token::LeftBrace(_) => () })
+    .then(function_def(expr_parser).repeated().collect::<Vec<_>>())
+    .then(select! {
+        Token::RightBrace(t) => t.span(),
+    })
+    .map(
+        |(((impl_span, (target, target_span)), methods), rbrace_span)| ImplBlock {
+            target,
+            target_span,
+            methods,
+            // Impl span runs from the impl keyword to the closing brace
+            span: impl_span | rbrace_span,
+        },
+    )
+    .labelled("impl block")
+}
+
+// ============================================================================
+// Program Parser
+// ============================================================================
+
+/// Parse a complete source file into a program
+///
+/// Imports must come before any other definition.
+///
+/// Syntax:
+///   <import>* (<sketch> | <struct> | <impl> | <fn>)*
+pub fn program<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Program<'src>, ParseError<'src>> + Clone {
+    let expr = expr_inner();
+    let definition = choice((
+        sketch_def(expr.clone()).map(Item::Sketch),
+        struct_def(expr.clone()).map(Item::Struct),
+        impl_block(expr.clone()).map(Item::Impl),
+        function_def(expr).map(Item::Function),
+    ));
+
+    import_decl()
+        .repeated()
+        .collect::<Vec<_>>()
+        .then(definition.repeated().collect::<Vec<_>>())
+        .then_ignore(end())
+        .map(|(imports, items)| Program {
+            imports,
+            items,
+            ..Program::default()
+        })
+}
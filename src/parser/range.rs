@@ -0,0 +1,44 @@
+//! Range expression parser
+//!
+//! This module contains the parser for ranges:
+//! - Range (..) - non-associative, below pipelines
+//!
+//! `0..5` is the integers from 0 up to, but not including, 5. Ranges are
+//! what `for` loops usually iterate over.
+
+use crate::ast::Expr;
+use crate::ast::HasSpan;
+use crate::lexer::Token;
+use chumsky::prelude::*;
+
+use super::ParseError;
+
+// ============================================================================
+// Range Parser
+// ============================================================================
+
+/// Parser for ranges (lower precedence than pipelines)
+///
+/// A range bound cannot itself be a range, so `a..b..c` does not parse.
+pub fn range_parser<'src, P>(
+    pipe: P,
+) -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone
+where
+    P: Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone,
+{
+    let range_op = select! { Token::DotDot(_) => () };
+
+    pipe.clone()
+        .then(range_op.ignore_then(pipe).or_not())
+        .map(|(start, end)| match end {
+            Some(end) => {
+                let span = start.span() | end.span();
+                Expr::Range {
+                    start: Box::new(start),
+                    end: Box::new(end),
+                    span,
+                }
+            }
+            None => start,
+        })
+}
@@ -1,49 +1,68 @@
 //! Statement and type annotation parsers
 
-use crate::ast::{Stmt, Type};
-use crate::lexer::Token;
-use crate::parser::ParseError;
+use crate::ast::{CompoundOp, Expr, HasSpan, Stmt, Type};
+use crate::lexer::{Token, TokenTrait};
+use crate::parser::{ParseError, condition_expr};
+use crate::span::Span;
 use chumsky::prelude::*;
 
 // ============================================================================
 // Type Annotation Parser
 // ============================================================================
 
-/// Parse type annotations (bool, i32, f64, Real, Algebraic)
+/// Parse type annotations (bool, i32, f64, Real, Algebraic, named types and references)
 pub fn type_annotation<'src>()
--> impl Parser<'src, &'src [Token<'src>], Type, ParseError<'src>> + Clone {
-    use crate::lexer::Span;
-
-    choice((
-        select! {
-            Token::BoolType(t) => Type::Bool {
-                span: Span { start: t.position, lines: 0, end_column: t.position.column + 4 }
+-> impl Parser<'src, &'src [Token<'src>], Type<'src>, ParseError<'src>> + Clone {
+    recursive(|type_annotation| {
+        choice((
+            select! {
+                Token::BoolType(t) => Type::Bool { span: t.span() },
             },
-        },
-        select! {
-            Token::I32Type(t) => Type::I32 {
-                span: Span { start: t.position, lines: 0, end_column: t.position.column + 3 }
+            select! {
+                Token::I32Type(t) => Type::I32 { span: t.span() },
             },
-        },
-        select! {
-            Token::F64Type(t) => Type::F64 {
-                span: Span { start: t.position, lines: 0, end_column: t.position.column + 3 }
+            select! {
+                Token::F64Type(t) => Type::F64 { span: t.span() },
             },
-        },
-        select! {
-            Token::RealType(t) => Type::Real {
-                span: Span { start: t.position, lines: 0, end_column: t.position.column + 4 }
+            select! {
+                Token::RealType(t) => Type::Real { span: t.span() },
             },
-        },
-        select! {
-            Token::AlgebraicType(t) => Type::Algebraic {
-                span: Span { start: t.position, lines: 0, end_column: t.position.column + 9 }
+            select! {
+                Token::AlgebraicType(t) => Type::Algebraic { span: t.span() },
             },
-        },
-    ))
+            select! {
+                Token::Identifier(t) => Type::Named { name: t.name, span: t.span },
+            },
+            // Reference type: &<type>
+            select! { Token::Ampersand(t) => t.span() }
+                .then(type_annotation)
+                .map(|(amp_span, inner): (_, Type<'src>)| Type::Ref {
+                    span: amp_span | inner.span(),
+                    inner: Box::new(inner),
+                }),
+        ))
+    })
     .labelled("type annotation")
 }
 
+/// Parse a function return type: a type annotation or `impl <Trait>`
+pub fn return_type<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Type<'src>, ParseError<'src>> + Clone {
+    select! { Token::Impl(t) => t.span() }
+        .then(
+            select! {
+                Token::Identifier(t) => (t.name, t.span),
+            }
+            .labelled("trait name"),
+        )
+        .map(|(impl_span, (trait_name, name_span))| Type::ImplTrait {
+            trait_name,
+            span: impl_span | name_span,
+        })
+        .or(type_annotation())
+        .labelled("return type")
+}
+
 // ============================================================================
 // Statement Parser
 // ============================================================================
@@ -59,13 +78,11 @@ pub fn let_stmt<'src>(
     expr_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
     + Clone,
 ) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
-    use crate::lexer::Span;
-
     let colon = select! { Token::Colon(_) => () };
     let equals = select! { Token::Equals(_) => () };
 
     select! {
-        Token::Let(t) => t.position,
+        Token::Let(t) => t.span(),
     }
     .then(
         select! {
@@ -82,26 +99,12 @@ pub fn let_stmt<'src>(
         equals.ignore_then(expr_parser).or_not(),
     )
     .then(select! {
-        Token::SemiColon(t) => t.position,
+        Token::SemiColon(t) => t.span(),
     })
     .map(
-        |((((let_pos, (name, name_span)), type_annotation), init), semi_pos)| {
-            // Construct span from let keyword to semicolon
-            let span = if let_pos.line == semi_pos.line {
-                // Same line
-                Span {
-                    start: let_pos,
-                    lines: 0,
-                    end_column: semi_pos.column + 1,
-                }
-            } else {
-                // Multiple lines
-                Span {
-                    start: let_pos,
-                    lines: semi_pos.line - let_pos.line,
-                    end_column: semi_pos.column + 1,
-                }
-            };
+        |((((let_span, (name, name_span)), type_annotation), init), semi_span)| {
+            // Statement span runs from the let keyword to the semicolon
+            let span = let_span | semi_span;
 
             Stmt::Let {
                 name,
@@ -114,3 +117,348 @@ pub fn let_stmt<'src>(
     )
     .labelled("let statement")
 }
+
+/// Parse an assert statement
+///
+/// Syntax:
+///   assert <expr>;
+///   assert <expr>, <message expr>;
+pub fn assert_stmt<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    select! {
+        Token::Assert(t) => t.span(),
+    }
+    .then(expr_parser.clone().labelled("condition"))
+    .then(
+        select! { Token::Comma(_) => () }
+            .ignore_then(expr_parser)
+            .or_not(),
+    )
+    .then(select! {
+        Token::SemiColon(t) => t.span(),
+    })
+    .map(
+        |(((assert_span, condition), message), semi_span)| Stmt::Assert {
+            condition,
+            message,
+            span: assert_span | semi_span,
+        },
+    )
+    .labelled("assert statement")
+}
+
+/// Parse a place that can be assigned to: a variable or a chain of field
+/// accesses on one, e.g. `self.center.x`
+///
+/// Syntax:
+///   <name>(.<field>)*
+fn place<'src>() -> impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone {
+    let base = select! {
+        Token::Identifier(t) => Expr::Var { name: t.name, span: t.span },
+        Token::SelfKw(t) => Expr::Var { name: "self", span: t.span() },
+    };
+    let field = select! { Token::Dot(_) => () }.ignore_then(select! {
+        Token::Identifier(t) => (t.name, t.span),
+    });
+
+    base.foldl(field.repeated(), |receiver, (field, field_span)| {
+        let span = receiver.span() | field_span;
+        Expr::FieldAccess {
+            receiver: Box::new(receiver),
+            field,
+            span,
+        }
+    })
+    .labelled("assignment target")
+}
+
+/// Parse a compound assignment
+///
+/// Only a variable or field can be assigned to, so the target is a place
+/// rather than any expression.
+///
+/// Syntax:
+///   <place> (+= | -= | *= | /= | %= | ^=) <expr>;
+pub fn compound_assign_stmt<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], Expr<'src>, ParseError<'src>> + Clone,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    let op = select! {
+        Token::PlusAssign(_) => CompoundOp::Add,
+        Token::MinusAssign(_) => CompoundOp::Sub,
+        Token::MulAssign(_) => CompoundOp::Mul,
+        Token::DivAssign(_) => CompoundOp::Div,
+        Token::ModAssign(_) => CompoundOp::Mod,
+        Token::PowAssign(_) => CompoundOp::Pow,
+    }
+    .labelled("assignment operator");
+
+    place()
+        .then(op)
+        .then(expr_parser.labelled("value"))
+        .then(select! {
+            Token::SemiColon(t) => t.span(),
+        })
+        .map(|(((target, op), value), semi_span)| Stmt::CompoundAssign {
+            span: target.span() | semi_span,
+            target,
+            op,
+            value,
+        })
+        .labelled("compound assignment")
+}
+
+/// Parse an if statement, with `condition_parser` for its conditions and
+/// `stmt_parser` for the statements in its branches
+///
+/// An `else if` chain is parsed as an else body holding a single nested If.
+///
+/// Syntax:
+///   if <expr> { <stmt>* }
+///   if <expr> { <stmt>* } else { <stmt>* }
+///   if <expr> { <stmt>* } else if <expr> { <stmt>* } ...
+pub fn if_stmt<'src>(
+    condition_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone
+    + 'src,
+    stmt_parser: impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    let body = select! { Token::LeftBrace(_) => () }
+        .ignore_then(stmt_parser.repeated().collect::<Vec<_>>())
+        .then(select! { Token::RightBrace(t) => t.span() });
+
+    recursive(|if_stmt| {
+        select! {
+            Token::If(t) => t.span(),
+        }
+        .then(condition_parser.labelled("condition"))
+        .then(body.clone())
+        .then(
+            select! { Token::Else(_) => () }
+                .ignore_then(choice((
+                    body,
+                    if_stmt.map(|nested: Stmt<'src>| {
+                        let span = nested.span();
+                        (vec![nested], span)
+                    }),
+                )))
+                .or_not(),
+        )
+        .map(
+            |(((if_span, condition), (then_body, then_span)), else_part)| {
+                // Statement span runs from the if keyword to the last closing brace
+                let end_span = else_part.as_ref().map_or(then_span, |(_, span)| *span);
+
+                Stmt::If {
+                    condition,
+                    then_body,
+                    else_body: else_part.map(|(body, _)| body),
+                    span: if_span | end_span,
+                }
+            },
+        )
+        .labelled("if statement")
+    })
+}
+
+/// Parse the label in front of a loop, returning its name and the span up
+/// to the colon
+///
+/// Syntax:
+///   '<name>:
+fn loop_label<'src>()
+-> impl Parser<'src, &'src [Token<'src>], (&'src str, Span), ParseError<'src>> + Clone {
+    select! {
+        Token::Label(t) => (t.name, t.span),
+    }
+    .then(select! { Token::Colon(t) => t.span() })
+    .map(|((name, label_span), colon_span)| (name, label_span | colon_span))
+}
+
+/// Parse a for loop, with `iterable_parser` for the value it iterates over
+/// and `stmt_parser` for the statements in its body
+///
+/// Syntax:
+///   ['<label>:] for <name> in <expr> { <stmt>* }
+pub fn for_stmt<'src>(
+    iterable_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone,
+    stmt_parser: impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    loop_label()
+        .or_not()
+        .then(select! {
+            Token::For(t) => t.span(),
+        })
+        .then(
+            select! {
+                Token::Identifier(t) => (t.name, t.span),
+            }
+            .labelled("loop variable"),
+        )
+        .then_ignore(select! { Token::In(_) => () })
+        .then(iterable_parser.labelled("iterable"))
+        .then_ignore(select! { Token::LeftBrace(_) => () })
+        .then(stmt_parser.repeated().collect::<Vec<_>>())
+        .then(select! { Token::RightBrace(t) => t.span() })
+        .map(
+            |(((((label, for_span), (var, var_span)), iterable), body), rbrace_span)| Stmt::For {
+                label: label.map(|(name, _)| name),
+                var,
+                var_span,
+                iterable,
+                body,
+                span: label.map_or(for_span, |(_, label_span)| label_span) | rbrace_span,
+            },
+        )
+        .labelled("for loop")
+}
+
+/// Parse a while loop, with `condition_parser` for its condition and
+/// `stmt_parser` for the statements in its body
+///
+/// Syntax:
+///   ['<label>:] while <expr> { <stmt>* }
+pub fn while_stmt<'src>(
+    condition_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone,
+    stmt_parser: impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    loop_label()
+        .or_not()
+        .then(select! {
+            Token::While(t) => t.span(),
+        })
+        .then(condition_parser.labelled("condition"))
+        .then_ignore(select! { Token::LeftBrace(_) => () })
+        .then(stmt_parser.repeated().collect::<Vec<_>>())
+        .then(select! { Token::RightBrace(t) => t.span() })
+        .map(
+            |((((label, while_span), condition), body), rbrace_span)| Stmt::While {
+                label: label.map(|(name, _)| name),
+                condition,
+                body,
+                span: label.map_or(while_span, |(_, label_span)| label_span) | rbrace_span,
+            },
+        )
+        .labelled("while loop")
+}
+
+/// Parse a with block, with `context_parser` for the value it applies and
+/// `stmt_parser` for the statements in its body
+///
+/// Syntax:
+///   with <expr> { <stmt>* }
+pub fn with_stmt<'src>(
+    context_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone,
+    stmt_parser: impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    select! {
+        Token::With(t) => t.span(),
+    }
+    .then(context_parser.labelled("context"))
+    .then_ignore(select! { Token::LeftBrace(_) => () })
+    .then(stmt_parser.repeated().collect::<Vec<_>>())
+    .then(select! { Token::RightBrace(t) => t.span() })
+    .map(|(((with_span, context), body), rbrace_span)| Stmt::With {
+        context,
+        body,
+        span: with_span | rbrace_span,
+    })
+    .labelled("with block")
+}
+
+/// Parse a break or continue statement
+///
+/// Syntax:
+///   break ['<label>];
+///   continue ['<label>];
+pub fn loop_control_stmt<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    let label_and_semicolon = select! { Token::Label(t) => t.name }
+        .or_not()
+        .then(select! {
+            Token::SemiColon(t) => t.span(),
+        });
+    let break_stmt = select! { Token::Break(t) => t.span() }
+        .then(label_and_semicolon)
+        .map(|(break_span, (label, semi_span))| Stmt::Break {
+            label,
+            span: break_span | semi_span,
+        });
+    let continue_stmt = select! { Token::Continue(t) => t.span() }
+        .then(label_and_semicolon)
+        .map(|(continue_span, (label, semi_span))| Stmt::Continue {
+            label,
+            span: continue_span | semi_span,
+        });
+    break_stmt
+        .or(continue_stmt)
+        .labelled("loop control statement")
+}
+
+/// Recovery for a statement that failed to parse
+///
+/// Skips to the next `;`, stepping over nested `{ ... }` blocks, and yields
+/// a `Stmt::Error` covering the skipped tokens. The `}` closing the
+/// enclosing block is never consumed, so the rest of that block still
+/// parses.
+fn skip_statement<'src>()
+-> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    let block = recursive(|block| {
+        let inner = any()
+            .filter(|t: &Token| !matches!(t, Token::LeftBrace(_) | Token::RightBrace(_)))
+            .ignored();
+
+        select! { Token::LeftBrace(t) => t.span() }
+            .then_ignore(choice((block.ignored(), inner)).repeated())
+            .then(select! { Token::RightBrace(t) => t.span() })
+            .map(|(open_span, close_span)| open_span | close_span)
+    });
+    let token = any()
+        .filter(|t: &Token| {
+            !matches!(
+                t,
+                Token::LeftBrace(_) | Token::RightBrace(_) | Token::SemiColon(_)
+            )
+        })
+        .map(|t: Token<'src>| t.span());
+
+    choice((block, token))
+        .repeated()
+        .collect::<Vec<_>>()
+        .then(select! {
+            Token::SemiColon(t) => t.span(),
+        })
+        .map(|(skipped, semi_span)| Stmt::Error {
+            span: skipped
+                .first()
+                .map_or(semi_span, |first| *first | semi_span),
+        })
+}
+
+/// Parse any statement
+///
+/// A statement that fails to parse is skipped up to its `;` and becomes a
+/// `Stmt::Error`, so the statements after it are still parsed.
+pub fn stmt<'src>(
+    expr_parser: impl Parser<'src, &'src [Token<'src>], crate::ast::Expr<'src>, ParseError<'src>>
+    + Clone
+    + 'src,
+) -> impl Parser<'src, &'src [Token<'src>], Stmt<'src>, ParseError<'src>> + Clone {
+    recursive(|stmt| {
+        choice((
+            let_stmt(expr_parser.clone()),
+            assert_stmt(expr_parser.clone()),
+            if_stmt(condition_expr(expr_parser.clone()), stmt.clone()),
+            for_stmt(condition_expr(expr_parser.clone()), stmt.clone()),
+            while_stmt(condition_expr(expr_parser.clone()), stmt.clone()),
+            with_stmt(condition_expr(expr_parser.clone()), stmt),
+            loop_control_stmt(),
+            compound_assign_stmt(expr_parser),
+        ))
+        .recover_with(via_parser(skip_statement()))
+    })
+}
@@ -1,7 +1,9 @@
 use super::*;
-use crate::ast::{Stmt, Type};
+use crate::ast::{CompoundOp, Item, Pattern, SketchDef, Stmt, StructDef, Type, Visibility};
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
 use crate::lexer;
 use crate::parser::stmt::type_annotation;
+use crate::span::LineColumn;
 use assert_matches::assert_matches;
 use std::time::Duration;
 
@@ -17,7 +19,10 @@ fn parse_with_timeout<T: Send + 'static>(
     timeout: Duration,
 ) -> Result<T, String> {
     // First tokenize the input - since input is 'static, tokens will be too
-    let tokens = lexer::tokenize(input).map_err(|e| format!("Lexer error: {}", e))?;
+    let Ok((tokens, errors)) = lexer::tokenize(input);
+    if let Some(error) = errors.first() {
+        return Err(format!("Lexer error: {}", error));
+    }
 
     // Make tokens static by leaking (only for tests)
     let tokens_static: &'static [Token<'static>] = Box::leak(tokens.into_boxed_slice());
@@ -71,7 +76,7 @@ fn test_bool_lit_true() {
         |input| atoms::bool_lit().parse(input).into_result(),
         Duration::from_secs(1),
     );
-    assert_eq!(result.unwrap(), true);
+    assert!(result.unwrap());
 }
 
 #[test]
@@ -81,7 +86,7 @@ fn test_bool_lit_false() {
         |input| atoms::bool_lit().parse(input).into_result(),
         Duration::from_secs(1),
     );
-    assert_eq!(result.unwrap(), false);
+    assert!(!result.unwrap());
 }
 
 #[test]
@@ -478,6 +483,198 @@ fn test_expr_mixed_eq_neq() {
     }
 }
 
+#[test]
+fn test_expr_simple_lt() {
+    // Test: a < b
+    let result = parse_with_timeout(
+        "a < b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Lt { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, CmpLhs::Var { name: "a", .. }));
+            assert!(matches!(*rhs, CmpRhs::Var { name: "b", .. }));
+        }
+        other => panic!("Expected Expr::Lt, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_simple_gt() {
+    // Test: a > b
+    let result = parse_with_timeout(
+        "a > b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Gt { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, CmpLhs::Var { name: "a", .. }));
+            assert!(matches!(*rhs, CmpRhs::Var { name: "b", .. }));
+        }
+        other => panic!("Expected Expr::Gt, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_simple_lt_eq() {
+    // Test: a <= b
+    let result = parse_with_timeout(
+        "a <= b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::LtEq { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, CmpLhs::Var { name: "a", .. }));
+            assert!(matches!(*rhs, CmpRhs::Var { name: "b", .. }));
+        }
+        other => panic!("Expected Expr::LtEq, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_simple_gt_eq() {
+    // Test: a >= b
+    let result = parse_with_timeout(
+        "a >= b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::GtEq { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, CmpLhs::Var { name: "a", .. }));
+            assert!(matches!(*rhs, CmpRhs::Var { name: "b", .. }));
+        }
+        other => panic!("Expected Expr::GtEq, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_lt_left_associative() {
+    // Test: a < b < c should be (a < b) < c
+    let result = parse_with_timeout(
+        "a < b < c",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Lt { lhs, rhs, .. } => {
+            match *lhs {
+                CmpLhs::Lt {
+                    lhs: ref inner_lhs,
+                    rhs: ref inner_rhs,
+                    ..
+                } => {
+                    assert!(matches!(**inner_lhs, CmpLhs::Var { name: "a", .. }));
+                    assert!(matches!(**inner_rhs, CmpRhs::Var { name: "b", .. }));
+                }
+                ref other => panic!("Expected CmpLhs::Lt, got {:?}", other),
+            }
+            assert!(matches!(*rhs, CmpRhs::Var { name: "c", .. }));
+        }
+        other => panic!("Expected Expr::Lt, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_ordering_with_addition() {
+    // Test: 1 + 2 >= 3 should be (1 + 2) >= 3
+    let result = parse_with_timeout(
+        "1 + 2 >= 3",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::GtEq { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, CmpLhs::Add { .. }));
+            assert!(matches!(*rhs, CmpRhs::IntLit { value: 3, .. }));
+        }
+        other => panic!("Expected Expr::GtEq, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_mixed_ordering_and_neq() {
+    // Test: a <= b != c > d should be ((a <= b) != c) > d
+    let result = parse_with_timeout(
+        "a <= b != c > d",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Gt { lhs, .. } => match *lhs {
+            CmpLhs::NotEq { ref lhs, .. } => {
+                assert!(matches!(**lhs, CmpLhs::LtEq { .. }));
+            }
+            ref other => panic!("Expected CmpLhs::NotEq, got {:?}", other),
+        },
+        other => panic!("Expected Expr::Gt, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_ordering_with_logical() {
+    // Test: a < b and c > d should be (a < b) and (c > d)
+    let result = parse_with_timeout(
+        "a < b and c > d",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(result.unwrap().to_string(), "((a < b) and ((c > d)))");
+}
+
+#[test]
+fn test_expr_in() {
+    let result = parse_with_timeout(
+        "p in points",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::In { lhs, rhs, .. } => {
+            assert_matches!(*lhs, CmpLhs::Var { name: "p", .. });
+            assert_matches!(*rhs, CmpRhs::Var { name: "points", .. });
+        }
+        other => panic!("Expected Expr::In, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_not_in_with_arithmetic_and_logical() {
+    // x + 1 not in [1, 2] and ok should be ((x + 1) not in [1, 2]) and ok
+    let result = parse_with_timeout(
+        "x + 1 not in [1, 2] and ok",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(
+        result.unwrap().to_string(),
+        "(((x + 1) not in [1, 2]) and (ok))"
+    );
+}
+
+#[test]
+fn test_error_not_without_in() {
+    let result = parse_with_timeout(
+        "a not b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert!(result.is_err(), "`not` is only valid before `in`");
+}
+
 // ========================================================================
 // Power Operator Tests
 // ========================================================================
@@ -1454,6 +1651,240 @@ fn test_expr_logical_left_associative() {
     }
 }
 
+// ========================================================================
+// parse_expression Tests
+// ========================================================================
+
+#[test]
+fn test_parse_expression() {
+    let Ok((tokens, _)) = lexer::tokenize("(1 + 2) * x");
+    let (expr, errors) = parse_expression(&tokens);
+
+    assert!(errors.is_empty());
+    assert_eq!(expr.unwrap().to_string(), "(((1 + 2)) * x)");
+}
+
+#[test]
+fn test_parse_expression_digit_separators() {
+    let Ok((tokens, _)) = lexer::tokenize("1_000");
+    let (expr, _) = parse_expression(&tokens);
+
+    assert_matches!(expr, Some(Expr::IntLit { value: 1000, .. }));
+}
+
+#[test]
+fn test_parse_expression_reports_diagnostics() {
+    let Ok((tokens, _)) = lexer::tokenize("1 + + 2");
+    let (expr, errors) = parse_expression(&tokens);
+
+    assert!(expr.is_none());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code(), "parse-error");
+    assert_eq!(errors[0].message(), "unexpected '+'");
+}
+
+#[test]
+fn test_parse_expression_rejects_trailing_tokens() {
+    let Ok((tokens, _)) = lexer::tokenize("1 + 2;");
+    let (_, errors) = parse_expression(&tokens);
+
+    assert_eq!(errors.len(), 1);
+}
+
+// ========================================================================
+// Pipe Operator Tests
+// ========================================================================
+
+#[test]
+fn test_expr_pipe_to_function_name() {
+    let result = parse_with_timeout(
+        "shape |> fillet",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Pipe { lhs, rhs_fn, .. } => {
+            assert_matches!(*lhs, Expr::Var { name: "shape", .. });
+            assert_matches!(*rhs_fn, Atom::Var { name: "fillet", .. });
+        }
+        other => panic!("Expected Expr::Pipe, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_pipe_to_call_is_left_associative() {
+    let result = parse_with_timeout(
+        "base |> fillet(2) |> translate(0, 10)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Pipe { lhs, rhs_fn, .. } => {
+            assert_matches!(*lhs, Expr::Pipe { .. });
+            assert_matches!(*rhs_fn, Atom::Call { name: "translate", ref args, .. } if args.len() == 2);
+        }
+        other => panic!("Expected Expr::Pipe, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_pipe_has_lowest_precedence() {
+    let result = parse_with_timeout(
+        "a + 1 > b and c |> f",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(
+        result.unwrap().to_string(),
+        "((((a + 1) > b) and (c)) |> f)"
+    );
+}
+
+#[test]
+fn test_expr_pipe_in_parentheses() {
+    let result = parse_with_timeout(
+        "(x |> f) * 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(result.unwrap().to_string(), "(((x |> f)) * 2)");
+}
+
+#[test]
+fn test_error_pipe_to_non_function() {
+    let result = parse_with_timeout(
+        "x |> 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert!(result.is_err(), "Should fail when piping into a literal");
+}
+
+#[test]
+fn test_expr_bitwise_is_between_comparison_and_logical() {
+    let result = parse_with_timeout(
+        "a | b == c and x << 2 & mask",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(
+        result.unwrap().to_string(),
+        "((a | ((b == c))) and (((x << (2)) & (mask))))"
+    );
+}
+
+#[test]
+fn test_expr_shift_binds_tighter_than_bitwise_and_or() {
+    for (source, expected) in [
+        ("a | b << 2", "(a | ((b << (2))))"),
+        ("mask & x << 2", "(mask & ((x << (2))))"),
+        ("x >> 4 | y", "((x >> (4)) | (y))"),
+        ("x << 1 >> 2 & m", "(((x << (1)) >> (2)) & (m))"),
+    ] {
+        let result = parse_with_timeout(
+            source,
+            |input| expr().parse(input).into_result(),
+            Duration::from_secs(2),
+        );
+        assert_eq!(result.unwrap().to_string(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_expr_bitwise_and_binds_tighter_than_or() {
+    for (source, expected) in [
+        ("a | b & c", "(a | ((b & (c))))"),
+        ("a & b | c & d", "((a & (b)) | ((c & (d))))"),
+        ("flags | mask & x << 2", "(flags | ((mask & ((x << (2))))))"),
+        ("a | b | c & d", "((a | (b)) | ((c & (d))))"),
+        ("(a | b) & c", "(((a | (b))) & (c))"),
+    ] {
+        let result = parse_with_timeout(
+            source,
+            |input| expr().parse(input).into_result(),
+            Duration::from_secs(2),
+        );
+        assert_eq!(result.unwrap().to_string(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_expr_mixed_bitwise_structure() {
+    // a | b & c << 2: OR at the root, AND on its right, the shift inside that
+    let result = parse_with_timeout(
+        "a | b & c << 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let Expr::BitOr { lhs, rhs, .. } = result.unwrap() else {
+        panic!("Expected Expr::BitOr at the root");
+    };
+    assert_matches!(*lhs, CmpLhs::Var { name: "a", .. });
+    let CmpRhs::Paren { inner, .. } = *rhs else {
+        panic!("Expected the rhs of | in a Paren");
+    };
+    let Expr::BitAnd { lhs, rhs, .. } = *inner else {
+        panic!("Expected Expr::BitAnd on the right of |");
+    };
+    assert_matches!(*lhs, CmpLhs::Var { name: "b", .. });
+    assert_matches!(*rhs, CmpRhs::Paren { ref inner, .. } if matches!(**inner, Expr::Shl { .. }));
+}
+
+#[test]
+fn test_expr_bitwise_not_binds_tighter_than_power() {
+    let result = parse_with_timeout(
+        "~x ^ 2 + ~-y",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(result.unwrap().to_string(), "(((~x) ^ 2) + (~(-y)))");
+}
+
+#[test]
+fn test_expr_binary_and_after_reference() {
+    let result = parse_with_timeout(
+        "&a & &b",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::BitAnd { lhs, rhs, .. } => {
+            assert_matches!(*lhs, CmpLhs::Ref { .. });
+            assert_matches!(*rhs, CmpRhs::Paren { ref inner, .. } if matches!(**inner, Expr::Ref { .. }));
+        }
+        other => panic!("Expected Expr::BitAnd, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_expr_range_is_below_pipelines() {
+    let result = parse_with_timeout(
+        "0..n + 1 |> f",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert_eq!(result.unwrap().to_string(), "(0..((n + 1) |> f))");
+}
+
+#[test]
+fn test_error_range_is_not_associative() {
+    let result = parse_with_timeout(
+        "a..b..c",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert!(result.is_err(), "Should fail on a chained range");
+}
+
 // ========================================================================
 
 #[test]
@@ -1465,7 +1896,7 @@ fn test_ariadne_error_reporting_example() {
     use super::report_parse_errors;
 
     let source = "1 + + 2";
-    let tokens = lexer::tokenize(source).unwrap();
+    let Ok((tokens, _)) = lexer::tokenize(source);
 
     // Parse and capture errors
     match expr().parse(&tokens).into_result() {
@@ -1532,16 +1963,44 @@ fn test_type_algebraic() {
     assert!(matches!(result.unwrap(), Type::Algebraic { .. }));
 }
 
-// ========================================================================
-// Let Statement Tests
-// ========================================================================
-
 #[test]
-fn test_let_with_type_and_init() {
-    // let x: i32 = 42;
+fn test_type_annotation_named() {
     let result = parse_with_timeout(
-        "let x: i32 = 42;",
-        |input| let_stmt(expr_inner()).parse(input).into_result(),
+        "Point",
+        |input| type_annotation().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert_matches!(result.unwrap(), Type::Named { name: "Point", .. });
+}
+
+#[test]
+fn test_type_annotation_reference() {
+    let result = parse_with_timeout(
+        "&Point",
+        |input| type_annotation().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Type::Ref { inner, span } => {
+            assert_matches!(*inner, Type::Named { name: "Point", .. });
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 7);
+        }
+        other => panic!("Expected Type::Ref, got {:?}", other),
+    }
+}
+
+// ========================================================================
+// Let Statement Tests
+// ========================================================================
+
+#[test]
+fn test_let_with_type_and_init() {
+    // let x: i32 = 42;
+    let result = parse_with_timeout(
+        "let x: i32 = 42;",
+        |input| let_stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
@@ -1556,6 +2015,7 @@ fn test_let_with_type_and_init() {
             assert!(matches!(type_annotation, Some(Type::I32 { .. })));
             assert!(matches!(init, Some(Expr::IntLit { value: 42, .. })));
         }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
@@ -1579,14 +2039,15 @@ fn test_let_with_type_only() {
             assert!(matches!(type_annotation, Some(Type::Bool { .. })));
             assert!(init.is_none());
         }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
 #[test]
 fn test_let_with_init_only() {
-    // let z = 3.14;
+    // let z = 2.5;
     let result = parse_with_timeout(
-        "let z = 3.14;",
+        "let z = 2.5;",
         |input| let_stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
@@ -1600,8 +2061,26 @@ fn test_let_with_init_only() {
         } => {
             assert_eq!(name, "z");
             assert!(type_annotation.is_none());
-            assert!(matches!(init, Some(Expr::FloatLit { value, .. }) if value == 3.14));
+            assert!(matches!(init, Some(Expr::FloatLit { value, .. }) if value == 2.5));
+        }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_let_with_string_literal() {
+    let result = parse_with_timeout(
+        r#"let label = "side \"A\"";"#,
+        |input| let_stmt(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Stmt::Let { name, init, .. } => {
+            assert_eq!(name, "label");
+            assert_matches!(init, Some(Expr::StrLit { ref value, span }) if value == "side \"A\"" && span.end_column == 25);
         }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
@@ -1625,6 +2104,7 @@ fn test_let_no_type_no_init() {
             assert!(type_annotation.is_none());
             assert!(init.is_none());
         }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
@@ -1664,977 +2144,2518 @@ fn test_let_with_expression() {
                 other => panic!("Expected Some(Expr::Add), got {:?}", other),
             }
         }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
 // ========================================================================
-// Span Tracking Tests
+// Assert Statement Tests
 // ========================================================================
 
 #[test]
-fn test_span_simple_int_literal() {
-    // Test: 42
+fn test_assert_without_message() {
     let result = parse_with_timeout(
-        "42",
-        |input| expr().parse(input).into_result(),
+        "assert length >= 2 * diameter;",
+        |input| assert_stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 3); // "42" is 2 chars, end_column is exclusive
+    match result.unwrap() {
+        Stmt::Assert {
+            condition,
+            message,
+            span,
+        } => {
+            assert_matches!(condition, Expr::GtEq { .. });
+            assert!(message.is_none());
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 31);
+        }
+        other => panic!("Expected Stmt::Assert, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_simple_var() {
-    // Test: foo
+fn test_assert_with_message() {
     let result = parse_with_timeout(
-        "foo",
-        |input| expr().parse(input).into_result(),
+        "assert width > 0, width;",
+        |input| assert_stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 4); // "foo" is 3 chars
+    match result.unwrap() {
+        Stmt::Assert {
+            condition, message, ..
+        } => {
+            assert_matches!(condition, Expr::Gt { .. });
+            assert_matches!(message, Some(Expr::Var { name: "width", .. }));
+        }
+        other => panic!("Expected Stmt::Assert, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_binary_addition() {
-    // Test: 1 + 2
+fn test_assert_missing_semicolon() {
     let result = parse_with_timeout(
-        "1 + 2",
-        |input| expr().parse(input).into_result(),
+        "assert x",
+        |input| assert_stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    // Span should cover from "1" to "2"
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 6); // "1 + 2" covers columns 1-5
+    assert!(result.is_err());
 }
 
 #[test]
-fn test_span_nested_expression() {
-    // Test: 1 + 2 * 3
+fn test_assert_in_sketch_and_function_bodies() {
     let result = parse_with_timeout(
-        "1 + 2 * 3",
-        |input| expr().parse(input).into_result(),
+        "sketch Bolt(d: f64, l: f64) {\n    assert l >= 2 * d;\n    fn check(x: f64) { assert x > 0; }\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    // Span should cover the entire expression
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 10);
+    let sketch = result.unwrap();
+    assert_matches!(sketch.items[0], SketchItem::Stmt(Stmt::Assert { .. }));
+    assert_matches!(&sketch.items[1], SketchItem::Function(function) if matches!(function.body[0], Stmt::Assert { .. }));
 }
 
+// ========================================================================
+// If Tests
+// ========================================================================
+
 #[test]
-fn test_span_parenthesized() {
-    // Test: (1 + 2)
+fn test_if_statement_without_else() {
     let result = parse_with_timeout(
-        "(1 + 2)",
-        |input| expr().parse(input).into_result(),
+        "if width > 10 { assert height > 2; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    // Span should include the parentheses
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1); // Start at '('
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 8); // End after ')'
+    match result.unwrap() {
+        Stmt::If {
+            condition,
+            then_body,
+            else_body,
+            span,
+        } => {
+            assert_matches!(condition, Expr::Gt { .. });
+            assert_matches!(then_body[..], [Stmt::Assert { .. }]);
+            assert!(else_body.is_none());
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 37);
+        }
+        other => panic!("Expected Stmt::If, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_unary_negation() {
-    // Test: -42
+fn test_if_statement_else_if_chain() {
     let result = parse_with_timeout(
-        "-42",
-        |input| expr().parse(input).into_result(),
+        "if a { let x = 1; } else if b { let x = 2; } else { let x = 3; let y = 4; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    // Span should start at '-' and end after '42'
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 4);
+    let Stmt::If {
+        else_body, span, ..
+    } = result.unwrap()
+    else {
+        panic!("Expected Stmt::If");
+    };
+    assert_eq!(span.end_column, 76);
+
+    // The else if is a single nested If holding the final else
+    let else_body = else_body.unwrap();
+    let [
+        Stmt::If {
+            condition,
+            else_body: Some(last),
+            ..
+        },
+    ] = &else_body[..]
+    else {
+        panic!("Expected a nested If, got {:?}", else_body);
+    };
+    assert_matches!(condition, Expr::Var { name: "b", .. });
+    assert_eq!(last.len(), 2);
 }
 
 #[test]
-fn test_span_comparison() {
-    // Test: 1 == 2
+fn test_if_statement_nested_and_empty() {
     let result = parse_with_timeout(
-        "1 == 2",
-        |input| expr().parse(input).into_result(),
+        "if ready { if a > 1 {} } else {}",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 7);
+    match result.unwrap() {
+        Stmt::If {
+            then_body,
+            else_body,
+            ..
+        } => {
+            assert_matches!(then_body[..], [Stmt::If { .. }]);
+            assert_eq!(else_body, Some(vec![]));
+        }
+        other => panic!("Expected Stmt::If, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_logical_and() {
-    // Test: true and false
+fn test_if_statement_bare_name_condition() {
     let result = parse_with_timeout(
-        "true and false",
-        |input| expr().parse(input).into_result(),
+        "if ready {}",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 15);
+    match result.unwrap() {
+        Stmt::If {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            assert_matches!(condition, Expr::Var { name: "ready", .. });
+            assert!(then_body.is_empty());
+            assert!(else_body.is_none());
+        }
+        other => panic!("Expected Stmt::If, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_power_operator() {
-    // Test: 2 ^ 3
+fn test_if_statement_bare_name_condition_with_else() {
     let result = parse_with_timeout(
-        "2 ^ 3",
-        |input| expr().parse(input).into_result(),
+        "if ready {} else {}",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    let expr = result.unwrap();
-    let span = expr.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 6);
+    match result.unwrap() {
+        Stmt::If {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            assert_matches!(condition, Expr::Var { name: "ready", .. });
+            assert!(then_body.is_empty());
+            assert_eq!(else_body, Some(vec![]));
+        }
+        other => panic!("Expected Stmt::If, got {:?}", other),
+    }
 }
 
 #[test]
-fn test_span_let_statement() {
-    // Test: let x = 42;
+fn test_if_condition_struct_literal_in_parentheses() {
     let result = parse_with_timeout(
-        "let x = 42;",
-        |input| let_stmt(expr_inner()).parse(input).into_result(),
+        "if origin == (Point { x: 0, y: 0 }) { assert ready; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Stmt::Let {
-            name_span, span, ..
+        Stmt::If {
+            condition: Expr::Eq { rhs, .. },
+            then_body,
+            ..
         } => {
-            // name_span should point to "x"
-            assert_eq!(name_span.start.line, 1);
-            assert_eq!(name_span.start.column, 5); // "x" starts at column 5
-
-            // Overall span should cover entire statement
-            assert_eq!(span.start.line, 1);
-            assert_eq!(span.start.column, 1); // Starts at "let"
-            assert_eq!(span.lines, 0);
-            assert_eq!(span.end_column, 12); // Ends after ';'
+            assert_eq!(rhs.to_string(), "(Point { x: 0, y: 0 })");
+            assert_matches!(then_body[..], [Stmt::Assert { .. }]);
         }
+        other => panic!("Expected Stmt::If comparing with a struct, got {:?}", other),
     }
 }
 
 #[test]
-fn test_span_type_annotation() {
-    // Test: i32
+fn test_if_expression_bare_name_condition() {
     let result = parse_with_timeout(
-        "i32",
-        |input| type_annotation().parse(input).into_result(),
-        Duration::from_secs(1),
+        "if ready { 1 } else { 2 }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
     );
 
-    let type_ann = result.unwrap();
-    let span = type_ann.span();
-
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 4); // "i32" is 3 chars
+    assert_eq!(result.unwrap().to_string(), "(if ready { 1 } else { 2 })");
 }
 
 #[test]
-fn test_span_complex_nested() {
-    // Test: (1 + 2) * 3
+fn test_if_expression() {
     let result = parse_with_timeout(
-        "(1 + 2) * 3",
+        "if x > 0 { length } else { -length }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     let expr = result.unwrap();
-    let span = expr.span();
-
-    // Should span from '(' to '3'
-    assert_eq!(span.start.line, 1);
-    assert_eq!(span.start.column, 1);
-    assert_eq!(span.lines, 0);
-    assert_eq!(span.end_column, 12);
+    assert_eq!(
+        expr.to_string(),
+        "(if (x > 0) { length } else { (-length) })"
+    );
+    assert_eq!(expr.span().end_column, 37);
 }
 
 #[test]
-fn test_hasspan_trait_for_different_types() {
-    // Test that HasSpan trait works for various AST node types
-    use crate::ast::HasSpan;
-
-    let expr_result = parse_with_timeout(
-        "42",
+fn test_if_expression_as_operand() {
+    let result = parse_with_timeout(
+        "2 * if a { 1 } else if b { 2 } else { 3 }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
-    let expr = expr_result.unwrap();
-    let _expr_span = expr.span(); // Uses HasSpan trait
-
-    let type_result = parse_with_timeout(
-        "bool",
-        |input| type_annotation().parse(input).into_result(),
-        Duration::from_secs(1),
-    );
-    let type_ann = type_result.unwrap();
-    let _type_span = type_ann.span(); // Uses HasSpan trait
 
-    // If we get here without panic, HasSpan works for all types
-    assert!(true);
+    match result.unwrap() {
+        Expr::Mul { rhs, .. } => match *rhs {
+            MulRhs::If { else_branch, .. } => {
+                assert_matches!(*else_branch, Expr::If { .. });
+            }
+            other => panic!("Expected MulRhs::If, got {:?}", other),
+        },
+        other => panic!("Expected Expr::Mul, got {:?}", other),
+    }
 }
 
-// ========================================================================
-// Function Call Tests
-// ========================================================================
-
 #[test]
-fn test_function_call_no_args() {
-    // Test: foo()
+fn test_if_expression_requires_else() {
     let result = parse_with_timeout(
-        "foo()",
+        "if a { 1 }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::Call { name, args, .. } => {
-            assert_eq!(name, "foo");
-            assert_eq!(args.len(), 0);
-        }
-        other => panic!("Expected Expr::Call, got {:?}", other),
-    }
+    assert!(result.is_err());
 }
 
 #[test]
-fn test_function_call_one_arg() {
-    // Test: foo(42)
+fn test_if_in_function_body() {
     let result = parse_with_timeout(
-        "foo(42)",
-        |input| expr().parse(input).into_result(),
+        "fn sign(x: f64) -> f64 { if x < 0 { assert x != 0; } if x < 0 { -1 } else { 1 } }",
+        |input| function_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::Call { name, args, .. } => {
-            assert_eq!(name, "foo");
-            assert_eq!(args.len(), 1);
-            assert!(matches!(args[0], Expr::IntLit { value: 42, .. }));
-        }
-        other => panic!("Expected Expr::Call, got {:?}", other),
-    }
+    let function = result.unwrap();
+    assert_matches!(function.body[..], [Stmt::If { .. }]);
+    assert_matches!(function.result, Some(Expr::If { .. }));
 }
 
-#[test]
-fn test_function_call_multiple_args() {
-    // Test: add(1, 2, 3)
-    let result = parse_with_timeout(
-        "add(1, 2, 3)",
-        |input| expr().parse(input).into_result(),
-        Duration::from_secs(2),
-    );
-
-    match result.unwrap() {
-        Expr::Call { name, args, .. } => {
-            assert_eq!(name, "add");
-            assert_eq!(args.len(), 3);
-            assert!(matches!(args[0], Expr::IntLit { value: 1, .. }));
-            assert!(matches!(args[1], Expr::IntLit { value: 2, .. }));
-            assert!(matches!(args[2], Expr::IntLit { value: 3, .. }));
-        }
-        other => panic!("Expected Expr::Call, got {:?}", other),
-    }
-}
+// ========================================================================
+// Match Tests
+// ========================================================================
 
 #[test]
-fn test_function_call_expr_args() {
-    // Test: foo(1 + 2, 3 * 4)
+fn test_match_expression_patterns() {
     let result = parse_with_timeout(
-        "foo(1 + 2, 3 * 4)",
+        "match shape { Circle { r } => r * r, Rect { w, h: 0 } => 0, 1 => one, other => 2, _ => 3 }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::Call { name, args, .. } => {
-            assert_eq!(name, "foo");
-            assert_eq!(args.len(), 2);
-            assert!(matches!(args[0], Expr::Add { .. }));
-            assert!(matches!(args[1], Expr::Mul { .. }));
+    let Expr::Match {
+        scrutinee,
+        arms,
+        span,
+    } = result.unwrap()
+    else {
+        panic!("Expected Expr::Match");
+    };
+    assert_matches!(*scrutinee, Expr::Var { name: "shape", .. });
+    assert_eq!(span.end_column, 91);
+
+    let patterns: Vec<_> = arms.iter().map(|arm| &arm.pattern).collect();
+    let [circle, rect, one, other, wildcard] = patterns[..] else {
+        panic!("Expected five arms, got {:?}", arms);
+    };
+    // A field without a pattern binds its own name
+    let Pattern::Struct { name, fields, .. } = circle else {
+        panic!("Expected a struct pattern, got {:?}", circle);
+    };
+    assert_eq!(*name, "Circle");
+    assert_matches!(fields[..], [("r", Pattern::Binding { name: "r", .. })]);
+    let Pattern::Struct { fields, .. } = rect else {
+        panic!("Expected a struct pattern, got {:?}", rect);
+    };
+    assert_matches!(
+        fields[..],
+        [
+            ("w", Pattern::Binding { name: "w", .. }),
+            (
+                "h",
+                Pattern::Literal {
+                    value: Atom::IntLit { value: 0, .. }
+                }
+            )
+        ]
+    );
+    assert_matches!(
+        one,
+        Pattern::Literal {
+            value: Atom::IntLit { value: 1, .. }
         }
-        other => panic!("Expected Expr::Call, got {:?}", other),
-    }
+    );
+    assert_matches!(other, Pattern::Binding { name: "other", .. });
+    assert_matches!(wildcard, Pattern::Wildcard { .. });
 }
 
 #[test]
-fn test_function_call_nested() {
-    // Test: foo(bar(42))
+fn test_match_expression_as_operand() {
     let result = parse_with_timeout(
-        "foo(bar(42))",
+        "2 * match n { 0 => zero, _ => n, }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::Call { name, args, .. } => {
-            assert_eq!(name, "foo");
-            assert_eq!(args.len(), 1);
-            match &args[0] {
-                Expr::Call {
-                    name: inner_name,
-                    args: inner_args,
-                    ..
-                } => {
-                    assert_eq!(*inner_name, "bar");
-                    assert_eq!(inner_args.len(), 1);
-                    assert!(matches!(inner_args[0], Expr::IntLit { value: 42, .. }));
-                }
-                other => panic!("Expected inner Expr::Call, got {:?}", other),
-            }
-        }
-        other => panic!("Expected Expr::Call, got {:?}", other),
-    }
+    assert_eq!(
+        result.unwrap().to_string(),
+        "(2 * (match n { 0 => zero, _ => n }))"
+    );
 }
 
 #[test]
-fn test_function_call_in_expression() {
-    // Test: foo(1) + bar(2)
+fn test_error_match_arm_without_arrow() {
     let result = parse_with_timeout(
-        "foo(1) + bar(2)",
+        "match n { 0 zero }",
         |input| expr().parse(input).into_result(),
         Duration::from_secs(2),
     );
-
-    match result.unwrap() {
-        Expr::Add { lhs, rhs, .. } => {
-            assert!(matches!(*lhs, AddLhs::Call { .. }));
-            assert!(matches!(*rhs, AddRhs::Call { .. }));
-        }
-        other => panic!("Expected Expr::Add, got {:?}", other),
-    }
+    assert!(result.is_err(), "Should fail without '=>'");
 }
 
 // ========================================================================
-// Method Call Tests
+// For Tests
 // ========================================================================
 
 #[test]
-fn test_method_call_no_args() {
-    // Test: obj.method()
+fn test_for_statement_over_range() {
     let result = parse_with_timeout(
-        "obj.method()",
-        |input| expr().parse(input).into_result(),
+        "for i in 0..5 { assert i < 5; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
+        Stmt::For {
+            var,
+            iterable,
+            body,
+            span,
             ..
         } => {
-            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
-            assert_eq!(method, "method");
-            assert_eq!(args.len(), 0);
+            assert_eq!(var, "i");
+            assert_matches!(iterable, Expr::Range { .. });
+            assert_matches!(body[..], [Stmt::Assert { .. }]);
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 32);
         }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+        other => panic!("Expected Stmt::For, got {:?}", other),
     }
 }
 
 #[test]
-fn test_method_call_one_arg() {
-    // Test: obj.method(42)
+fn test_for_statement_iterable_is_not_a_struct_literal() {
     let result = parse_with_timeout(
-        "obj.method(42)",
-        |input| expr().parse(input).into_result(),
+        "for p in points {}",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
-        } => {
-            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
-            assert_eq!(method, "method");
-            assert_eq!(args.len(), 1);
-            assert!(matches!(args[0], Expr::IntLit { value: 42, .. }));
+        Stmt::For { iterable, body, .. } => {
+            assert_matches!(iterable, Expr::Var { name: "points", .. });
+            assert!(body.is_empty());
         }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+        other => panic!("Expected Stmt::For, got {:?}", other),
     }
 }
 
+// ========================================================================
+// While Tests
+// ========================================================================
+
 #[test]
-fn test_method_call_multiple_args() {
-    // Test: obj.method(1, 2, 3)
+fn test_while_statement() {
     let result = parse_with_timeout(
-        "obj.method(1, 2, 3)",
-        |input| expr().parse(input).into_result(),
+        "while dist > tol { assert dist > 0; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
+        Stmt::While {
+            label: None,
+            condition,
+            body,
+            span,
         } => {
-            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
-            assert_eq!(method, "method");
-            assert_eq!(args.len(), 3);
-            assert!(matches!(args[0], Expr::IntLit { value: 1, .. }));
-            assert!(matches!(args[1], Expr::IntLit { value: 2, .. }));
-            assert!(matches!(args[2], Expr::IntLit { value: 3, .. }));
+            assert_matches!(condition, Expr::Gt { .. });
+            assert_matches!(body[..], [Stmt::Assert { .. }]);
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 38);
         }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+        other => panic!("Expected Stmt::While, got {:?}", other),
     }
 }
 
 #[test]
-fn test_method_call_chaining() {
-    // Test: obj.method1().method2()
+fn test_while_statement_condition_is_not_a_struct_literal() {
     let result = parse_with_timeout(
-        "obj.method1().method2()",
-        |input| expr().parse(input).into_result(),
+        "while running { for i in 0..n {} }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
+        Stmt::While {
+            condition, body, ..
         } => {
-            assert_eq!(method, "method2");
-            assert_eq!(args.len(), 0);
-            match *receiver {
-                Expr::MethodCall {
-                    receiver: inner_receiver,
-                    method: inner_method,
-                    args: inner_args,
+            assert_matches!(
+                condition,
+                Expr::Var {
+                    name: "running",
                     ..
-                } => {
-                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
-                    assert_eq!(inner_method, "method1");
-                    assert_eq!(inner_args.len(), 0);
                 }
-                other => panic!("Expected inner Expr::MethodCall, got {:?}", other),
-            }
+            );
+            assert_matches!(body[..], [Stmt::For { .. }]);
         }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+        other => panic!("Expected Stmt::While, got {:?}", other),
     }
 }
 
 #[test]
-fn test_method_call_on_function_call() {
-    // Test: foo().bar()
+fn test_break_and_continue_in_loop_body() {
     let result = parse_with_timeout(
-        "foo().bar()",
-        |input| expr().parse(input).into_result(),
+        "for i in 0..5 { if i > 3 { break; } continue; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
-        } => {
-            assert_eq!(method, "bar");
-            assert_eq!(args.len(), 0);
-            assert!(matches!(*receiver, Expr::Call { name, .. } if name == "foo"));
-        }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
-    }
+    let Stmt::For { body, .. } = result.unwrap() else {
+        panic!("Expected Stmt::For");
+    };
+    let [
+        Stmt::If { then_body, .. },
+        Stmt::Continue { label: None, span },
+    ] = &body[..]
+    else {
+        panic!("Expected an if and a continue, got {:?}", body);
+    };
+    assert_matches!(then_body[..], [Stmt::Break { .. }]);
+    assert_eq!(span.start.column, 37);
+    assert_eq!(span.end_column, 46);
 }
 
 #[test]
-fn test_method_call_with_expr_args() {
-    // Test: obj.method(1 + 2, 3 * 4)
+fn test_labelled_loops() {
     let result = parse_with_timeout(
-        "obj.method(1 + 2, 3 * 4)",
-        |input| expr().parse(input).into_result(),
+        "'rows: for r in rows { 'cells: while true { continue 'rows; break; } }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
-        } => {
-            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
-            assert_eq!(method, "method");
-            assert_eq!(args.len(), 2);
-            assert!(matches!(args[0], Expr::Add { .. }));
-            assert!(matches!(args[1], Expr::Mul { .. }));
-        }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
-    }
+    let Stmt::For {
+        label, body, span, ..
+    } = result.unwrap()
+    else {
+        panic!("Expected Stmt::For");
+    };
+    assert_eq!(label, Some("rows"));
+    // The loop's span starts at its label
+    assert_eq!(span.start.column, 1);
+
+    let [Stmt::While { label, body, .. }] = &body[..] else {
+        panic!("Expected a labelled while, got {:?}", body);
+    };
+    assert_eq!(*label, Some("cells"));
+    assert_matches!(
+        body[..],
+        [
+            Stmt::Continue {
+                label: Some("rows"),
+                ..
+            },
+            Stmt::Break { label: None, .. }
+        ]
+    );
 }
 
 #[test]
-fn test_method_call_in_expression() {
-    // Test: obj.method(1) + 2
+fn test_error_label_on_non_loop() {
     let result = parse_with_timeout(
-        "obj.method(1) + 2",
-        |input| expr().parse(input).into_result(),
+        "'outer: if a {}",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
+    assert!(result.is_err(), "Should fail on a labelled if");
+}
 
-    match result.unwrap() {
-        Expr::Add { lhs, rhs, .. } => {
-            assert!(matches!(*lhs, AddLhs::MethodCall { .. }));
-            assert!(matches!(*rhs, AddRhs::IntLit { value: 2, .. }));
-        }
-        other => panic!("Expected Expr::Add, got {:?}", other),
-    }
+#[test]
+fn test_error_break_without_semicolon() {
+    let result = parse_with_timeout(
+        "break",
+        |input| stmt(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert!(result.is_err(), "Should fail without a semicolon");
 }
 
+// ========================================================================
+// With Tests
+// ========================================================================
+
 #[test]
-fn test_field_access() {
-    // Test: obj.field
+fn test_with_block() {
     let result = parse_with_timeout(
-        "obj.field",
-        |input| expr().parse(input).into_result(),
+        "with shift { with inner {} let p: Point = line.start; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::FieldAccess {
-            receiver, field, ..
+        Stmt::With {
+            context,
+            body,
+            span,
         } => {
-            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
-            assert_eq!(field, "field");
+            // The context is not taken for the start of a struct literal
+            assert_matches!(context, Expr::Var { name: "shift", .. });
+            assert_matches!(body[..], [Stmt::With { .. }, Stmt::Let { .. }]);
+            assert_eq!(span.end_column, 56);
         }
-        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+        other => panic!("Expected Stmt::With, got {:?}", other),
     }
 }
 
+// ========================================================================
+// Compound Assignment Tests
+// ========================================================================
+
 #[test]
-fn test_field_access_chaining() {
-    // Test: obj.field1.field2
+fn test_compound_assignment_to_field() {
     let result = parse_with_timeout(
-        "obj.field1.field2",
-        |input| expr().parse(input).into_result(),
+        "p.x *= scale + 1;",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
     match result.unwrap() {
-        Expr::FieldAccess {
-            receiver, field, ..
+        Stmt::CompoundAssign {
+            target,
+            op,
+            value,
+            span,
         } => {
-            assert_eq!(field, "field2");
-            match *receiver {
-                Expr::FieldAccess {
-                    receiver: inner_receiver,
-                    field: inner_field,
-                    ..
-                } => {
-                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
-                    assert_eq!(inner_field, "field1");
-                }
-                other => panic!("Expected inner Expr::FieldAccess, got {:?}", other),
-            }
+            assert_matches!(target, Expr::FieldAccess { field: "x", .. });
+            assert_eq!(op, CompoundOp::Mul);
+            assert_matches!(value, Expr::Add { .. });
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 18);
         }
-        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+        other => panic!("Expected Stmt::CompoundAssign, got {:?}", other),
     }
 }
 
 #[test]
-fn test_field_access_on_function_call() {
-    // Test: foo().field
+fn test_compound_assignment_operators() {
     let result = parse_with_timeout(
-        "foo().field",
-        |input| expr().parse(input).into_result(),
+        "while n > 0 { n -= 1; t /= 2; r %= 3; e ^= 2; s += n; }",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::FieldAccess {
-            receiver, field, ..
-        } => {
-            assert_eq!(field, "field");
-            assert!(matches!(*receiver, Expr::Call { name, .. } if name == "foo"));
-        }
-        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
-    }
+    let Stmt::While { body, .. } = result.unwrap() else {
+        panic!("Expected Stmt::While");
+    };
+    let ops: Vec<_> = body
+        .iter()
+        .map(|stmt| match stmt {
+            Stmt::CompoundAssign { op, .. } => *op,
+            other => panic!("Expected Stmt::CompoundAssign, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            CompoundOp::Sub,
+            CompoundOp::Div,
+            CompoundOp::Mod,
+            CompoundOp::Pow,
+            CompoundOp::Add
+        ]
+    );
 }
 
 #[test]
-fn test_field_access_then_method_call() {
-    // Test: obj.field.method()
+fn test_compound_assignment_to_nested_field_of_self() {
     let result = parse_with_timeout(
-        "obj.field.method()",
-        |input| expr().parse(input).into_result(),
+        "self.center.x += 1;",
+        |input| stmt(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::MethodCall {
-            receiver,
-            method,
-            args,
-            ..
+    let Stmt::CompoundAssign { target, .. } = result.unwrap() else {
+        panic!("Expected Stmt::CompoundAssign");
+    };
+    assert_eq!(target.to_string(), "self.center.x");
+}
+
+#[test]
+fn test_error_compound_assignment_to_non_place() {
+    for source in [
+        "1 + 2 += 3;",
+        "f() -= x;",
+        "p.len() *= 2;",
+        "(a) += 1;",
+        "-a += 1;",
+    ] {
+        let result = parse_with_timeout(
+            source,
+            |input| stmt(expr_inner()).parse(input).into_result(),
+            Duration::from_secs(2),
+        );
+        assert!(result.is_err(), "Should reject {:?}", source);
+    }
+}
+
+#[test]
+fn test_error_expression_without_assignment() {
+    let result = parse_with_timeout(
+        "width + 1;",
+        |input| stmt(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    assert!(
+        result.is_err(),
+        "Should fail on an expression that is not assigned"
+    );
+}
+
+// ========================================================================
+// Span Tracking Tests
+// ========================================================================
+
+#[test]
+fn test_span_simple_int_literal() {
+    // Test: 42
+    let result = parse_with_timeout(
+        "42",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 3); // "42" is 2 chars, end_column is exclusive
+}
+
+#[test]
+fn test_span_simple_var() {
+    // Test: foo
+    let result = parse_with_timeout(
+        "foo",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 4); // "foo" is 3 chars
+}
+
+#[test]
+fn test_span_binary_addition() {
+    // Test: 1 + 2
+    let result = parse_with_timeout(
+        "1 + 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    // Span should cover from "1" to "2"
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 6); // "1 + 2" covers columns 1-5
+}
+
+#[test]
+fn test_span_nested_expression() {
+    // Test: 1 + 2 * 3
+    let result = parse_with_timeout(
+        "1 + 2 * 3",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    // Span should cover the entire expression
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 10);
+}
+
+#[test]
+fn test_span_parenthesized() {
+    // Test: (1 + 2)
+    let result = parse_with_timeout(
+        "(1 + 2)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    // Span should include the parentheses
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1); // Start at '('
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 8); // End after ')'
+}
+
+#[test]
+fn test_span_unary_negation() {
+    // Test: -42
+    let result = parse_with_timeout(
+        "-42",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    // Span should start at '-' and end after '42'
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 4);
+}
+
+#[test]
+fn test_span_comparison() {
+    // Test: 1 == 2
+    let result = parse_with_timeout(
+        "1 == 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 7);
+}
+
+#[test]
+fn test_span_logical_and() {
+    // Test: true and false
+    let result = parse_with_timeout(
+        "true and false",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 15);
+}
+
+#[test]
+fn test_span_power_operator() {
+    // Test: 2 ^ 3
+    let result = parse_with_timeout(
+        "2 ^ 3",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 6);
+}
+
+#[test]
+fn test_span_let_statement() {
+    // Test: let x = 42;
+    let result = parse_with_timeout(
+        "let x = 42;",
+        |input| let_stmt(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Stmt::Let {
+            name_span, span, ..
         } => {
-            assert_eq!(method, "method");
-            assert_eq!(args.len(), 0);
-            match *receiver {
-                Expr::FieldAccess {
-                    receiver: inner_receiver,
-                    field,
-                    ..
-                } => {
-                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
-                    assert_eq!(field, "field");
-                }
-                other => panic!("Expected Expr::FieldAccess, got {:?}", other),
-            }
+            // name_span should point to "x"
+            assert_eq!(name_span.start.line, 1);
+            assert_eq!(name_span.start.column, 5); // "x" starts at column 5
+
+            // Overall span should cover entire statement
+            assert_eq!(span.start.line, 1);
+            assert_eq!(span.start.column, 1); // Starts at "let"
+            assert_eq!(span.lines, 0);
+            assert_eq!(span.end_column, 12); // Ends after ';'
         }
-        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+        other => panic!("Expected Stmt::Let, got {:?}", other),
     }
 }
 
 #[test]
-fn test_method_call_then_field_access() {
-    // Test: obj.method().field
+fn test_span_type_annotation() {
+    // Test: i32
+    let result = parse_with_timeout(
+        "i32",
+        |input| type_annotation().parse(input).into_result(),
+        Duration::from_secs(1),
+    );
+
+    let type_ann = result.unwrap();
+    let span = type_ann.span();
+
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 4); // "i32" is 3 chars
+}
+
+#[test]
+fn test_span_complex_nested() {
+    // Test: (1 + 2) * 3
+    let result = parse_with_timeout(
+        "(1 + 2) * 3",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let expr = result.unwrap();
+    let span = expr.span();
+
+    // Should span from '(' to '3'
+    assert_eq!(span.start.line, 1);
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 12);
+}
+
+#[test]
+fn test_span_function_call() {
+    // Test: foo(1, 2)
+    let result = parse_with_timeout(
+        "foo(1, 2)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let span = result.unwrap().span();
+
+    // Should span from 'foo' to ')'
+    assert_eq!(span.start.column, 1);
+    assert_eq!(span.lines, 0);
+    assert_eq!(span.end_column, 10);
+}
+
+#[test]
+fn test_span_array_literal() {
+    // Test: [1, 2, 3]
+    let result = parse_with_timeout(
+        "x + [1, 2, 3]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Add { rhs, .. } => {
+            let span = rhs.span();
+            assert_eq!(span.start.column, 5); // Starts at '['
+            assert_eq!(span.lines, 0);
+            assert_eq!(span.end_column, 14); // Ends after ']'
+        }
+        other => panic!("Expected Expr::Add, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_span_method_call_chain() {
+    // Test: a.b.c(1)
+    let result = parse_with_timeout(
+        "a.b.c(1)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall { receiver, span, .. } => {
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.end_column, 9);
+            // Receiver 'a.b' ends after 'b'
+            assert_eq!(receiver.span().end_column, 4);
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_span_multiline_let_statement() {
+    // Test: let statement spanning three lines
+    let result = parse_with_timeout(
+        "let x =\n  1 +\n  2;",
+        |input| let_stmt(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Stmt::Let { init, span, .. } => {
+            assert_eq!(span.start.line, 1);
+            assert_eq!(span.start.column, 1);
+            assert_eq!(span.lines, 2);
+            assert_eq!(span.end_column, 5); // Ends after ';' on line 3
+
+            let init_span = init.unwrap().span();
+            assert_eq!(init_span.start.line, 2);
+            assert_eq!(init_span.lines, 1);
+            assert_eq!(init_span.end_column, 4);
+        }
+        other => panic!("Expected Stmt::Let, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_hasspan_trait_for_different_types() {
+    // Test that HasSpan trait works for various AST node types
+    use crate::ast::HasSpan;
+
+    let expr_result = parse_with_timeout(
+        "42",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+    let expr = expr_result.unwrap();
+    let _expr_span = expr.span(); // Uses HasSpan trait
+
+    let type_result = parse_with_timeout(
+        "bool",
+        |input| type_annotation().parse(input).into_result(),
+        Duration::from_secs(1),
+    );
+    let type_ann = type_result.unwrap();
+    let _type_span = type_ann.span(); // Uses HasSpan trait
+}
+
+// ========================================================================
+// Function Call Tests
+// ========================================================================
+
+#[test]
+fn test_function_call_no_args() {
+    // Test: foo()
+    let result = parse_with_timeout(
+        "foo()",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Call { name, args, .. } => {
+            assert_eq!(name, "foo");
+            assert_eq!(args.len(), 0);
+        }
+        other => panic!("Expected Expr::Call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_call_one_arg() {
+    // Test: foo(42)
+    let result = parse_with_timeout(
+        "foo(42)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Call { name, args, .. } => {
+            assert_eq!(name, "foo");
+            assert_eq!(args.len(), 1);
+            assert!(matches!(args[0], Expr::IntLit { value: 42, .. }));
+        }
+        other => panic!("Expected Expr::Call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_call_multiple_args() {
+    // Test: add(1, 2, 3)
+    let result = parse_with_timeout(
+        "add(1, 2, 3)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Call { name, args, .. } => {
+            assert_eq!(name, "add");
+            assert_eq!(args.len(), 3);
+            assert!(matches!(args[0], Expr::IntLit { value: 1, .. }));
+            assert!(matches!(args[1], Expr::IntLit { value: 2, .. }));
+            assert!(matches!(args[2], Expr::IntLit { value: 3, .. }));
+        }
+        other => panic!("Expected Expr::Call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_call_expr_args() {
+    // Test: foo(1 + 2, 3 * 4)
+    let result = parse_with_timeout(
+        "foo(1 + 2, 3 * 4)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Call { name, args, .. } => {
+            assert_eq!(name, "foo");
+            assert_eq!(args.len(), 2);
+            assert!(matches!(args[0], Expr::Add { .. }));
+            assert!(matches!(args[1], Expr::Mul { .. }));
+        }
+        other => panic!("Expected Expr::Call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_call_nested() {
+    // Test: foo(bar(42))
+    let result = parse_with_timeout(
+        "foo(bar(42))",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Call { name, args, .. } => {
+            assert_eq!(name, "foo");
+            assert_eq!(args.len(), 1);
+            match &args[0] {
+                Expr::Call {
+                    name: inner_name,
+                    args: inner_args,
+                    ..
+                } => {
+                    assert_eq!(*inner_name, "bar");
+                    assert_eq!(inner_args.len(), 1);
+                    assert!(matches!(inner_args[0], Expr::IntLit { value: 42, .. }));
+                }
+                other => panic!("Expected inner Expr::Call, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Expr::Call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_function_call_in_expression() {
+    // Test: foo(1) + bar(2)
+    let result = parse_with_timeout(
+        "foo(1) + bar(2)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Add { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, AddLhs::Call { .. }));
+            assert!(matches!(*rhs, AddRhs::Call { .. }));
+        }
+        other => panic!("Expected Expr::Add, got {:?}", other),
+    }
+}
+
+// ========================================================================
+// Method Call Tests
+// ========================================================================
+
+#[test]
+fn test_method_call_no_args() {
+    // Test: obj.method()
+    let result = parse_with_timeout(
+        "obj.method()",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
+            assert_eq!(method, "method");
+            assert_eq!(args.len(), 0);
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_one_arg() {
+    // Test: obj.method(42)
+    let result = parse_with_timeout(
+        "obj.method(42)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
+            assert_eq!(method, "method");
+            assert_eq!(args.len(), 1);
+            assert!(matches!(args[0], Expr::IntLit { value: 42, .. }));
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_multiple_args() {
+    // Test: obj.method(1, 2, 3)
+    let result = parse_with_timeout(
+        "obj.method(1, 2, 3)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
+            assert_eq!(method, "method");
+            assert_eq!(args.len(), 3);
+            assert!(matches!(args[0], Expr::IntLit { value: 1, .. }));
+            assert!(matches!(args[1], Expr::IntLit { value: 2, .. }));
+            assert!(matches!(args[2], Expr::IntLit { value: 3, .. }));
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_chaining() {
+    // Test: obj.method1().method2()
+    let result = parse_with_timeout(
+        "obj.method1().method2()",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert_eq!(method, "method2");
+            assert_eq!(args.len(), 0);
+            match *receiver {
+                Expr::MethodCall {
+                    receiver: inner_receiver,
+                    method: inner_method,
+                    args: inner_args,
+                    ..
+                } => {
+                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
+                    assert_eq!(inner_method, "method1");
+                    assert_eq!(inner_args.len(), 0);
+                }
+                other => panic!("Expected inner Expr::MethodCall, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_on_function_call() {
+    // Test: foo().bar()
+    let result = parse_with_timeout(
+        "foo().bar()",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert_eq!(method, "bar");
+            assert_eq!(args.len(), 0);
+            assert!(matches!(*receiver, Expr::Call { name, .. } if name == "foo"));
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_with_expr_args() {
+    // Test: obj.method(1 + 2, 3 * 4)
+    let result = parse_with_timeout(
+        "obj.method(1 + 2, 3 * 4)",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
+            assert_eq!(method, "method");
+            assert_eq!(args.len(), 2);
+            assert!(matches!(args[0], Expr::Add { .. }));
+            assert!(matches!(args[1], Expr::Mul { .. }));
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_in_expression() {
+    // Test: obj.method(1) + 2
+    let result = parse_with_timeout(
+        "obj.method(1) + 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Add { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, AddLhs::MethodCall { .. }));
+            assert!(matches!(*rhs, AddRhs::IntLit { value: 2, .. }));
+        }
+        other => panic!("Expected Expr::Add, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_access() {
+    // Test: obj.field
+    let result = parse_with_timeout(
+        "obj.field",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => {
+            assert!(matches!(*receiver, Expr::Var { name, .. } if name == "obj"));
+            assert_eq!(field, "field");
+        }
+        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_access_chaining() {
+    // Test: obj.field1.field2
+    let result = parse_with_timeout(
+        "obj.field1.field2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => {
+            assert_eq!(field, "field2");
+            match *receiver {
+                Expr::FieldAccess {
+                    receiver: inner_receiver,
+                    field: inner_field,
+                    ..
+                } => {
+                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
+                    assert_eq!(inner_field, "field1");
+                }
+                other => panic!("Expected inner Expr::FieldAccess, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_access_on_function_call() {
+    // Test: foo().field
+    let result = parse_with_timeout(
+        "foo().field",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => {
+            assert_eq!(field, "field");
+            assert!(matches!(*receiver, Expr::Call { name, .. } if name == "foo"));
+        }
+        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_access_then_method_call() {
+    // Test: obj.field.method()
+    let result = parse_with_timeout(
+        "obj.field.method()",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::MethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        } => {
+            assert_eq!(method, "method");
+            assert_eq!(args.len(), 0);
+            match *receiver {
+                Expr::FieldAccess {
+                    receiver: inner_receiver,
+                    field,
+                    ..
+                } => {
+                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
+                    assert_eq!(field, "field");
+                }
+                other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Expr::MethodCall, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_method_call_then_field_access() {
+    // Test: obj.method().field
+    let result = parse_with_timeout(
+        "obj.method().field",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::FieldAccess {
+            receiver, field, ..
+        } => {
+            assert_eq!(field, "field");
+            match *receiver {
+                Expr::MethodCall {
+                    receiver: inner_receiver,
+                    method,
+                    args,
+                    ..
+                } => {
+                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
+                    assert_eq!(method, "method");
+                    assert_eq!(args.len(), 0);
+                }
+                other => panic!("Expected Expr::MethodCall, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_access_in_expression() {
+    // Test: obj.field + 2
+    let result = parse_with_timeout(
+        "obj.field + 2",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::Add { lhs, rhs, .. } => {
+            assert!(matches!(*lhs, AddLhs::FieldAccess { .. }));
+            assert!(matches!(*rhs, AddRhs::IntLit { value: 2, .. }));
+        }
+        other => panic!("Expected Expr::Add, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// Array Literal Tests
+// ============================================================================
+
+#[test]
+fn test_empty_array_literal() {
+    let result = parse_with_timeout(
+        "[]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 0);
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_literal_single_element() {
+    let result = parse_with_timeout(
+        "[42]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 1);
+            assert_matches!(elements[0], Expr::IntLit { value: 42, .. });
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_literal_multiple_elements() {
+    let result = parse_with_timeout(
+        "[1, 2, 3]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 3);
+            assert_matches!(elements[0], Expr::IntLit { value: 1, .. });
+            assert_matches!(elements[1], Expr::IntLit { value: 2, .. });
+            assert_matches!(elements[2], Expr::IntLit { value: 3, .. });
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_literal_with_expressions() {
+    let result = parse_with_timeout(
+        "[1 + 2, 3 * 4]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 2);
+            assert_matches!(elements[0], Expr::Add { .. });
+            assert_matches!(elements[1], Expr::Mul { .. });
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_literal_trailing_comma() {
+    let result = parse_with_timeout(
+        "[1, 2, 3,]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 3);
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_nested_array_literal() {
+    let result = parse_with_timeout(
+        "[[1, 2], [3, 4]]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 2);
+            assert_matches!(elements[0], Expr::ArrayLit { .. });
+            assert_matches!(elements[1], Expr::ArrayLit { .. });
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// Struct Literal Tests
+// ============================================================================
+
+#[test]
+fn test_empty_struct_literal() {
+    let result = parse_with_timeout(
+        "Point {}",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Point");
+            assert_eq!(fields.len(), 0);
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literal_single_field() {
+    let result = parse_with_timeout(
+        "Point { x: 10 }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Point");
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].0, "x");
+            assert_matches!(fields[0].1, Expr::IntLit { value: 10, .. });
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literal_multiple_fields() {
+    let result = parse_with_timeout(
+        "Point { x: 10, y: 20 }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Point");
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[0].0, "x");
+            assert_eq!(fields[1].0, "y");
+            assert_matches!(fields[0].1, Expr::IntLit { value: 10, .. });
+            assert_matches!(fields[1].1, Expr::IntLit { value: 20, .. });
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literal_with_expressions() {
+    let result = parse_with_timeout(
+        "Circle { center: point(0, 0), radius: 5 * 2 }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Circle");
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[0].0, "center");
+            assert_eq!(fields[1].0, "radius");
+            assert_matches!(fields[0].1, Expr::Call { .. });
+            assert_matches!(fields[1].1, Expr::Mul { .. });
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_struct_literal_trailing_comma() {
+    let result = parse_with_timeout(
+        "Point { x: 10, y: 20, }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Point");
+            assert_eq!(fields.len(), 2);
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_nested_struct_literal() {
+    let result = parse_with_timeout(
+        "Line { start: Point { x: 0, y: 0 }, end: Point { x: 10, y: 10 } }",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::StructLit { name, fields, .. } => {
+            assert_eq!(name, "Line");
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[0].0, "start");
+            assert_eq!(fields[1].0, "end");
+            assert_matches!(fields[0].1, Expr::StructLit { .. });
+            assert_matches!(fields[1].1, Expr::StructLit { .. });
+        }
+        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_of_struct_literals() {
+    let result = parse_with_timeout(
+        "[Point { x: 0, y: 0 }, Point { x: 10, y: 10 }]",
+        |input| expr().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    match result.unwrap() {
+        Expr::ArrayLit { elements, .. } => {
+            assert_eq!(elements.len(), 2);
+            assert_matches!(elements[0], Expr::StructLit { .. });
+            assert_matches!(elements[1], Expr::StructLit { .. });
+        }
+        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    }
+}
+
+// ========================================================================
+// Sketch Definition Tests
+// ========================================================================
+
+#[test]
+fn test_empty_sketch() {
+    let result = parse_with_timeout(
+        "sketch Empty {}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_eq!(sketch.name, "Empty");
+    assert!(sketch.items.is_empty());
+}
+
+#[test]
+fn test_sketch_with_statements() {
+    let result = parse_with_timeout(
+        "sketch Bracket {\n    let width: f64 = 10.0;\n    let height = width * 2;\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_eq!(sketch.name, "Bracket");
+    assert_matches!(
+        sketch.items[..],
+        [
+            SketchItem::Stmt(Stmt::Let { name: "width", .. }),
+            SketchItem::Stmt(Stmt::Let { name: "height", .. })
+        ]
+    );
+}
+
+#[test]
+fn test_sketch_span() {
+    let result = parse_with_timeout(
+        "sketch A {\n  let x = 1;\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_eq!(sketch.name_span.start.column, 8);
+    assert_eq!(sketch.name_span.end_column, 9);
+    assert_eq!(sketch.span.start.line, 1);
+    assert_eq!(sketch.span.start.column, 1);
+    assert_eq!(sketch.span.lines, 2);
+    assert_eq!(sketch.span.end_column, 2);
+}
+
+#[test]
+fn test_sketch_with_parameters() {
+    let result = parse_with_timeout(
+        "sketch Bolt(diameter: f64, length: f64) {\n    let radius = diameter / 2.0;\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_eq!(sketch.name, "Bolt");
+    assert_eq!(sketch.parameters.len(), 2);
+    assert_eq!(sketch.parameters[0].name, "diameter");
+    assert_matches!(sketch.parameters[0].ty, Type::F64 { .. });
+    assert_eq!(sketch.parameters[1].name, "length");
+    assert_eq!(sketch.items.len(), 1);
+}
+
+#[test]
+fn test_sketch_with_empty_parameter_list() {
+    let result = parse_with_timeout(
+        "sketch Plate() {}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.unwrap().parameters.is_empty());
+}
+
+#[test]
+fn test_sketch_with_test_statements() {
+    let result = parse_with_timeout(
+        "sketch Gauge {\n    #[test] assert r > 0;\n    let r = 1.0;\n    #[test]\n    assert r < 2;\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_matches!(
+        sketch.items[..],
+        [
+            SketchItem::Test(Stmt::Assert { .. }),
+            SketchItem::Stmt(Stmt::Let { name: "r", .. }),
+            SketchItem::Test(Stmt::Assert { .. })
+        ]
+    );
+}
+
+#[test]
+fn test_sketch_unknown_attribute() {
+    let result = parse_with_timeout(
+        "sketch Gauge { #[bench] assert true; }",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sketch_parameter_missing_type() {
+    let result = parse_with_timeout(
+        "sketch Bolt(diameter) {}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sketch_missing_closing_brace() {
+    let result = parse_with_timeout(
+        "sketch A { let x = 1;",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_program_with_multiple_sketches() {
+    let result = parse_with_timeout(
+        "sketch A { let x = 1; }\nsketch B { let y: bool = true; }",
+        |input| program().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let program = result.unwrap();
+    let names: Vec<_> = program.sketches().map(|sketch| sketch.name).collect();
+    assert_eq!(names, ["A", "B"]);
+}
+
+#[test]
+fn test_empty_program() {
+    let result = parse_with_timeout(
+        "",
+        |input| program().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.unwrap().items.is_empty());
+}
+
+#[test]
+fn test_program_rejects_top_level_let() {
+    let result = parse_with_timeout(
+        "let x = 1;",
+        |input| program().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sketch_is_still_a_valid_variable_name() {
+    let result = parse_with_timeout(
+        "sketch Main { let sketch = 1; }",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let sketch = result.unwrap();
+    assert_matches!(
+        sketch.items[0],
+        SketchItem::Stmt(Stmt::Let { name: "sketch", .. })
+    );
+}
+
+// ========================================================================
+// Struct Definition Tests
+// ========================================================================
+
+#[test]
+fn test_struct_with_zero_fields() {
+    let result = parse_with_timeout(
+        "struct Empty {}",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let def = result.unwrap();
+    assert_eq!(def.name, "Empty");
+    assert!(def.fields.is_empty());
+    assert!(def.methods.is_empty());
+}
+
+#[test]
+fn test_struct_with_one_field() {
+    let result = parse_with_timeout(
+        "struct Wrapper { value: f64 }",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let def = result.unwrap();
+    assert_eq!(def.fields.len(), 1);
+    assert_eq!(def.fields[0].name, "value");
+    assert_matches!(def.fields[0].ty, Type::F64 { .. });
+}
+
+#[test]
+fn test_struct_with_multiple_field_types() {
+    let result = parse_with_timeout(
+        "struct Mixed { flag: bool, count: i32, center: Point, anchor: &Point, }",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let def = result.unwrap();
+    assert_eq!(def.fields.len(), 4);
+    assert_matches!(def.fields[0].ty, Type::Bool { .. });
+    assert_matches!(def.fields[1].ty, Type::I32 { .. });
+    assert_matches!(def.fields[2].ty, Type::Named { name: "Point", .. });
+    assert_matches!(def.fields[3].ty, Type::Ref { .. });
+}
+
+#[test]
+fn test_struct_with_methods() {
+    let result = parse_with_timeout(
+        "struct Circle {\n    radius: f64,\n\n    fn diameter() -> f64 {\n        self.radius * 2.0\n    }\n\n    fn scaled(factor: f64) -> Circle {\n        let r = self.radius * factor;\n        Circle { radius: r }\n    }\n}",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let def = result.unwrap();
+    assert_eq!(def.fields.len(), 1);
+    assert_eq!(def.methods.len(), 2);
+
+    let diameter = &def.methods[0];
+    assert_eq!(diameter.name, "diameter");
+    assert!(diameter.params.is_empty());
+    assert_matches!(diameter.return_type, Some(Type::F64 { .. }));
+    assert!(diameter.body.is_empty());
+    assert_matches!(diameter.result, Some(Expr::Mul { .. }));
+
+    let scaled = &def.methods[1];
+    assert_eq!(scaled.params.len(), 1);
+    assert_eq!(scaled.params[0].name, "factor");
+    assert_eq!(scaled.body.len(), 1);
+    assert_matches!(scaled.result, Some(Expr::StructLit { name: "Circle", .. }));
+}
+
+#[test]
+fn test_struct_span() {
+    let result = parse_with_timeout(
+        "struct A {\n  x: f64\n}",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let def = result.unwrap();
+    assert_eq!(def.fields[0].span.start.column, 3);
+    assert_eq!(def.fields[0].span.end_column, 9);
+    assert_eq!(def.span.start.column, 1);
+    assert_eq!(def.span.lines, 2);
+    assert_eq!(def.span.end_column, 2);
+}
+
+#[test]
+fn test_struct_field_missing_type() {
+    let result = parse_with_timeout(
+        "struct A { x }",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_program_with_structs_and_sketches() {
+    let result = parse_with_timeout(
+        "struct Point { x: f64, y: f64 }\nsketch Main { let p = Point { x: 0, y: 0 }; }\nstruct Line { start: Point, end: Point }",
+        |input| program().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let program = result.unwrap();
+    assert_matches!(
+        program.items[..],
+        [
+            Item::Struct(StructDef { name: "Point", .. }),
+            Item::Sketch(SketchDef { name: "Main", .. }),
+            Item::Struct(StructDef { name: "Line", .. })
+        ]
+    );
+}
+
+// ========================================================================
+// Impl Block Tests
+// ========================================================================
+
+#[test]
+fn test_impl_block_with_methods() {
+    let result = parse_with_timeout(
+        "impl Circle {\n    fn area() -> f64 { self.radius * self.radius }\n    fn grow(by: f64) { let r = self.radius + by; }\n}",
+        |input| impl_block(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let block = result.unwrap();
+    assert_eq!(block.target, "Circle");
+    assert_eq!(block.target_span.start.column, 6);
+    assert_eq!(block.methods.len(), 2);
+    assert_eq!(block.methods[0].name, "area");
+    assert_eq!(block.methods[1].name, "grow");
+    assert_eq!(block.span.start.column, 1);
+    assert_eq!(block.span.lines, 3);
+}
+
+#[test]
+fn test_empty_impl_block() {
+    let result = parse_with_timeout(
+        "impl Point {}",
+        |input| impl_block(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let block = result.unwrap();
+    assert_eq!(block.target, "Point");
+    assert!(block.methods.is_empty());
+}
+
+#[test]
+fn test_impl_block_rejects_fields() {
+    let result = parse_with_timeout(
+        "impl Point { x: f64 }",
+        |input| impl_block(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_program_with_two_impl_blocks_for_one_struct() {
+    let result = parse_with_timeout(
+        "struct Circle { radius: f64 }\nimpl Circle { fn diameter() -> f64 { self.radius * 2.0 } }\nimpl Circle { fn area() -> f64 { self.radius * self.radius } }\nsketch Main { let d = c.diameter() + c.area(); }",
+        |input| program().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let program = result.unwrap();
+    assert_eq!(program.structs().count(), 1);
+    let impls: Vec<_> = program.impls().collect();
+    assert_eq!(impls.len(), 2);
+    assert_eq!(impls[0].target, "Circle");
+    assert_eq!(impls[0].methods[0].name, "diameter");
+    assert_eq!(impls[1].target, "Circle");
+    assert_eq!(impls[1].methods[0].name, "area");
+}
+
+// ========================================================================
+// Function Definition Tests
+// ========================================================================
+
+#[test]
+fn test_function_with_zero_params() {
     let result = parse_with_timeout(
-        "obj.method().field",
-        |input| expr().parse(input).into_result(),
+        "fn origin() -> Point { point(0, 0) }",
+        |input| function_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::FieldAccess {
-            receiver, field, ..
-        } => {
-            assert_eq!(field, "field");
-            match *receiver {
-                Expr::MethodCall {
-                    receiver: inner_receiver,
-                    method,
-                    args,
-                    ..
-                } => {
-                    assert!(matches!(*inner_receiver, Expr::Var { name, .. } if name == "obj"));
-                    assert_eq!(method, "method");
-                    assert_eq!(args.len(), 0);
-                }
-                other => panic!("Expected Expr::MethodCall, got {:?}", other),
-            }
-        }
-        other => panic!("Expected Expr::FieldAccess, got {:?}", other),
-    }
+    let function = result.unwrap();
+    assert_eq!(function.name, "origin");
+    assert!(function.params.is_empty());
+    assert_matches!(
+        function.return_type,
+        Some(Type::Named { name: "Point", .. })
+    );
+    assert_matches!(function.result, Some(Expr::Call { name: "point", .. }));
 }
 
 #[test]
-fn test_field_access_in_expression() {
-    // Test: obj.field + 2
+fn test_function_with_multiple_params() {
     let result = parse_with_timeout(
-        "obj.field + 2",
-        |input| expr().parse(input).into_result(),
+        "fn distance(p1: &Point, p2: &Point, scale: f64) -> f64 {\n    let dx = p2.x - p1.x;\n    dx * scale\n}",
+        |input| function_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::Add { lhs, rhs, .. } => {
-            assert!(matches!(*lhs, AddLhs::FieldAccess { .. }));
-            assert!(matches!(*rhs, AddRhs::IntLit { value: 2, .. }));
-        }
-        other => panic!("Expected Expr::Add, got {:?}", other),
-    }
+    let function = result.unwrap();
+    assert_eq!(function.params.len(), 3);
+    assert_eq!(function.params[0].name, "p1");
+    assert_matches!(function.params[0].ty, Type::Ref { .. });
+    assert_eq!(function.params[2].name, "scale");
+    assert_matches!(function.params[2].ty, Type::F64 { .. });
+    assert_eq!(function.body.len(), 1);
+    assert_matches!(function.result, Some(Expr::Mul { .. }));
 }
 
-// ============================================================================
-// Array Literal Tests
-// ============================================================================
+#[test]
+fn test_function_with_impl_trait_return_type() {
+    let result = parse_with_timeout(
+        "fn unit() -> impl Shape { Circle { radius: 1.0 } }",
+        |input| function_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let function = result.unwrap();
+    assert_matches!(
+        function.return_type,
+        Some(Type::ImplTrait {
+            trait_name: "Shape",
+            ..
+        })
+    );
+    assert_matches!(
+        function.result,
+        Some(Expr::StructLit { name: "Circle", .. })
+    );
+}
 
 #[test]
-fn test_empty_array_literal() {
+fn test_impl_trait_only_as_return_type() {
     let result = parse_with_timeout(
-        "[]",
-        |input| expr().parse(input).into_result(),
+        "impl Shape",
+        |input| type_annotation().parse(input).into_result(),
         Duration::from_secs(2),
     );
+    assert!(result.is_err());
+}
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 0);
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
-    }
+#[test]
+fn test_function_without_return_type() {
+    let result = parse_with_timeout(
+        "fn setup(x: i32) { let y = x; }",
+        |input| function_def(expr_inner()).parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let function = result.unwrap();
+    assert!(function.return_type.is_none());
+    assert_eq!(function.body.len(), 1);
+    assert!(function.result.is_none());
 }
 
 #[test]
-fn test_array_literal_single_element() {
+fn test_function_span() {
     let result = parse_with_timeout(
-        "[42]",
-        |input| expr().parse(input).into_result(),
+        "fn f(a: i32) {}",
+        |input| function_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 1);
-            assert_matches!(elements[0], Expr::IntLit { value: 42, .. });
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
-    }
+    let function = result.unwrap();
+    assert_eq!(function.params[0].span.start.column, 6);
+    assert_eq!(function.params[0].span.end_column, 12);
+    assert_eq!(function.span.start.column, 1);
+    assert_eq!(function.span.end_column, 16);
 }
 
 #[test]
-fn test_array_literal_multiple_elements() {
+fn test_function_missing_param_type() {
     let result = parse_with_timeout(
-        "[1, 2, 3]",
-        |input| expr().parse(input).into_result(),
+        "fn f(a) {}",
+        |input| function_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 3);
-            assert_matches!(elements[0], Expr::IntLit { value: 1, .. });
-            assert_matches!(elements[1], Expr::IntLit { value: 2, .. });
-            assert_matches!(elements[2], Expr::IntLit { value: 3, .. });
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
-    }
+    assert!(result.is_err());
 }
 
 #[test]
-fn test_array_literal_with_expressions() {
+fn test_sketch_with_local_function_call() {
     let result = parse_with_timeout(
-        "[1 + 2, 3 * 4]",
-        |input| expr().parse(input).into_result(),
+        "sketch Main {\n    fn double(x: f64) -> f64 { x * 2.0 }\n    let y = double(1.5);\n}",
+        |input| sketch_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 2);
-            assert_matches!(elements[0], Expr::Add { .. });
-            assert_matches!(elements[1], Expr::Mul { .. });
+    let sketch = result.unwrap();
+    assert_eq!(sketch.items.len(), 2);
+    assert_matches!(&sketch.items[0], SketchItem::Function(function) if function.name == "double");
+    match &sketch.items[1] {
+        SketchItem::Stmt(Stmt::Let {
+            init: Some(Expr::Call { name, args, .. }),
+            ..
+        }) => {
+            assert_eq!(*name, "double");
+            assert_eq!(args.len(), 1);
         }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+        other => panic!("Expected let with call initializer, got {:?}", other),
     }
 }
 
 #[test]
-fn test_array_literal_trailing_comma() {
+fn test_program_with_top_level_functions() {
     let result = parse_with_timeout(
-        "[1, 2, 3,]",
-        |input| expr().parse(input).into_result(),
+        "fn helper() -> i32 { 1 }\nsketch Main { let x = helper(); }\nfn other(a: i32) {}",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 3);
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
-    }
+    let program = result.unwrap();
+    assert_matches!(
+        program.items[..],
+        [
+            Item::Function(FunctionDef { name: "helper", .. }),
+            Item::Sketch(SketchDef { name: "Main", .. }),
+            Item::Function(FunctionDef { name: "other", .. })
+        ]
+    );
 }
 
+// ========================================================================
+// Import Tests
+// ========================================================================
+
 #[test]
-fn test_nested_array_literal() {
+fn test_import_path_only() {
     let result = parse_with_timeout(
-        "[[1, 2], [3, 4]]",
-        |input| expr().parse(input).into_result(),
+        "import \"lib/shapes.cad\";",
+        |input| import_decl().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 2);
-            assert_matches!(elements[0], Expr::ArrayLit { .. });
-            assert_matches!(elements[1], Expr::ArrayLit { .. });
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
-    }
+    let import = result.unwrap();
+    assert_eq!(import.path, "lib/shapes.cad");
+    assert!(import.items.is_none());
+    assert_eq!(import.path_span.start.column, 8);
+    assert_eq!(import.span.end_column, 25);
 }
 
-// ============================================================================
-// Struct Literal Tests
-// ============================================================================
+#[test]
+fn test_import_with_items() {
+    let result = parse_with_timeout(
+        "import \"shapes.cad\" use Point, Line;",
+        |input| import_decl().parse(input).into_result(),
+        Duration::from_secs(2),
+    );
+
+    let import = result.unwrap();
+    let names: Vec<_> = import
+        .items
+        .unwrap()
+        .iter()
+        .map(|(name, _)| *name)
+        .collect();
+    assert_eq!(names, vec!["Point", "Line"]);
+}
 
 #[test]
-fn test_empty_struct_literal() {
+fn test_import_with_empty_use_list() {
     let result = parse_with_timeout(
-        "Point {}",
-        |input| expr().parse(input).into_result(),
+        "import \"shapes.cad\" use;",
+        |input| import_decl().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Point");
-            assert_eq!(fields.len(), 0);
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
-    }
+    assert!(result.is_err());
 }
 
 #[test]
-fn test_struct_literal_single_field() {
+fn test_program_with_imports() {
     let result = parse_with_timeout(
-        "Point { x: 10 }",
-        |input| expr().parse(input).into_result(),
+        "import \"a.cad\";\nimport \"b.cad\" use B;\nsketch Main {}",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Point");
-            assert_eq!(fields.len(), 1);
-            assert_eq!(fields[0].0, "x");
-            assert_matches!(fields[0].1, Expr::IntLit { value: 10, .. });
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
-    }
+    let program = result.unwrap();
+    assert_eq!(program.imports.len(), 2);
+    assert_eq!(program.imports[1].path, "b.cad");
+    assert_eq!(program.sketches().count(), 1);
 }
 
 #[test]
-fn test_struct_literal_multiple_fields() {
+fn test_program_rejects_import_after_definition() {
     let result = parse_with_timeout(
-        "Point { x: 10, y: 20 }",
-        |input| expr().parse(input).into_result(),
+        "sketch Main {}\nimport \"a.cad\";",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Point");
-            assert_eq!(fields.len(), 2);
-            assert_eq!(fields[0].0, "x");
-            assert_eq!(fields[1].0, "y");
-            assert_matches!(fields[0].1, Expr::IntLit { value: 10, .. });
-            assert_matches!(fields[1].1, Expr::IntLit { value: 20, .. });
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
-    }
+    assert!(result.is_err());
 }
 
+// ========================================================================
+// Visibility Tests
+// ========================================================================
+
 #[test]
-fn test_struct_literal_with_expressions() {
+fn test_definitions_are_private_by_default() {
     let result = parse_with_timeout(
-        "Circle { center: point(0, 0), radius: 5 * 2 }",
-        |input| expr().parse(input).into_result(),
+        "struct Point { x: f64 }\nfn origin() {}\nsketch Main {}",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Circle");
-            assert_eq!(fields.len(), 2);
-            assert_eq!(fields[0].0, "center");
-            assert_eq!(fields[1].0, "radius");
-            assert_matches!(fields[0].1, Expr::Call { .. });
-            assert_matches!(fields[1].1, Expr::Mul { .. });
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
+    let program = result.unwrap();
+    let point = program.structs().next().unwrap();
+    assert_eq!(point.visibility, Visibility::Private);
+    assert_eq!(point.fields[0].visibility, Visibility::Private);
+    for item in &program.items {
+        let visibility = match item {
+            Item::Sketch(sketch) => sketch.visibility,
+            Item::Struct(def) => def.visibility,
+            Item::Impl(_) => continue,
+            Item::Function(function) => function.visibility,
+        };
+        assert_eq!(visibility, Visibility::Private);
     }
 }
 
 #[test]
-fn test_struct_literal_trailing_comma() {
+fn test_pub_definitions() {
     let result = parse_with_timeout(
-        "Point { x: 10, y: 20, }",
-        |input| expr().parse(input).into_result(),
+        "pub struct Point { pub x: f64, y: f64, pub fn len() {} }\npub fn origin() {}\npub sketch Main {}",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Point");
-            assert_eq!(fields.len(), 2);
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
-    }
+    let program = result.unwrap();
+    let point = program.structs().next().unwrap();
+    assert_eq!(point.visibility, Visibility::Public);
+    assert_eq!(point.fields[0].visibility, Visibility::Public);
+    assert_eq!(point.fields[1].visibility, Visibility::Private);
+    assert_eq!(point.methods[0].visibility, Visibility::Public);
+    let origin = program.functions().next().unwrap();
+    assert_eq!(origin.visibility, Visibility::Public);
+    let main = program.sketches().next().unwrap();
+    assert_eq!(main.visibility, Visibility::Public);
 }
 
 #[test]
-fn test_nested_struct_literal() {
+fn test_pub_span_starts_at_modifier() {
     let result = parse_with_timeout(
-        "Line { start: Point { x: 0, y: 0 }, end: Point { x: 10, y: 10 } }",
-        |input| expr().parse(input).into_result(),
+        "pub struct Point { pub x: f64 }",
+        |input| struct_def(expr_inner()).parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::StructLit { name, fields, .. } => {
-            assert_eq!(name, "Line");
-            assert_eq!(fields.len(), 2);
-            assert_eq!(fields[0].0, "start");
-            assert_eq!(fields[1].0, "end");
-            assert_matches!(fields[0].1, Expr::StructLit { .. });
-            assert_matches!(fields[1].1, Expr::StructLit { .. });
-        }
-        other => panic!("Expected Expr::StructLit, got {:?}", other),
-    }
+    let def = result.unwrap();
+    assert_eq!(def.span.start.column, 1);
+    assert_eq!(def.fields[0].span.start.column, 20);
+    assert_eq!(def.fields[0].name_span.start.column, 24);
 }
 
 #[test]
-fn test_array_of_struct_literals() {
+fn test_pub_impl_is_rejected() {
     let result = parse_with_timeout(
-        "[Point { x: 0, y: 0 }, Point { x: 10, y: 10 }]",
-        |input| expr().parse(input).into_result(),
+        "pub impl Point {}",
+        |input| program().parse(input).into_result(),
         Duration::from_secs(2),
     );
 
-    match result.unwrap() {
-        Expr::ArrayLit { elements, .. } => {
-            assert_eq!(elements.len(), 2);
-            assert_matches!(elements[0], Expr::StructLit { .. });
-            assert_matches!(elements[1], Expr::StructLit { .. });
-        }
-        other => panic!("Expected Expr::ArrayLit, got {:?}", other),
+    assert!(result.is_err());
+}
+
+// ========================================================================
+// Error Recovery Tests
+// ========================================================================
+
+#[test]
+fn test_recovery_skips_to_next_statement() {
+    let Ok((tokens, _)) = lexer::tokenize("sketch Main { let a = 1 + ; let b = 2; assert b >; }");
+    let (program, errors) = parse(&tokens);
+
+    let program = program.unwrap();
+    let items = &program.sketches().next().unwrap().items;
+    assert_matches!(
+        items[..],
+        [
+            SketchItem::Stmt(Stmt::Error { .. }),
+            SketchItem::Stmt(Stmt::Let { name: "b", .. }),
+            SketchItem::Stmt(Stmt::Error { .. })
+        ]
+    );
+
+    assert_eq!(errors.len(), 2);
+    for error in &errors {
+        assert_matches!(error.kind, DiagnosticKind::RecoveredStatement { .. });
+        assert_eq!(error.message(), "unexpected ';'");
     }
 }
+
+#[test]
+fn test_recovered_statement_span_covers_skipped_tokens() {
+    let Ok((tokens, _)) = lexer::tokenize("sketch Main {\n    let a = 1 +;\n}");
+    let (program, errors) = parse(&tokens);
+
+    let program = program.unwrap();
+    let SketchItem::Stmt(stmt) = &program.sketches().next().unwrap().items[0] else {
+        panic!("Expected a statement");
+    };
+    let span = stmt.span();
+    assert_eq!(span.start, LineColumn { line: 2, column: 5 });
+    assert_eq!(
+        span.end(),
+        LineColumn {
+            line: 2,
+            column: 17
+        }
+    );
+    assert_matches!(
+        errors[0].kind,
+        DiagnosticKind::RecoveredStatement { skipped, .. } if skipped == span
+    );
+}
+
+#[test]
+fn test_recovery_steps_over_nested_blocks() {
+    let Ok((tokens, _)) = lexer::tokenize("fn f() { let p = Point { x: 1 } +; let q = 2; q }");
+    let (program, errors) = parse(&tokens);
+
+    let program = program.unwrap();
+    let function = program.functions().next().unwrap();
+    assert_eq!(function.body.len(), 2);
+    assert_matches!(function.body[0], Stmt::Error { .. });
+    assert_matches!(function.result, Some(Expr::Var { name: "q", .. }));
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_recovery_inside_if_body() {
+    let Ok((tokens, _)) = lexer::tokenize("sketch Main { if a > 1 { let x = ; } let y = 1; }");
+    let (program, errors) = parse(&tokens);
+
+    let program = program.unwrap();
+    let items = &program.sketches().next().unwrap().items;
+    assert_matches!(&items[0], SketchItem::Stmt(Stmt::If { then_body, .. }) if matches!(then_body[..], [Stmt::Error { .. }]));
+    assert_matches!(items[1], SketchItem::Stmt(Stmt::Let { name: "y", .. }));
+    assert_matches!(
+        errors[..],
+        [Diagnostic {
+            kind: DiagnosticKind::RecoveredStatement { .. },
+            ..
+        }]
+    );
+}
+
+#[test]
+fn test_recovery_keeps_sketch_functions() {
+    let Ok((tokens, _)) =
+        lexer::tokenize("sketch Main { let a = ); fn half() -> f64 { 0.5 } let b = 1; }");
+    let (program, errors) = parse(&tokens);
+
+    let program = program.unwrap();
+    assert_matches!(
+        program.sketches().next().unwrap().items[..],
+        [
+            SketchItem::Stmt(Stmt::Error { .. }),
+            SketchItem::Function(_),
+            SketchItem::Stmt(Stmt::Let { name: "b", .. })
+        ]
+    );
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_error_outside_statement_is_not_recovered() {
+    let Ok((tokens, _)) = lexer::tokenize("sketch Main { let a = 1; ");
+    let (program, errors) = parse(&tokens);
+
+    assert!(program.is_none());
+    assert_matches!(
+        errors[..],
+        [Diagnostic {
+            kind: DiagnosticKind::Parse(_),
+            ..
+        }]
+    );
+}
@@ -0,0 +1,429 @@
+//! Source location types shared by the lexer, parser and AST
+//!
+//! Positions are 1-based line/column pairs. A `Span` records where it starts,
+//! how many newlines it crosses and the (exclusive) column it ends at.
+
+use std::ops::{BitOr, Range};
+
+// ============================================================================
+// Position and Span Types
+// ============================================================================
+
+/// Positions order by line, then column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: LineColumn,
+    pub lines: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// The span from `start` up to (excluding) `end`
+    pub fn between(start: LineColumn, end: LineColumn) -> Span {
+        Span {
+            start,
+            lines: end.line - start.line,
+            end_column: end.column,
+        }
+    }
+
+    /// A zero-length span at `position`, e.g. for the end of input
+    pub fn empty(position: LineColumn) -> Span {
+        Span::between(position, position)
+    }
+
+    /// The (exclusive) position this span ends at
+    pub fn end(&self) -> LineColumn {
+        LineColumn {
+            line: self.start.line + self.lines,
+            column: self.end_column,
+        }
+    }
+
+    /// Whether the position at `line`/`column` falls inside this span
+    ///
+    /// The end is exclusive, so an empty span contains no positions.
+    pub fn contains_position(&self, line: usize, column: usize) -> bool {
+        let position = LineColumn { line, column };
+        self.start <= position && position < self.end()
+    }
+
+    /// Whether the two spans share at least one character
+    ///
+    /// Spans that only touch, where one ends where the other starts, do not
+    /// overlap, and neither does an empty span.
+    #[cfg_attr(not(test), allow(dead_code))] // No LSP hover handler yet
+    pub fn overlaps(&self, other: Span) -> bool {
+        // The intersection is non-empty, which also rules out empty spans
+        self.start.max(other.start) < self.end().min(other.end())
+    }
+
+    /// Byte offset of the start of this span in `source`
+    ///
+    /// Builds a `LineIndex` on every call; use `LineIndex::offset` directly
+    /// when converting many positions from the same source.
+    #[cfg_attr(not(test), allow(dead_code))] // Diagnostics convert whole spans with `LineIndex::range`
+    pub fn start_byte_offset(&self, source: &str) -> usize {
+        LineIndex::new(source).offset(self.start)
+    }
+}
+
+/// `a | b` is the smallest span covering both `a` and `b`
+///
+/// Typically used to merge the first and last token of an AST node, but the
+/// operands may come in either order.
+impl BitOr for Span {
+    type Output = Span;
+
+    fn bitor(self, rhs: Span) -> Span {
+        Span::between(self.start.min(rhs.start), self.end().max(rhs.end()))
+    }
+}
+
+impl std::fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Spans display as `line 1, column 5–9`, or `line 1 column 5 – line 3 column 2`
+/// when they cross lines. The end column is exclusive, as in `Span` itself, and
+/// an empty span displays as its position alone.
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let end = self.end();
+        if self.start == end {
+            write!(f, "{}", self.start)
+        } else if self.lines == 0 {
+            write!(f, "{}–{}", self.start, end.column)
+        } else {
+            write!(
+                f,
+                "line {} column {} – line {} column {}",
+                self.start.line, self.start.column, end.line, end.column
+            )
+        }
+    }
+}
+
+// ============================================================================
+// Line Index
+// ============================================================================
+
+/// Byte offsets of the start of every line in a source string
+///
+/// Converts between 1-based line/column positions (as stored in `Span`) and
+/// byte offsets (as used by Logos and most editor protocols). Columns count
+/// bytes, matching the lexer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// Byte offset of a line/column position
+    ///
+    /// The position must lie within the source this index was built from.
+    pub fn offset(&self, position: LineColumn) -> usize {
+        self.starts[position.line - 1] + position.column - 1
+    }
+
+    /// Byte range covered by a span
+    pub fn range(&self, span: Span) -> Range<usize> {
+        self.offset(span.start)..self.offset(span.end())
+    }
+
+    /// Line/column position of a byte offset
+    pub fn line_column(&self, offset: usize) -> LineColumn {
+        // Number of lines starting at or before the offset is the 1-based line
+        let line = self.starts.partition_point(|&start| start <= offset);
+        LineColumn {
+            line,
+            column: offset - self.starts[line - 1] + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_bitor_single_line() {
+        let left = Span {
+            start: LineColumn { line: 1, column: 1 },
+            lines: 0,
+            end_column: 4,
+        };
+        let right = Span {
+            start: LineColumn { line: 1, column: 8 },
+            lines: 0,
+            end_column: 10,
+        };
+
+        let merged = left | right;
+        assert_eq!(merged.start, LineColumn { line: 1, column: 1 });
+        assert_eq!(merged.lines, 0);
+        assert_eq!(merged.end_column, 10);
+    }
+
+    #[test]
+    fn test_span_bitor_multi_line() {
+        let left = Span {
+            start: LineColumn { line: 2, column: 5 },
+            lines: 1,
+            end_column: 3,
+        };
+        let right = Span {
+            start: LineColumn { line: 4, column: 1 },
+            lines: 2,
+            end_column: 7,
+        };
+
+        // Ends on line 6, four lines after the start line
+        let merged = left | right;
+        assert_eq!(merged.start, LineColumn { line: 2, column: 5 });
+        assert_eq!(merged.lines, 4);
+        assert_eq!(merged.end_column, 7);
+    }
+
+    #[test]
+    fn test_span_bitor_is_order_independent() {
+        let outer = Span {
+            start: LineColumn { line: 1, column: 1 },
+            lines: 2,
+            end_column: 2,
+        };
+        let inner = Span {
+            start: LineColumn { line: 2, column: 3 },
+            lines: 0,
+            end_column: 6,
+        };
+
+        assert_eq!(inner | outer, outer);
+        assert_eq!(outer | inner, outer);
+        assert_eq!(inner | inner, inner);
+    }
+
+    #[test]
+    fn test_span_empty() {
+        let position = LineColumn { line: 3, column: 7 };
+        let span = Span::empty(position);
+
+        assert_eq!(span.start, position);
+        assert_eq!(span.end(), position);
+        assert_eq!(span | span, span);
+    }
+
+    #[test]
+    fn test_span_contains_position_boundaries() {
+        // Covers columns 5..9 of line 2
+        let span = Span {
+            start: LineColumn { line: 2, column: 5 },
+            lines: 0,
+            end_column: 9,
+        };
+
+        assert!(!span.contains_position(2, 4));
+        assert!(span.contains_position(2, 5));
+        assert!(span.contains_position(2, 8));
+        assert!(!span.contains_position(2, 9));
+        assert!(!span.contains_position(1, 6));
+        assert!(!span.contains_position(3, 6));
+        assert!(!Span::empty(span.start).contains_position(2, 5));
+    }
+
+    #[test]
+    fn test_span_contains_position_multi_line() {
+        // From line 1 column 10 to line 3 column 2
+        let span = Span {
+            start: LineColumn {
+                line: 1,
+                column: 10,
+            },
+            lines: 2,
+            end_column: 2,
+        };
+
+        assert!(span.contains_position(1, 10));
+        assert!(span.contains_position(2, 100));
+        assert!(span.contains_position(3, 1));
+        assert!(!span.contains_position(3, 2));
+        assert!(!span.contains_position(1, 9));
+    }
+
+    #[test]
+    fn test_span_overlaps_boundaries() {
+        let span = |column, end_column| Span {
+            start: LineColumn { line: 1, column },
+            lines: 0,
+            end_column,
+        };
+
+        // Adjacent spans share no character, in either order
+        assert!(!span(1, 5).overlaps(span(5, 8)));
+        assert!(!span(5, 8).overlaps(span(1, 5)));
+        // Sharing only column 4
+        assert!(span(1, 5).overlaps(span(4, 8)));
+        assert!(span(4, 8).overlaps(span(1, 5)));
+        // Containment, including a span with itself
+        assert!(span(1, 9).overlaps(span(3, 4)));
+        assert!(span(3, 4).overlaps(span(1, 9)));
+        assert!(span(3, 4).overlaps(span(3, 4)));
+        // Empty spans overlap nothing, even inside another span
+        assert!(!span(3, 3).overlaps(span(1, 5)));
+        assert!(!span(1, 5).overlaps(span(3, 3)));
+        assert!(!span(3, 3).overlaps(span(3, 3)));
+    }
+
+    #[test]
+    fn test_span_overlaps_multi_line() {
+        // From line 1 column 10 to line 3 column 2
+        let multi = Span {
+            start: LineColumn {
+                line: 1,
+                column: 10,
+            },
+            lines: 2,
+            end_column: 2,
+        };
+        let on_line = |line, column, end_column| Span {
+            start: LineColumn { line, column },
+            lines: 0,
+            end_column,
+        };
+
+        assert!(multi.overlaps(on_line(2, 1, 3)));
+        assert!(on_line(2, 1, 3).overlaps(multi));
+        assert!(multi.overlaps(on_line(3, 1, 2)));
+        assert!(multi.overlaps(on_line(1, 5, 11)));
+        // Touching the start or the end of the multi-line span
+        assert!(!multi.overlaps(on_line(1, 5, 10)));
+        assert!(!multi.overlaps(on_line(3, 2, 6)));
+        // Two multi-line spans that share lines
+        let other = Span {
+            start: LineColumn { line: 2, column: 4 },
+            lines: 3,
+            end_column: 1,
+        };
+        assert!(multi.overlaps(other));
+        assert!(other.overlaps(multi));
+    }
+
+    #[test]
+    fn test_span_hash_set_deduplicates() {
+        use std::collections::HashSet;
+
+        let a = Span {
+            start: LineColumn { line: 1, column: 1 },
+            lines: 0,
+            end_column: 4,
+        };
+        let b = Span {
+            start: LineColumn { line: 2, column: 1 },
+            lines: 0,
+            end_column: 4,
+        };
+
+        let spans: HashSet<Span> = [a, b, a].into_iter().collect();
+        assert_eq!(spans.len(), 2);
+        assert!(spans.contains(&a));
+        assert!(spans.contains(&b));
+    }
+
+    #[test]
+    fn test_span_as_hash_map_key() {
+        use std::collections::HashMap;
+
+        let span = Span {
+            start: LineColumn { line: 3, column: 5 },
+            lines: 0,
+            end_column: 9,
+        };
+        let other = Span {
+            start: LineColumn { line: 3, column: 5 },
+            lines: 1,
+            end_column: 9,
+        };
+
+        let mut diagnostics: HashMap<Span, &str> = HashMap::new();
+        diagnostics.insert(span, "unexpected token");
+
+        assert_eq!(diagnostics.get(&span), Some(&"unexpected token"));
+        assert_eq!(diagnostics.get(&other), None);
+    }
+
+    #[test]
+    fn test_line_index_round_trip() {
+        let source = "let a = 1;\n\nlet bb = 22;\n";
+        let index = LineIndex::new(source);
+
+        for offset in 0..source.len() {
+            let position = index.line_column(offset);
+            assert_eq!(index.offset(position), offset);
+        }
+
+        assert_eq!(index.line_column(0), LineColumn { line: 1, column: 1 });
+        assert_eq!(index.line_column(11), LineColumn { line: 2, column: 1 });
+        assert_eq!(index.line_column(16), LineColumn { line: 3, column: 5 });
+    }
+
+    #[test]
+    fn test_start_byte_offset_matches_lexer_positions() {
+        use crate::lexer::{TokenTrait, tokenize};
+
+        let source = "let x = y;\n  let yy = x;";
+        let Ok((tokens, _)) = tokenize(source);
+
+        for token in &tokens {
+            let offset = token.span().start_byte_offset(source);
+            assert!(source[offset..].starts_with(token.value_str()));
+        }
+    }
+
+    #[test]
+    fn test_span_display_single_line() {
+        let span = Span {
+            start: LineColumn { line: 4, column: 5 },
+            lines: 0,
+            end_column: 9,
+        };
+
+        assert_eq!(span.to_string(), "line 4, column 5–9");
+        assert_eq!(
+            format!("undefined symbol 'x' at {}", span),
+            "undefined symbol 'x' at line 4, column 5–9"
+        );
+    }
+
+    #[test]
+    fn test_span_display_multi_line() {
+        let span = Span {
+            start: LineColumn { line: 1, column: 5 },
+            lines: 2,
+            end_column: 3,
+        };
+
+        assert_eq!(span.to_string(), "line 1 column 5 – line 3 column 3");
+    }
+
+    #[test]
+    fn test_span_display_empty() {
+        let span = Span::empty(LineColumn { line: 2, column: 7 });
+
+        assert_eq!(span.to_string(), "line 2, column 7");
+    }
+}
@@ -0,0 +1,439 @@
+//! End-to-end tests for the command line interface
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Run the CLI with `args`, feeding `stdin` to it
+fn run_with_stdin(args: &[&str], stdin: &str) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_cad-dsl"));
+    command.args(args);
+    feed_stdin(command, stdin)
+}
+
+/// Run the CLI in the fixture directory `dir`, feeding `stdin` to it
+fn run_with_stdin_in(args: &[&str], stdin: &str, dir: &str) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_cad-dsl"));
+    command.args(args).current_dir(fixture(dir));
+    feed_stdin(command, stdin)
+}
+
+fn feed_stdin(mut command: Command, stdin: &str) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start cad-dsl");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_lex_from_stdin_with_dash() {
+    let output = run_with_stdin(&["lex", "-"], "let x = 42;");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Let("));
+    assert!(stdout.contains("SemiColon("));
+}
+
+#[test]
+fn test_lex_from_stdin_without_file() {
+    let output = run_with_stdin(&["lex"], "x");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Identifier("));
+}
+
+#[test]
+fn test_lex_reports_every_invalid_character() {
+    let output = run_with_stdin(&["lex"], "let $ = 1 @;");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("SemiColon("));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unexpected character '$'"));
+    assert!(stderr.contains("unexpected character '@'"));
+}
+
+#[test]
+fn test_parse_max_errors_limits_reported_errors() {
+    let output = run_with_stdin(&["parse", "--max-errors", "1"], "let $ = 1 @ ?;");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unexpected character '$'"));
+    assert!(!stderr.contains("unexpected character '@'"));
+    assert!(stderr.contains("too many errors, 2 more not shown"));
+}
+
+#[test]
+fn test_parse_rejects_zero_max_errors() {
+    let output = run_with_stdin(&["parse", "--max-errors", "0"], "let $ = 1;");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--max-errors"));
+    assert!(!stderr.contains("unexpected character '$'"));
+}
+
+#[test]
+fn test_parse_from_stdin() {
+    let output = run_with_stdin(&["parse", "-"], "sketch Main { let x = 1; }");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Successfully parsed!"));
+}
+
+#[test]
+fn test_parse_error_from_stdin_reports_stdin_name() {
+    let output = run_with_stdin(&["parse", "-"], "sketch Main { let x = ; }");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("<stdin>"));
+}
+
+#[test]
+fn test_parse_error_lists_only_real_alternatives() {
+    let output = run_with_stdin(&["parse"], "sketch Main { a b }");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Expected one of: assignment operator"));
+    assert!(!stderr.contains("something else"));
+}
+
+#[test]
+fn test_parse_reports_errors_in_several_statements() {
+    let output = run_with_stdin(
+        &["parse"],
+        "sketch Main { let x = ; let y = 1; let z = ); }",
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unexpected ';'"));
+    assert!(stderr.contains("Unexpected ')'"));
+    assert_eq!(stderr.matches("skipped up to the next ';'").count(), 2);
+}
+
+/// Path to a file under tests/fixtures
+fn fixture(path: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), path)
+}
+
+#[test]
+fn test_parse_records_source_files() {
+    let path = fixture("multi_file/shapes.cad");
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&format!("source_files: [{:?}]", path)));
+
+    let output = run_with_stdin(&["parse"], "sketch Main { let x = 1; }");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("source_files: []"));
+}
+
+#[test]
+fn test_parse_max_errors_counts_across_files() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .args(["parse", "--max-errors", "1"])
+        .arg(fixture("errors/first.cad"))
+        .arg(fixture("errors/second.cad"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unexpected character '$'"));
+    assert!(!stderr.contains("unexpected character '@'"));
+    assert!(!stderr.contains("unexpected character '`'"));
+    // One note for the whole run, not one per file
+    assert_eq!(stderr.matches("too many errors").count(), 1);
+    assert!(stderr.contains("too many errors, 3 more not shown"));
+}
+
+#[test]
+fn test_parse_multiple_files_into_one_program() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("multi_file/shapes.cad"))
+        .arg(fixture("multi_file/main.cad"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("StructDef { name: \"Point\""));
+    assert!(stdout.contains("SketchDef { name: \"Main\""));
+    assert!(stdout.contains(&format!(
+        "source_files: [{:?}, {:?}]",
+        fixture("multi_file/shapes.cad"),
+        fixture("multi_file/main.cad")
+    )));
+}
+
+#[test]
+fn test_parse_reports_struct_defined_in_two_files() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("multi_file/shapes.cad"))
+        .arg(fixture("multi_file/duplicate.cad"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("'Point' is defined more than once"));
+    assert!(stderr.contains("shapes.cad at line 2, column 8–13"));
+}
+
+#[test]
+fn test_parse_follows_relative_imports() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("imports/main.cad"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("FunctionDef { name: \"half\""));
+    assert!(stdout.contains("StructDef { name: \"Point\""));
+    assert!(stdout.contains("SketchDef { name: \"Main\""));
+}
+
+#[test]
+fn test_parse_loads_each_imported_file_once() {
+    // shapes.cad is both imported by main.cad and given directly
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("imports/main.cad"))
+        .arg(fixture("imports/lib/shapes.cad"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("StructDef { name: \"Point\"").count(), 1);
+}
+
+#[test]
+fn test_parse_mixed_top_level_definitions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("mixed/program.cad"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("ImportDecl { path: \"units.cad\""));
+    assert!(stdout.contains("StructDef { name: \"Circle\""));
+    assert!(stdout.contains("ImplBlock { target: \"Circle\""));
+    assert!(stdout.contains("SketchDef { name: \"Washer\""));
+    assert!(stdout.contains("SketchDef { name: \"Main\""));
+    for function in ["area", "double", "mm", "diameter", "scaled", "ring"] {
+        assert!(stdout.contains(&format!("FunctionDef {{ name: {:?}", function)));
+    }
+}
+
+#[test]
+fn test_parse_reports_missing_import() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("imports/missing.cad"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot find imported file 'does_not_exist.cad'"));
+}
+
+#[test]
+fn test_parse_reports_circular_import() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .arg("parse")
+        .arg(fixture("imports/cycle_a.cad"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("import of 'cycle_a.cad' forms a cycle"));
+}
+
+#[test]
+fn test_parse_emit_dot() {
+    let output = run_with_stdin(&["parse", "--emit-dot", "-"], "sketch Main { let x = 1; }");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("digraph ast {"));
+    assert!(stdout.contains("[label=\"Sketch Main\"]"));
+    assert!(!stdout.contains("Successfully parsed!"));
+}
+
+#[test]
+fn test_parse_emit_source() {
+    let output = run_with_stdin(
+        &["parse", "--emit-source", "-"],
+        "sketch Main{let x=1+2;assert x>0;}",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "sketch Main {\n  let x = 1 + 2;\n  assert x > 0;\n}\n"
+    );
+}
+
+#[test]
+fn test_parse_emit_source_prints_only_the_given_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .args(["parse", "--emit-source"])
+        .arg(fixture("mixed/program.cad"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let formatted = String::from_utf8(output.stdout).unwrap();
+    assert!(formatted.starts_with("import \"units.cad\" use mm;\n"));
+    assert!(!formatted.contains("fn mm"));
+    // Definitions keep their order in the file
+    let positions: Vec<_> = [
+        "fn area",
+        "struct Circle",
+        "sketch Washer",
+        "impl Circle",
+        "fn double",
+        "sketch Main",
+    ]
+    .iter()
+    .map(|item| formatted.find(item).unwrap())
+    .collect();
+    assert!(positions.is_sorted());
+
+    // The output parses again next to the file it imports, and is stable
+    let mut reformat = run_with_stdin_in(&["parse", "--emit-source"], &formatted, "mixed");
+    assert!(reformat.status.success());
+    assert_eq!(String::from_utf8(reformat.stdout).unwrap(), formatted);
+    reformat = run_with_stdin_in(&["parse"], &formatted, "mixed");
+    assert!(reformat.status.success());
+}
+
+#[test]
+fn test_parse_emit_source_rejects_several_files() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .args(["parse", "--emit-source"])
+        .arg(fixture("multi_file/main.cad"))
+        .arg(fixture("multi_file/shapes.cad"))
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--emit-source formats a single file"));
+}
+
+#[test]
+fn test_parse_emit_sarif_reports_errors() {
+    let output = run_with_stdin(&["parse", "--emit-sarif", "-"], "sketch Main { let x = ; }");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"version\": \"2.1.0\""));
+    assert!(stdout.contains("\"ruleId\": \"parse-error\""));
+    assert!(stdout.contains("\"startLine\": 1, \"startColumn\": 23"));
+    // Standard input has no URI
+    assert!(stdout.contains("\"description\": { \"text\": \"standard input\" }"));
+    assert!(!stdout.contains("<stdin>"));
+}
+
+#[test]
+fn test_parse_emit_sarif_escapes_messages() {
+    let output = run_with_stdin(
+        &["parse", "--emit-sarif", "-"],
+        "import \"no\\\"such\\\\dir\\n.cad\";\nsketch Main {}",
+    );
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // The quote, backslash and newline of the path are escaped, keeping the
+    // message on one line of the log
+    assert!(
+        stdout.contains(
+            r#""message": { "text": "cannot find imported file 'no\"such\\dir\n.cad'" },"#
+        )
+    );
+}
+
+#[test]
+fn test_parse_emit_sarif_uses_relative_uris() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cad-dsl"))
+        .current_dir(fixture(""))
+        .args(["parse", "--emit-sarif", "errors/first.cad"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"artifactLocation\": { \"uri\": \"errors/first.cad\" }"));
+}
+
+#[test]
+fn test_version_subcommand() {
+    let output = run_with_stdin(&["version"], "");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some(concat!("cad-dsl ", env!("CARGO_PKG_VERSION")))
+    );
+    assert!(lines.next().unwrap().starts_with("commit "));
+}
+
+#[test]
+fn test_version_flag() {
+    let output = run_with_stdin(&["-V"], "");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout.trim(),
+        concat!("cad-dsl ", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn test_errors_end_with_version_footer() {
+    let output = run_with_stdin(&["parse"], "sketch Main { let x = ; }");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.trim_end().ends_with(concat!(
+        "error reported by cad-dsl ",
+        env!("CARGO_PKG_VERSION")
+    )));
+}